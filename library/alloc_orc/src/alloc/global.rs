@@ -0,0 +1,165 @@
+use super::{dangling, AllocError, Allocator, Layout};
+use std::alloc;
+use std::ptr::NonNull;
+
+/// The global memory allocator, i.e. `std::alloc::alloc` and friends.
+///
+/// This is the default allocator of the collections in this crate.
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Global;
+
+impl Global {
+    fn alloc_impl(&self, layout: Layout, zeroed: bool) -> Result<NonNull<[u8]>, AllocError> {
+        // `GlobalAlloc` doesn't allow zero-sized allocations.
+        if layout.size() == 0 {
+            return Ok(NonNull::slice_from_raw_parts(dangling(layout), 0));
+        }
+
+        // SAFETY:
+        // `layout` is not zero-sized.
+        let raw = unsafe {
+            if zeroed {
+                alloc::alloc_zeroed(layout)
+            } else {
+                alloc::alloc(layout)
+            }
+        };
+        let ptr = NonNull::new(raw).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    /// # Safety
+    ///
+    /// Same as `Allocator::grow`.
+    unsafe fn grow_impl(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+        zeroed: bool,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let old_size = old_layout.size();
+        let new_size = new_layout.size();
+
+        if old_size == 0 {
+            return self.alloc_impl(new_layout, zeroed);
+        }
+
+        if old_layout.align() != new_layout.align() {
+            // `realloc` can't change the alignment, so allocate a new block.
+            let new_ptr = self.alloc_impl(new_layout, zeroed)?;
+            std::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.cast().as_ptr(), old_size);
+            self.deallocate(ptr, old_layout);
+            return Ok(new_ptr);
+        }
+
+        let raw = alloc::realloc(ptr.as_ptr(), old_layout, new_size);
+        let new_ptr = NonNull::new(raw).ok_or(AllocError)?;
+        if zeroed {
+            raw.add(old_size).write_bytes(0, new_size - old_size);
+        }
+        Ok(NonNull::slice_from_raw_parts(new_ptr, new_size))
+    }
+}
+
+unsafe impl Allocator for Global {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc_impl(layout, false)
+    }
+
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.alloc_impl(layout, true)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // zero-sized blocks are dangling pointers, which were never allocated.
+        if layout.size() != 0 {
+            alloc::dealloc(ptr.as_ptr(), layout)
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.grow_impl(ptr, old_layout, new_layout, false)
+    }
+
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        self.grow_impl(ptr, old_layout, new_layout, true)
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_size = new_layout.size();
+
+        if new_size == 0 {
+            self.deallocate(ptr, old_layout);
+            return Ok(NonNull::slice_from_raw_parts(dangling(new_layout), 0));
+        }
+
+        if old_layout.align() != new_layout.align() {
+            let new_ptr = self.alloc_impl(new_layout, false)?;
+            std::ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.cast().as_ptr(), new_size);
+            self.deallocate(ptr, old_layout);
+            return Ok(new_ptr);
+        }
+
+        let raw = alloc::realloc(ptr.as_ptr(), old_layout, new_size);
+        let new_ptr = NonNull::new(raw).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(new_ptr, new_size))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_allocate_and_grow() {
+        let layout = Layout::array::<u32>(4).unwrap();
+        let ptr = Global.allocate_zeroed(layout).unwrap();
+        assert!(ptr.len() >= layout.size());
+
+        unsafe {
+            let p = ptr.cast::<u32>().as_ptr();
+            assert_eq!(*p.add(3), 0);
+            p.write(42);
+
+            let new_layout = Layout::array::<u32>(8).unwrap();
+            let ptr = Global.grow_zeroed(ptr.cast(), layout, new_layout).unwrap();
+            let p = ptr.cast::<u32>().as_ptr();
+            assert_eq!(*p, 42);
+            assert_eq!(*p.add(7), 0);
+
+            let small_layout = Layout::array::<u32>(1).unwrap();
+            let ptr = Global.shrink(ptr.cast(), new_layout, small_layout).unwrap();
+            assert_eq!(*ptr.cast::<u32>().as_ptr(), 42);
+
+            Global.deallocate(ptr.cast(), small_layout);
+        }
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        let layout = Layout::new::<()>();
+        let ptr = Global.allocate(layout).unwrap();
+        assert_eq!(ptr.len(), 0);
+        unsafe { Global.deallocate(ptr.cast(), layout) };
+
+        let layout = Layout::from_size_align(0, 16).unwrap();
+        let ptr = Global.allocate(layout).unwrap();
+        assert_eq!(ptr.cast::<u8>().as_ptr() as usize % 16, 0);
+    }
+}
@@ -0,0 +1,304 @@
+//! Memory allocation APIs.
+//!
+//! The collections of this crate take an `Allocator` as a type parameter,
+//! which is `Global` (the global allocator) by default.
+//!
+//! Resources:
+//! * https://doc.rust-lang.org/std/alloc/trait.Allocator.html
+//! * https://github.com/rust-lang/wg-allocators
+
+use std::fmt;
+use std::ptr::{self, NonNull};
+
+pub use std::alloc::{handle_alloc_error, Layout};
+
+mod global;
+mod tracking;
+
+pub use global::Global;
+pub use tracking::{Checkpoint, LeakGuard, Stats, TrackingAllocator};
+
+/// The error returned when an `Allocator` fails to allocate memory.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct AllocError;
+
+impl fmt::Display for AllocError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("memory allocation failed")
+    }
+}
+
+impl std::error::Error for AllocError {}
+
+/// An implementation of `Allocator` can allocate, grow, shrink, and
+/// deallocate blocks of memory described by a `Layout`.
+///
+/// Unlike `GlobalAlloc`, zero-sized allocations are allowed, and the returned
+/// block may be larger than requested (which is why it is `NonNull<[u8]>`).
+///
+/// # Safety
+///
+/// * A block returned by an allocator must stay valid until it is
+///   deallocated, or the allocator (and all its clones) is dropped.
+/// * Cloning or moving the allocator must not invalidate the blocks returned
+///   from it, and a clone must behave like the original.
+/// * Any block returned by an allocator can be passed to any other method of
+///   the same allocator (or its clones).
+pub unsafe trait Allocator {
+    /// Allocates a block of memory which fits `layout`.
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError>;
+
+    /// Same as `allocate`, but the returned memory is zero-initialized.
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.allocate(layout)?;
+        // SAFETY:
+        // `allocate` returns a block which is valid for writes of its length.
+        unsafe { ptr.cast::<u8>().as_ptr().write_bytes(0, ptr.len()) };
+        Ok(ptr)
+    }
+
+    /// Deallocates the memory referenced by `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must denote a block of memory currently allocated via this allocator.
+    /// * `layout` must fit that block of memory.
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout);
+
+    /// Extends the memory block. The contents up to `old_layout.size()` are
+    /// preserved, and `ptr` must not be used anymore if this succeeds.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must denote a block of memory currently allocated via this allocator.
+    /// * `old_layout` must fit that block of memory.
+    /// * `new_layout.size()` must be greater than or equal to `old_layout.size()`.
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(new_layout.size() >= old_layout.size());
+
+        let new_ptr = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.cast().as_ptr(), old_layout.size());
+        self.deallocate(ptr, old_layout);
+
+        Ok(new_ptr)
+    }
+
+    /// Same as `grow`, but the newly added memory is zero-initialized.
+    ///
+    /// # Safety
+    ///
+    /// Same as `grow`.
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        let new_ptr = self.grow(ptr, old_layout, new_layout)?;
+        let old_size = old_layout.size();
+        new_ptr
+            .cast::<u8>()
+            .as_ptr()
+            .add(old_size)
+            .write_bytes(0, new_ptr.len() - old_size);
+
+        Ok(new_ptr)
+    }
+
+    /// Shrinks the memory block. The contents up to `new_layout.size()` are
+    /// preserved, and `ptr` must not be used anymore if this succeeds.
+    ///
+    /// # Safety
+    ///
+    /// * `ptr` must denote a block of memory currently allocated via this allocator.
+    /// * `old_layout` must fit that block of memory.
+    /// * `new_layout.size()` must be smaller than or equal to `old_layout.size()`.
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(new_layout.size() <= old_layout.size());
+
+        let new_ptr = self.allocate(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.cast().as_ptr(), new_layout.size());
+        self.deallocate(ptr, old_layout);
+
+        Ok(new_ptr)
+    }
+
+    /// Creates a "by reference" adapter for this allocator.
+    fn by_ref(&self) -> &Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+}
+
+unsafe impl<A: Allocator + ?Sized> Allocator for &A {
+    #[track_caller]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).allocate(layout)
+    }
+
+    #[track_caller]
+    fn allocate_zeroed(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).allocate_zeroed(layout)
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        (**self).deallocate(ptr, layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).grow(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn grow_zeroed(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).grow_zeroed(ptr, old_layout, new_layout)
+    }
+
+    #[track_caller]
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        (**self).shrink(ptr, old_layout, new_layout)
+    }
+}
+
+/// Returns a dangling pointer which is well-aligned for `layout`, used for
+/// zero-sized allocations.
+pub(crate) fn dangling(layout: Layout) -> NonNull<u8> {
+    // SAFETY:
+    // alignment is never 0.
+    unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(layout.align())) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::bocs::Bocs;
+    use crate::rc::Rc;
+    use crate::vec::Vec;
+    use std::cell::Cell;
+
+    /// An allocator which counts the number of live allocations.
+    #[derive(Default)]
+    struct Counting {
+        live: Cell<usize>,
+        total: Cell<usize>,
+    }
+
+    unsafe impl Allocator for Counting {
+        fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+            self.live.set(self.live.get() + 1);
+            self.total.set(self.total.get() + 1);
+            Global.allocate(layout)
+        }
+
+        unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+            self.live.set(self.live.get() - 1);
+            Global.deallocate(ptr, layout)
+        }
+    }
+
+    #[test]
+    fn test_vec_in() {
+        let counting = Counting::default();
+        {
+            let mut v = Vec::new_in(&counting);
+            assert_eq!(counting.total.get(), 0);
+
+            for i in 0..100 {
+                v.push(i);
+            }
+            assert_eq!(counting.live.get(), 1);
+            assert!(counting.total.get() > 1);
+            assert_eq!(v.as_slice(), (0..100).collect::<std::vec::Vec<_>>());
+        }
+        assert_eq!(counting.live.get(), 0);
+
+        let v = Vec::<u64, _>::with_capacity_in(10, &counting);
+        assert_eq!(counting.live.get(), 1);
+        assert!(v.capacity() >= 10);
+        drop(v);
+        assert_eq!(counting.live.get(), 0);
+    }
+
+    #[test]
+    fn test_vec_into_iter_in() {
+        use core_orc::{into_iterator::IntoIterator, iter::traits::iterator::Iterator};
+
+        let counting = Counting::default();
+        let mut v = Vec::new_in(&counting);
+        v.push(1);
+        v.push(2);
+
+        let mut iter = v.into_iter();
+        assert_eq!(iter.next(), Some(1));
+        drop(iter);
+        assert_eq!(counting.live.get(), 0);
+    }
+
+    #[test]
+    fn test_bocs_and_rc_in() {
+        let counting = Counting::default();
+
+        let b = Bocs::new_in(42, &counting);
+        assert_eq!(*b, 42);
+        assert_eq!(counting.live.get(), 1);
+        drop(b);
+        assert_eq!(counting.live.get(), 0);
+
+        let rc = Rc::new_in(String::from("rc"), &counting);
+        let rc2 = rc.clone();
+        assert_eq!(counting.live.get(), 1);
+        drop(rc);
+        assert_eq!(rc2.as_ref(), "rc");
+        drop(rc2);
+        assert_eq!(counting.live.get(), 0);
+    }
+
+    #[test]
+    fn test_grow_default_via_allocate() {
+        let counting = Counting::default();
+        unsafe {
+            let old = Layout::array::<u8>(4).unwrap();
+            let ptr = counting.allocate_zeroed(old).unwrap();
+            ptr.cast::<u8>().as_ptr().write(7);
+
+            let new = Layout::array::<u8>(16).unwrap();
+            let ptr = counting.grow_zeroed(ptr.cast(), old, new).unwrap();
+            assert_eq!(*ptr.cast::<u8>().as_ptr(), 7);
+            assert_eq!(*ptr.cast::<u8>().as_ptr().add(15), 0);
+            assert_eq!(counting.live.get(), 1);
+
+            let ptr = counting.shrink(ptr.cast(), new, old).unwrap();
+            assert_eq!(*ptr.cast::<u8>().as_ptr(), 7);
+            counting.deallocate(ptr.cast(), old);
+        }
+        assert_eq!(counting.live.get(), 0);
+    }
+}
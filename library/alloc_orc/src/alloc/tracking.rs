@@ -0,0 +1,466 @@
+use super::{dangling, AllocError, Allocator, Layout};
+use std::alloc::{GlobalAlloc, System};
+use std::cell::Cell;
+use std::fmt;
+use std::panic::Location;
+use std::ptr::{self, NonNull};
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+/// The maximum number of distinct call sites which are recorded.
+const MAX_SITES: usize = 64;
+
+/// Statistics of the allocations made through a `TrackingAllocator`.
+#[derive(Copy, Clone, PartialEq, Eq, Default, Debug)]
+pub struct Stats {
+    /// The number of allocations made so far.
+    pub allocations: usize,
+    /// The number of deallocations made so far.
+    pub deallocations: usize,
+    /// The number of bytes currently allocated.
+    pub live_bytes: usize,
+    /// The largest value `live_bytes` has ever reached.
+    pub peak_bytes: usize,
+}
+
+impl Stats {
+    /// Returns the number of allocations which are not deallocated yet.
+    pub fn live_allocations(&self) -> usize {
+        self.allocations - self.deallocations
+    }
+}
+
+/// The statistics of the current thread, which are kept separately from the
+/// ones of the allocator, so that tests running in parallel don't see each
+/// other's allocations.
+struct ThreadStats {
+    allocations: Cell<usize>,
+    deallocations: Cell<usize>,
+    live_bytes: Cell<isize>,
+}
+
+thread_local! {
+    // const-initialized and without destructor, so it never allocates.
+    static THREAD_STATS: ThreadStats = const {
+        ThreadStats {
+            allocations: Cell::new(0),
+            deallocations: Cell::new(0),
+            live_bytes: Cell::new(0),
+        }
+    };
+}
+
+/// An allocation site, recorded via `#[track_caller]`.
+struct Site {
+    location: AtomicPtr<Location<'static>>,
+    count: AtomicUsize,
+}
+
+impl Site {
+    const fn new() -> Self {
+        Site {
+            location: AtomicPtr::new(ptr::null_mut()),
+            count: AtomicUsize::new(0),
+        }
+    }
+}
+
+/// An allocator which wraps the system allocator, and records the number of
+/// live allocations and bytes, the peak usage, and the number of allocations
+/// per call site.
+///
+/// It can be used both as `#[global_allocator]` and as the `Allocator` of the
+/// collections of this crate. Call sites are only recorded for the latter,
+/// since the caller of the global allocator is always inside `std`.
+///
+/// ```
+/// use alloc_orc::alloc::TrackingAllocator;
+/// use alloc_orc::vec::Vec;
+///
+/// let tracking = TrackingAllocator::new();
+/// let checkpoint = TrackingAllocator::checkpoint();
+///
+/// let mut v = Vec::new_in(&tracking);
+/// v.push(1);
+/// assert_eq!(tracking.stats().live_allocations(), 1);
+///
+/// drop(v);
+/// assert_eq!(tracking.stats().live_allocations(), 0);
+/// checkpoint.assert_no_leaks();
+/// ```
+pub struct TrackingAllocator {
+    allocations: AtomicUsize,
+    deallocations: AtomicUsize,
+    live_bytes: AtomicUsize,
+    peak_bytes: AtomicUsize,
+    sites: [Site; MAX_SITES],
+}
+
+impl TrackingAllocator {
+    pub const fn new() -> Self {
+        TrackingAllocator {
+            allocations: AtomicUsize::new(0),
+            deallocations: AtomicUsize::new(0),
+            live_bytes: AtomicUsize::new(0),
+            peak_bytes: AtomicUsize::new(0),
+            sites: [const { Site::new() }; MAX_SITES],
+        }
+    }
+
+    /// Returns the statistics of all the allocations made through this allocator.
+    pub fn stats(&self) -> Stats {
+        Stats {
+            allocations: self.allocations.load(Ordering::Relaxed),
+            deallocations: self.deallocations.load(Ordering::Relaxed),
+            live_bytes: self.live_bytes.load(Ordering::Relaxed),
+            peak_bytes: self.peak_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Returns the number of allocations made from each call site, in the
+    /// order the sites were first seen.
+    pub fn sites(&self) -> std::vec::Vec<(&'static Location<'static>, usize)> {
+        self.sites
+            .iter()
+            .map_while(|site| {
+                let location = site.location.load(Ordering::Acquire);
+                // SAFETY:
+                // only `&'static Location` is stored.
+                let location = unsafe { location.as_ref()? };
+                Some((location, site.count.load(Ordering::Relaxed)))
+            })
+            .collect()
+    }
+
+    /// Returns the number of allocations made from `location`.
+    pub fn site_count(&self, location: &Location<'_>) -> usize {
+        self.sites()
+            .into_iter()
+            .filter(|(l, _)| *l == location)
+            .map(|(_, count)| count)
+            .sum()
+    }
+
+    /// Records the current state of the current thread, which is used to
+    /// check that no allocation made after this is leaked.
+    ///
+    /// The allocations made through any `TrackingAllocator` are counted.
+    pub fn checkpoint() -> Checkpoint {
+        Checkpoint {
+            start: thread_snapshot(),
+        }
+    }
+
+    fn record_alloc(&self, size: usize) {
+        self.allocations.fetch_add(1, Ordering::Relaxed);
+        let live = self.live_bytes.fetch_add(size, Ordering::Relaxed) + size;
+        self.peak_bytes.fetch_max(live, Ordering::Relaxed);
+
+        let _ = THREAD_STATS.try_with(|s| {
+            s.allocations.set(s.allocations.get() + 1);
+            s.live_bytes.set(s.live_bytes.get() + size as isize);
+        });
+    }
+
+    fn record_dealloc(&self, size: usize) {
+        self.deallocations.fetch_add(1, Ordering::Relaxed);
+        self.live_bytes.fetch_sub(size, Ordering::Relaxed);
+
+        let _ = THREAD_STATS.try_with(|s| {
+            s.deallocations.set(s.deallocations.get() + 1);
+            s.live_bytes.set(s.live_bytes.get() - size as isize);
+        });
+    }
+
+    fn record_realloc(&self, old_size: usize, new_size: usize) {
+        if new_size >= old_size {
+            let diff = new_size - old_size;
+            let live = self.live_bytes.fetch_add(diff, Ordering::Relaxed) + diff;
+            self.peak_bytes.fetch_max(live, Ordering::Relaxed);
+        } else {
+            self.live_bytes
+                .fetch_sub(old_size - new_size, Ordering::Relaxed);
+        }
+
+        let _ = THREAD_STATS.try_with(|s| {
+            s.live_bytes
+                .set(s.live_bytes.get() + new_size as isize - old_size as isize);
+        });
+    }
+
+    fn record_site(&self, location: &'static Location<'static>) {
+        let location = location as *const Location<'static> as *mut Location<'static>;
+        for site in &self.sites {
+            let current = match site.location.compare_exchange(
+                ptr::null_mut(),
+                location,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => location,
+                Err(current) => current,
+            };
+            // SAFETY:
+            // both are `&'static Location`.
+            if current == location || unsafe { *current == *location } {
+                site.count.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+        }
+        // the table is full, so this site is not recorded.
+    }
+}
+
+impl Default for TrackingAllocator {
+    fn default() -> Self {
+        TrackingAllocator::new()
+    }
+}
+
+impl fmt::Debug for TrackingAllocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrackingAllocator")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            self.record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            self.record_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        self.record_dealloc(layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            self.record_realloc(layout.size(), new_size);
+        }
+        new_ptr
+    }
+}
+
+unsafe impl Allocator for TrackingAllocator {
+    #[track_caller]
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        self.record_site(Location::caller());
+
+        if layout.size() == 0 {
+            return Ok(NonNull::slice_from_raw_parts(dangling(layout), 0));
+        }
+
+        // SAFETY:
+        // `layout` is not zero-sized.
+        let ptr = NonNull::new(unsafe { GlobalAlloc::alloc(self, layout) }).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            GlobalAlloc::dealloc(self, ptr.as_ptr(), layout)
+        }
+    }
+
+    #[track_caller]
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        if old_layout.size() == 0 || old_layout.align() != new_layout.align() {
+            let new_ptr = self.allocate(new_layout)?;
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.cast().as_ptr(), old_layout.size());
+            self.deallocate(ptr, old_layout);
+            return Ok(new_ptr);
+        }
+
+        let raw = GlobalAlloc::realloc(self, ptr.as_ptr(), old_layout, new_layout.size());
+        let new_ptr = NonNull::new(raw).ok_or(AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(new_ptr, new_layout.size()))
+    }
+}
+
+/// The counts of the current thread. The thread may deallocate memory
+/// allocated by other threads, so `live_bytes` can be negative.
+#[derive(Copy, Clone, Default, Debug)]
+struct ThreadSnapshot {
+    allocations: usize,
+    deallocations: usize,
+    live_bytes: isize,
+}
+
+fn thread_snapshot() -> ThreadSnapshot {
+    THREAD_STATS
+        .try_with(|s| ThreadSnapshot {
+            allocations: s.allocations.get(),
+            deallocations: s.deallocations.get(),
+            live_bytes: s.live_bytes.get(),
+        })
+        .unwrap_or_default()
+}
+
+/// A snapshot of the allocations of the current thread, created by
+/// `TrackingAllocator::checkpoint`.
+#[derive(Debug)]
+pub struct Checkpoint {
+    start: ThreadSnapshot,
+}
+
+impl Checkpoint {
+    /// Returns the number of allocations made since the checkpoint, which
+    /// are not deallocated yet.
+    pub fn leaked_allocations(&self) -> isize {
+        let now = thread_snapshot();
+        let allocations = now.allocations - self.start.allocations;
+        let deallocations = now.deallocations - self.start.deallocations;
+        allocations as isize - deallocations as isize
+    }
+
+    /// Returns the number of bytes allocated since the checkpoint, which are
+    /// not deallocated yet.
+    pub fn leaked_bytes(&self) -> isize {
+        thread_snapshot().live_bytes - self.start.live_bytes
+    }
+
+    /// Returns the number of allocations made since the checkpoint.
+    pub fn allocations(&self) -> usize {
+        thread_snapshot().allocations - self.start.allocations
+    }
+
+    /// Panics if some allocation made since the checkpoint is not deallocated.
+    #[track_caller]
+    pub fn assert_no_leaks(&self) {
+        let allocations = self.leaked_allocations();
+        let bytes = self.leaked_bytes();
+        assert!(
+            allocations <= 0 && bytes <= 0,
+            "{} allocations ({} bytes) leaked since the checkpoint",
+            allocations,
+            bytes
+        );
+    }
+}
+
+/// Asserts that no allocation made by the current thread while it's alive is
+/// leaked, when it's dropped.
+///
+/// The check is skipped if the thread is panicking.
+#[derive(Debug)]
+pub struct LeakGuard {
+    checkpoint: Checkpoint,
+}
+
+impl LeakGuard {
+    pub fn new() -> Self {
+        LeakGuard {
+            checkpoint: TrackingAllocator::checkpoint(),
+        }
+    }
+}
+
+impl Default for LeakGuard {
+    fn default() -> Self {
+        LeakGuard::new()
+    }
+}
+
+impl Drop for LeakGuard {
+    fn drop(&mut self) {
+        if !std::thread::panicking() {
+            self.checkpoint.assert_no_leaks();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::vec::Vec;
+
+    #[test]
+    fn test_stats() {
+        let tracking = TrackingAllocator::new();
+        let mut v = Vec::new_in(&tracking);
+        for i in 0..100u64 {
+            v.push(i);
+        }
+
+        let stats = tracking.stats();
+        assert_eq!(stats.live_allocations(), 1);
+        assert_eq!(stats.live_bytes, v.capacity() * 8);
+        assert!(stats.peak_bytes >= stats.live_bytes);
+
+        drop(v);
+        let stats = tracking.stats();
+        assert_eq!(stats.live_allocations(), 0);
+        assert_eq!(stats.live_bytes, 0);
+        assert!(stats.peak_bytes >= 100 * 8);
+    }
+
+    #[test]
+    fn test_sites() {
+        let tracking = TrackingAllocator::new();
+        let layout = Layout::new::<u8>();
+
+        for _ in 0..3 {
+            let ptr = tracking.allocate(layout).unwrap();
+            unsafe { tracking.deallocate(ptr.cast(), layout) };
+        }
+        // through a reference, from the inside of `Vec`.
+        let mut v = Vec::new_in(&tracking);
+        v.push(1);
+
+        let sites = tracking.sites();
+        assert_eq!(sites.len(), 2);
+        assert_eq!(sites[0].1, 3);
+        assert_eq!(sites[1].1, 1);
+        assert!(sites[0].0.file().ends_with("tracking.rs"));
+        assert!(sites[1].0.file().contains("vec"));
+        assert_eq!(tracking.site_count(sites[0].0), 3);
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let tracking = TrackingAllocator::new();
+        let checkpoint = TrackingAllocator::checkpoint();
+
+        let v = crate::vec![1, 2, 3];
+        let mut w = Vec::new_in(&tracking);
+        w.push(1);
+        assert_eq!(checkpoint.leaked_allocations(), 2);
+        assert!(checkpoint.allocations() >= 2);
+
+        drop(v);
+        drop(w);
+        checkpoint.assert_no_leaks();
+    }
+
+    #[test]
+    #[should_panic = "1 allocations"]
+    fn test_checkpoint_leak() {
+        let tracking = TrackingAllocator::new();
+        let checkpoint = TrackingAllocator::checkpoint();
+
+        let mut w = Vec::new_in(&tracking);
+        w.push(1);
+        std::mem::forget(w);
+
+        checkpoint.assert_no_leaks();
+    }
+}
@@ -0,0 +1,463 @@
+use crate::alloc::{dangling, handle_alloc_error, AllocError, Allocator, Global, Layout};
+use std::cell::Cell;
+use std::mem;
+use std::ptr::{self, NonNull};
+
+/// The size of the first chunk, including its header.
+const DEFAULT_CHUNK_SIZE: usize = 1024;
+
+/// Placed at the start of each chunk. Chunks form a linked list from the
+/// newest one to the oldest one.
+struct ChunkHeader {
+    prev: Option<NonNull<ChunkHeader>>,
+    layout: Layout,
+}
+
+/// A bump allocator.
+///
+/// Memory is allocated from chunks by just moving a pointer forward, and a
+/// new chunk (twice as large as the last one) is allocated when the current
+/// one is full. Each allocation is never freed individually; all the chunks
+/// are freed when the `Bump` is dropped.
+///
+/// Values allocated by `alloc` are never dropped. Use `TypedArena` if you
+/// need destructors to run.
+pub struct Bump {
+    /// The next free byte in the current chunk.
+    ptr: Cell<*mut u8>,
+    /// One past the last byte of the current chunk.
+    end: Cell<*mut u8>,
+    /// The current (newest) chunk.
+    chunk: Cell<Option<NonNull<ChunkHeader>>>,
+    /// The total size of the chunks.
+    allocated_bytes: Cell<usize>,
+}
+
+impl Bump {
+    /// Creates an empty `Bump`. This doesn't allocate until the first allocation.
+    pub fn new() -> Self {
+        Bump {
+            ptr: Cell::new(ptr::null_mut()),
+            end: Cell::new(ptr::null_mut()),
+            chunk: Cell::new(None),
+            allocated_bytes: Cell::new(0),
+        }
+    }
+
+    /// Creates a `Bump` which can allocate at least `capacity` bytes
+    /// without allocating a new chunk.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let bump = Bump::new();
+        if capacity > 0 {
+            let layout = Layout::from_size_align(capacity, 1).unwrap();
+            if bump.alloc_chunk(layout).is_err() {
+                handle_alloc_error(layout);
+            }
+        }
+        bump
+    }
+
+    /// Returns the total size of the chunks allocated by this `Bump`.
+    pub fn allocated_bytes(&self) -> usize {
+        self.allocated_bytes.get()
+    }
+
+    /// Allocates `val` in the arena, and returns a mutable reference to it.
+    ///
+    /// `val` is never dropped.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc<T>(&self, val: T) -> &mut T {
+        let ptr = self.alloc_layout(Layout::new::<T>()).cast::<T>();
+        // SAFETY:
+        // `ptr` is valid for writes and aligned, and nobody else refers to it.
+        unsafe {
+            ptr::write(ptr.as_ptr(), val);
+            &mut *ptr.as_ptr()
+        }
+    }
+
+    /// Copies `src` into the arena.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_slice<T: Copy>(&self, src: &[T]) -> &mut [T] {
+        let ptr = self.alloc_layout(Layout::for_value(src)).cast::<T>();
+        // SAFETY:
+        // `ptr` is valid for writes of `src.len()` elements, and doesn't
+        // overlap with `src`.
+        unsafe {
+            ptr::copy_nonoverlapping(src.as_ptr(), ptr.as_ptr(), src.len());
+            std::slice::from_raw_parts_mut(ptr.as_ptr(), src.len())
+        }
+    }
+
+    /// Copies `src` into the arena.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_str(&self, src: &str) -> &mut str {
+        let bytes = self.alloc_slice(src.as_bytes());
+        // SAFETY:
+        // the bytes are copied from a valid `str`.
+        unsafe { std::str::from_utf8_unchecked_mut(bytes) }
+    }
+
+    /// Allocates a block of memory which fits `layout`.
+    ///
+    /// Calls `handle_alloc_error` if the allocation fails.
+    pub fn alloc_layout(&self, layout: Layout) -> NonNull<u8> {
+        match self.try_alloc_layout(layout) {
+            Ok(ptr) => ptr,
+            Err(_) => handle_alloc_error(layout),
+        }
+    }
+
+    /// Same as `alloc_layout`, but returns an error if the allocation fails.
+    pub fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, AllocError> {
+        if layout.size() == 0 {
+            return Ok(dangling(layout));
+        }
+
+        if let Some(ptr) = self.alloc_in_current_chunk(layout) {
+            return Ok(ptr);
+        }
+
+        self.alloc_chunk(layout)?;
+        // the new chunk is large enough for `layout`.
+        Ok(self.alloc_in_current_chunk(layout).unwrap())
+    }
+
+    /// Frees all the chunks but the current one, and makes its whole memory
+    /// available again.
+    ///
+    /// This takes `&mut self`, so no reference to the allocated values is alive.
+    pub fn reset(&mut self) {
+        let Some(chunk) = self.chunk.get() else {
+            return;
+        };
+
+        // SAFETY:
+        // all the chunks were allocated by `alloc_chunk`, and nothing refers
+        // to them anymore.
+        unsafe {
+            let prev = (*chunk.as_ptr()).prev.take();
+            free_chunks(prev);
+
+            let layout = (*chunk.as_ptr()).layout;
+            self.ptr.set(chunk_start(chunk));
+            self.allocated_bytes.set(layout.size());
+        }
+    }
+
+    /// Bumps the pointer of the current chunk, if `layout` fits in it.
+    fn alloc_in_current_chunk(&self, layout: Layout) -> Option<NonNull<u8>> {
+        let ptr = self.ptr.get();
+        let end = self.end.get();
+        if ptr.is_null() {
+            return None;
+        }
+
+        let offset = ptr.align_offset(layout.align());
+        let available = end as usize - ptr as usize;
+        if offset > available || layout.size() > available - offset {
+            return None;
+        }
+
+        // SAFETY:
+        // `offset + layout.size()` is within the current chunk.
+        unsafe {
+            let start = ptr.add(offset);
+            self.ptr.set(start.add(layout.size()));
+            Some(NonNull::new_unchecked(start))
+        }
+    }
+
+    /// Allocates a new chunk which can hold `layout`, and makes it current.
+    fn alloc_chunk(&self, layout: Layout) -> Result<(), AllocError> {
+        let header_size = mem::size_of::<ChunkHeader>();
+        let align = layout.align().max(mem::align_of::<ChunkHeader>());
+
+        // reserve enough space for the header, `layout`, and the padding between them.
+        let required = header_size
+            .checked_add(layout.align() - 1)
+            .and_then(|s| s.checked_add(layout.size()))
+            .ok_or(AllocError)?;
+        let doubled = match self.chunk.get() {
+            // SAFETY:
+            // the current chunk is alive.
+            Some(chunk) => unsafe { (*chunk.as_ptr()).layout.size().saturating_mul(2) },
+            None => DEFAULT_CHUNK_SIZE,
+        };
+        let size = required.max(doubled);
+
+        let chunk_layout = Layout::from_size_align(size, align).map_err(|_| AllocError)?;
+        let chunk = Global.allocate(chunk_layout)?.cast::<ChunkHeader>();
+
+        // SAFETY:
+        // `chunk` is valid for writes, and aligned for `ChunkHeader`.
+        unsafe {
+            ptr::write(
+                chunk.as_ptr(),
+                ChunkHeader {
+                    prev: self.chunk.get(),
+                    layout: chunk_layout,
+                },
+            );
+            self.ptr.set(chunk_start(chunk));
+            self.end.set(chunk.as_ptr().cast::<u8>().add(size));
+        }
+        self.chunk.set(Some(chunk));
+        self.allocated_bytes.set(self.allocated_bytes.get() + size);
+
+        Ok(())
+    }
+
+    /// Returns `true` if `ptr` with `layout` is the last allocation.
+    fn is_last(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        ptr.as_ptr().wrapping_add(layout.size()) == self.ptr.get()
+    }
+}
+
+impl Default for Bump {
+    fn default() -> Self {
+        Bump::new()
+    }
+}
+
+impl Drop for Bump {
+    fn drop(&mut self) {
+        // SAFETY:
+        // all the chunks were allocated by `alloc_chunk`, and the allocated
+        // values can't outlive `self`.
+        unsafe { free_chunks(self.chunk.get()) }
+    }
+}
+
+/// Returns the first byte after the header of `chunk`.
+///
+/// # Safety
+///
+/// `chunk` must be a live chunk allocated by `Bump::alloc_chunk`.
+unsafe fn chunk_start(chunk: NonNull<ChunkHeader>) -> *mut u8 {
+    chunk
+        .as_ptr()
+        .cast::<u8>()
+        .add(mem::size_of::<ChunkHeader>())
+}
+
+/// Frees `chunk` and all the chunks older than it.
+///
+/// # Safety
+///
+/// The chunks must have been allocated by `Bump::alloc_chunk`, and must not
+/// be used anymore.
+unsafe fn free_chunks(mut chunk: Option<NonNull<ChunkHeader>>) {
+    while let Some(c) = chunk {
+        let ChunkHeader { prev, layout } = ptr::read(c.as_ptr());
+        Global.deallocate(c.cast(), layout);
+        chunk = prev;
+    }
+}
+
+// Each block is valid until the `Bump` is dropped or reset, and both of them
+// require that no reference to the `Bump` (including the ones held by the
+// collections using it) is alive.
+unsafe impl Allocator for Bump {
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.try_alloc_layout(layout)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+        // only the last allocation can be reused.
+        if layout.size() != 0 && self.is_last(ptr, layout) {
+            self.ptr.set(ptr.as_ptr());
+        }
+    }
+
+    unsafe fn grow(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(new_layout.size() >= old_layout.size());
+
+        // grow in place, if this is the last allocation and the chunk has enough room.
+        if old_layout.size() != 0
+            && self.is_last(ptr, old_layout)
+            && ptr.as_ptr().align_offset(new_layout.align()) == 0
+        {
+            let available = self.end.get() as usize - ptr.as_ptr() as usize;
+            if new_layout.size() <= available {
+                self.ptr.set(ptr.as_ptr().add(new_layout.size()));
+                return Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()));
+            }
+        }
+
+        let new_ptr = self.try_alloc_layout(new_layout)?;
+        ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), old_layout.size());
+        Ok(NonNull::slice_from_raw_parts(new_ptr, new_layout.size()))
+    }
+
+    unsafe fn shrink(
+        &self,
+        ptr: NonNull<u8>,
+        old_layout: Layout,
+        new_layout: Layout,
+    ) -> Result<NonNull<[u8]>, AllocError> {
+        debug_assert!(new_layout.size() <= old_layout.size());
+
+        if ptr.as_ptr().align_offset(new_layout.align()) != 0 {
+            let new_ptr = self.try_alloc_layout(new_layout)?;
+            ptr::copy_nonoverlapping(ptr.as_ptr(), new_ptr.as_ptr(), new_layout.size());
+            return Ok(NonNull::slice_from_raw_parts(new_ptr, new_layout.size()));
+        }
+
+        // give the rest back, if this is the last allocation.
+        if old_layout.size() != 0 && self.is_last(ptr, old_layout) {
+            self.ptr.set(ptr.as_ptr().add(new_layout.size()));
+        }
+        Ok(NonNull::slice_from_raw_parts(ptr, new_layout.size()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alloc::LeakGuard;
+    use crate::vec::Vec;
+
+    #[test]
+    fn test_alloc() {
+        let _leaks = LeakGuard::new();
+        let bump = Bump::new();
+        assert_eq!(bump.allocated_bytes(), 0);
+
+        let a = bump.alloc(1u8);
+        let b = bump.alloc(2u64);
+        let c = bump.alloc([3u16; 3]);
+        *a += 10;
+
+        assert_eq!(*a, 11);
+        assert_eq!(*b, 2);
+        assert_eq!(*c, [3, 3, 3]);
+        assert_eq!(b as *mut u64 as usize % mem::align_of::<u64>(), 0);
+        assert_eq!(bump.allocated_bytes(), DEFAULT_CHUNK_SIZE);
+    }
+
+    #[test]
+    fn test_alloc_slice_and_str() {
+        let _leaks = LeakGuard::new();
+        let bump = Bump::new();
+
+        let s = bump.alloc_slice(&[1, 2, 3]);
+        s[0] = 10;
+        assert_eq!(s, &[10, 2, 3]);
+
+        let st = bump.alloc_str("hello");
+        st.make_ascii_uppercase();
+        assert_eq!(st, "HELLO");
+
+        assert!(bump.alloc_slice::<u32>(&[]).is_empty());
+        assert_eq!(bump.alloc_str(""), "");
+    }
+
+    #[test]
+    fn test_alloc_zero_sized() {
+        let _leaks = LeakGuard::new();
+        let bump = Bump::new();
+        let unit = bump.alloc(());
+        assert_eq!(*unit, ());
+        assert_eq!(bump.allocated_bytes(), 0);
+    }
+
+    #[test]
+    fn test_new_chunks() {
+        let _leaks = LeakGuard::new();
+        let bump = Bump::new();
+        let mut refs = std::vec::Vec::new();
+        for i in 0..1000u64 {
+            refs.push(bump.alloc(i));
+        }
+        assert!(bump.allocated_bytes() > DEFAULT_CHUNK_SIZE);
+
+        // values in the older chunks are still alive.
+        for (i, r) in refs.iter().enumerate() {
+            assert_eq!(**r, i as u64);
+        }
+
+        // an allocation larger than the doubled chunk size.
+        let big = bump.alloc([7u8; 100_000]);
+        assert_eq!(big[99_999], 7);
+    }
+
+    #[test]
+    fn test_with_capacity_and_reset() {
+        let _leaks = LeakGuard::new();
+        let mut bump = Bump::with_capacity(4096);
+        let allocated = bump.allocated_bytes();
+        assert!(allocated >= 4096);
+
+        for i in 0..512u64 {
+            bump.alloc(i);
+        }
+        assert_eq!(bump.allocated_bytes(), allocated);
+
+        bump.alloc([0u8; 10_000]);
+        let before_reset = bump.allocated_bytes();
+        assert!(before_reset > allocated);
+
+        // only the newest chunk is kept.
+        bump.reset();
+        let after_reset = bump.allocated_bytes();
+        assert_eq!(after_reset, before_reset - allocated);
+
+        // the memory of the current chunk is reused.
+        let p1 = bump.alloc(1u8) as *mut u8;
+        bump.reset();
+        let p2 = bump.alloc(2u8) as *mut u8;
+        assert_eq!(p1, p2);
+        assert_eq!(bump.allocated_bytes(), after_reset);
+    }
+
+    #[test]
+    fn test_vec_in_bump() {
+        let _leaks = LeakGuard::new();
+        let bump = Bump::new();
+        let mut v = Vec::new_in(&bump);
+        for i in 0..100 {
+            v.push(i);
+        }
+        assert_eq!(v.as_slice(), (0..100).collect::<std::vec::Vec<_>>());
+
+        let mut w = Vec::new_in(&bump);
+        w.push("a");
+        v.push(100);
+        w.push("b");
+        assert_eq!(v.len(), 101);
+        assert_eq!(w.as_slice(), &["a", "b"]);
+    }
+
+    #[test]
+    fn test_grow_in_place() {
+        let _leaks = LeakGuard::new();
+        let bump = Bump::new();
+        unsafe {
+            let old = Layout::array::<u32>(2).unwrap();
+            let ptr = bump.allocate(old).unwrap().cast::<u8>();
+            let new = Layout::array::<u32>(8).unwrap();
+            let grown = bump.grow(ptr, old, new).unwrap();
+            assert_eq!(grown.cast::<u8>(), ptr);
+
+            // not the last allocation anymore, so it's copied.
+            bump.alloc(0u8);
+            ptr.cast::<u32>().as_ptr().write(42);
+            let newer = Layout::array::<u32>(16).unwrap();
+            let moved = bump.grow(ptr, new, newer).unwrap().cast::<u32>();
+            assert_ne!(moved.cast::<u8>(), ptr);
+            assert_eq!(*moved.as_ptr(), 42);
+
+            // deallocating the last allocation makes its memory reusable.
+            bump.deallocate(moved.cast(), newer);
+            let again = bump.allocate(newer).unwrap().cast::<u32>();
+            assert_eq!(again, moved);
+        }
+    }
+}
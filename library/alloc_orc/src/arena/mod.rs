@@ -0,0 +1,20 @@
+//! Arena allocators.
+//!
+//! An arena hands out many short-lived allocations, and frees them all at
+//! once when it's dropped (or reset), instead of freeing each of them.
+//!
+//! * `Bump` is a bump allocator for values of any type. It doesn't run
+//!   destructors, but implements `Allocator`, so collections like
+//!   `Vec::new_in(&bump)` can be backed by it.
+//! * `TypedArena<T>` allocates values of a single type, and drops them
+//!   when the arena is dropped.
+//!
+//! Resources:
+//! * https://docs.rs/bumpalo
+//! * https://github.com/rust-lang/rust/blob/master/compiler/rustc_arena/src/lib.rs
+
+mod bump;
+mod typed;
+
+pub use bump::Bump;
+pub use typed::TypedArena;
@@ -0,0 +1,216 @@
+use crate::vec::Vec;
+use core_orc::iter::traits::iterator::Iterator;
+use std::cell::UnsafeCell;
+use std::ptr;
+
+/// The number of elements of the first chunk.
+const DEFAULT_CHUNK_LEN: usize = 8;
+
+/// An arena of values of type `T`.
+///
+/// Unlike `Bump`, the values are dropped when the arena is dropped.
+pub struct TypedArena<T> {
+    /// Each chunk is a `Vec` which never grows beyond its initial capacity,
+    /// so the elements never move.
+    chunks: UnsafeCell<Vec<Vec<T>>>,
+}
+
+impl<T> TypedArena<T> {
+    pub fn new() -> Self {
+        TypedArena {
+            chunks: UnsafeCell::new(Vec::new()),
+        }
+    }
+
+    /// Creates an arena which can hold `capacity` values without allocating
+    /// a new chunk.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut chunks = Vec::new();
+        if capacity > 0 {
+            chunks.push(Vec::with_capacity(capacity));
+        }
+        TypedArena {
+            chunks: UnsafeCell::new(chunks),
+        }
+    }
+
+    /// Returns the number of values allocated in the arena.
+    pub fn len(&self) -> usize {
+        // SAFETY:
+        // no mutable reference to `chunks` is alive outside of `chunk_with_room`.
+        let chunks = unsafe { &*self.chunks.get() };
+        chunks.iter().map(|c| c.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Moves `val` into the arena, and returns a mutable reference to it.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc(&self, val: T) -> &mut T {
+        let chunk = self.chunk_with_room(1);
+        // SAFETY:
+        // `chunk` has room for `val`, so `push` doesn't reallocate, and the
+        // slot is never handed out again.
+        unsafe {
+            let slot = chunk.as_mut_ptr().add(chunk.len());
+            chunk.push(val);
+            &mut *slot
+        }
+    }
+
+    /// Moves all the values of `iter` into the arena, and returns them as a
+    /// contiguous slice.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_extend<I: Iterator<Item = T>>(&self, iter: I) -> &mut [T] {
+        // collect them first, since the iterator may use this arena.
+        let mut values: Vec<T> = iter.collect();
+        let n = values.len();
+        if n == 0 {
+            return &mut [];
+        }
+
+        let chunk = self.chunk_with_room(n);
+        // SAFETY:
+        // `chunk` has room for `n` values, and the values are moved (not
+        // copied) since `values` is emptied before being dropped.
+        unsafe {
+            let start = chunk.len();
+            let dst = chunk.as_mut_ptr().add(start);
+            ptr::copy_nonoverlapping(values.as_ptr(), dst, n);
+            values.set_len(0);
+            chunk.set_len(start + n);
+            std::slice::from_raw_parts_mut(dst, n)
+        }
+    }
+
+    /// Returns a chunk which has room for `n` more values, allocating a new
+    /// one if needed.
+    #[allow(clippy::mut_from_ref)]
+    fn chunk_with_room(&self, n: usize) -> &mut Vec<T> {
+        // SAFETY:
+        // the reference to `chunks` doesn't outlive this function (only the
+        // returned chunk does), and this is not reentrant.
+        let chunks = unsafe { &mut *self.chunks.get() };
+        let needs_new = match chunks.as_slice().last() {
+            Some(last) => last.capacity() - last.len() < n,
+            None => true,
+        };
+
+        if needs_new {
+            let last_cap = chunks.as_slice().last().map_or(0, |c| c.capacity());
+            let cap = n.max(last_cap.saturating_mul(2)).max(DEFAULT_CHUNK_LEN);
+            // pushing a chunk moves only the `Vec` headers, not the values.
+            chunks.push(Vec::with_capacity(cap));
+        }
+
+        let len = chunks.len();
+        &mut chunks.as_mut_slice()[len - 1]
+    }
+}
+
+impl<T> Default for TypedArena<T> {
+    fn default() -> Self {
+        TypedArena::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alloc::LeakGuard;
+    use core_orc::into_iterator::IntoIterator;
+    use std::cell::Cell;
+
+    struct DropCounter<'a>(&'a Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_alloc() {
+        let _leaks = LeakGuard::new();
+        let arena = TypedArena::new();
+        let mut refs = std::vec::Vec::new();
+        for i in 0..100 {
+            refs.push(arena.alloc(i));
+        }
+        *refs[0] = 42;
+
+        assert_eq!(arena.len(), 100);
+        assert_eq!(*refs[0], 42);
+        for (i, r) in refs.iter().enumerate().skip(1) {
+            assert_eq!(**r, i);
+        }
+    }
+
+    #[test]
+    fn test_alloc_extend() {
+        let _leaks = LeakGuard::new();
+        let arena = TypedArena::with_capacity(2);
+        let a = arena.alloc(String::from("a"));
+        let rest =
+            arena.alloc_extend(crate::vec![String::from("b"), String::from("c")].into_iter());
+        rest[1].push('!');
+
+        assert_eq!(a, "a");
+        assert_eq!(rest, &["b", "c!"]);
+        assert_eq!(arena.len(), 3);
+        assert!(arena
+            .alloc_extend(Vec::<String>::new().into_iter())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_drop() {
+        let _leaks = LeakGuard::new();
+        let count = Cell::new(0);
+        {
+            let arena = TypedArena::new();
+            for _ in 0..50 {
+                arena.alloc(DropCounter(&count));
+            }
+            arena.alloc_extend(crate::vec![DropCounter(&count), DropCounter(&count)].into_iter());
+            assert_eq!(count.get(), 0);
+        }
+        assert_eq!(count.get(), 52);
+    }
+
+    #[test]
+    fn test_tree() {
+        let _leaks = LeakGuard::new();
+        struct Node<'a> {
+            value: i32,
+            children: &'a [&'a Node<'a>],
+        }
+
+        fn sum(node: &Node<'_>) -> i32 {
+            node.value + node.children.iter().map(|c| sum(c)).sum::<i32>()
+        }
+
+        // the arena mutates its chunks through `&self`, so it is invariant in
+        // `T`, and without `#[may_dangle]` (unstable) its values can't borrow
+        // from the arena itself. The leaves live in an arena of their own.
+        let leaves = TypedArena::new();
+        let leaf1 = &*leaves.alloc(Node {
+            value: 1,
+            children: &[],
+        });
+        let leaf2 = &*leaves.alloc(Node {
+            value: 2,
+            children: &[],
+        });
+        let children = [leaf1, leaf2];
+        let arena = TypedArena::new();
+        let root = arena.alloc(Node {
+            value: 3,
+            children: &children,
+        });
+
+        assert_eq!(sum(root), 6);
+    }
+}
@@ -0,0 +1,236 @@
+//! Calling closures stored in `Bocs`.
+//!
+//! `Fn*` traits can't be implemented on stable, and `dyn FnOnce` can't be
+//! called through a pointer (it needs unsized locals), so:
+//!
+//! * `Bocs<dyn FnMut(..)>` and `Bocs<dyn Fn(..)>` are callable with
+//!   `call_mut` and `call`, through `CallMut` and `Call`, which every
+//!   `FnMut` and `Fn` (including the trait objects) implement.
+//! * a `FnOnce` is stored as `Bocs<dyn CallOnce<Args, Output = R>>`, which
+//!   moves the closure out of the box when it's called by `call_once`.
+//!
+//! As with `Fn*` traits, the arguments are passed as a tuple.
+
+use super::{Bocs, DeallocGuard};
+use crate::alloc::{Allocator, Layout};
+use std::mem::ManuallyDrop;
+use std::ptr::{self, NonNull};
+
+/// A `FnOnce` which can be called through a trait object, e.g.
+/// `Bocs<dyn CallOnce<(i32,), Output = i32>>`.
+///
+/// ```
+/// use alloc_orc::bocs::{Bocs, CallOnce};
+/// use alloc_orc::unsize_bocs;
+///
+/// let s = String::from("moved");
+/// let f: Bocs<dyn CallOnce<(&str,), Output = String>> =
+///     unsize_bocs!(Bocs::new(move |suffix: &str| s + suffix) => dyn CallOnce<(&str,), Output = String>);
+/// assert_eq!(f.call_once(("!",)), "moved!");
+/// ```
+pub trait CallOnce<Args> {
+    type Output;
+
+    /// Moves `self` out, and calls it.
+    ///
+    /// # Safety
+    ///
+    /// `self` must not be used (nor dropped) after this.
+    unsafe fn call_once_unchecked(&mut self, args: Args) -> Self::Output;
+
+    /// Calls `self`, consuming it.
+    fn call_once(self, args: Args) -> Self::Output
+    where
+        Self: Sized,
+    {
+        let mut f = ManuallyDrop::new(self);
+        // SAFETY:
+        // `f` is never used (nor dropped) again.
+        unsafe { f.call_once_unchecked(args) }
+    }
+}
+
+/// A `FnMut` (including `dyn FnMut`), which can be called through `Bocs`.
+pub trait CallMut<Args> {
+    type Output;
+
+    fn call_mut(&mut self, args: Args) -> Self::Output;
+}
+
+/// A `Fn` (including `dyn Fn`), which can be called through `Bocs`.
+pub trait Call<Args>: CallMut<Args> {
+    fn call(&self, args: Args) -> Self::Output;
+}
+
+macro_rules! impl_call {
+    ($($arg:ident: $ty:ident),*) => {
+        impl<F, R, $($ty),*> CallOnce<($($ty,)*)> for F
+        where
+            F: FnOnce($($ty),*) -> R,
+        {
+            type Output = R;
+
+            unsafe fn call_once_unchecked(&mut self, ($($arg,)*): ($($ty,)*)) -> R {
+                let f = ptr::read(self);
+                f($($arg),*)
+            }
+        }
+
+        impl<F, R, $($ty),*> CallMut<($($ty,)*)> for F
+        where
+            F: ?Sized + FnMut($($ty),*) -> R,
+        {
+            type Output = R;
+
+            fn call_mut(&mut self, ($($arg,)*): ($($ty,)*)) -> R {
+                self($($arg),*)
+            }
+        }
+
+        impl<F, R, $($ty),*> Call<($($ty,)*)> for F
+        where
+            F: ?Sized + Fn($($ty),*) -> R,
+        {
+            fn call(&self, ($($arg,)*): ($($ty,)*)) -> R {
+                self($($arg),*)
+            }
+        }
+    };
+}
+
+impl_call!();
+impl_call!(a1: A1);
+impl_call!(a1: A1, a2: A2);
+impl_call!(a1: A1, a2: A2, a3: A3);
+impl_call!(a1: A1, a2: A2, a3: A3, a4: A4);
+impl_call!(a1: A1, a2: A2, a3: A3, a4: A4, a5: A5);
+impl_call!(a1: A1, a2: A2, a3: A3, a4: A4, a5: A5, a6: A6);
+
+// `Bocs` is not `FnOnce` (which is `#[fundamental]`), so these don't overlap
+// with the impls for closures above.
+
+impl<Args, F: ?Sized + CallOnce<Args>, A: Allocator> CallOnce<Args> for Bocs<F, A> {
+    type Output = F::Output;
+
+    unsafe fn call_once_unchecked(&mut self, args: Args) -> F::Output {
+        let (raw, alloc) = Bocs::into_raw_with_allocator(ptr::read(self));
+        let layout = Layout::for_value(&*raw);
+        // the closure is moved out of the memory before it's called, so the
+        // memory can be freed even if it panics.
+        let _guard = DeallocGuard {
+            ptr: NonNull::new_unchecked(raw).cast(),
+            layout,
+            alloc: &alloc,
+        };
+        (*raw).call_once_unchecked(args)
+    }
+}
+
+impl<Args, F: ?Sized + CallMut<Args>, A: Allocator> CallMut<Args> for Bocs<F, A> {
+    type Output = F::Output;
+
+    fn call_mut(&mut self, args: Args) -> F::Output {
+        (**self).call_mut(args)
+    }
+}
+
+impl<Args, F: ?Sized + Call<Args>, A: Allocator> Call<Args> for Bocs<F, A> {
+    fn call(&self, args: Args) -> F::Output {
+        (**self).call(args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alloc::{LeakGuard, TrackingAllocator};
+    use crate::unsize_bocs;
+    use crate::vec::Vec;
+    use core_orc::{into_iterator::IntoIterator, iter::traits::iterator::Iterator};
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    type Task<'a> = Bocs<dyn CallOnce<(), Output = ()> + 'a>;
+
+    #[test]
+    fn test_call_once_task_queue() {
+        let _leaks = LeakGuard::new();
+        let log = Cell::new(0);
+
+        let mut queue: Vec<Task<'_>> = Vec::new();
+        for i in 1..=3 {
+            let name = i.to_string();
+            let log = &log;
+            queue.push(unsize_bocs!(Bocs::new(move || {
+                // `name` is moved out of the closure.
+                let n: usize = name.parse().unwrap();
+                log.set(log.get() * 10 + n);
+            }) => dyn CallOnce<(), Output = ()> + '_));
+        }
+        // a task which is never called is just dropped.
+        queue
+            .push(unsize_bocs!(Bocs::new(move || unreachable!()) => dyn CallOnce<(), Output = ()>));
+
+        let mut tasks = queue.into_iter();
+        for _ in 0..3 {
+            tasks.next().unwrap().call_once(());
+        }
+        drop(tasks);
+        assert_eq!(log.get(), 123);
+    }
+
+    #[test]
+    fn test_call_once_with_args() {
+        let _leaks = LeakGuard::new();
+
+        let v = crate::vec![1, 2];
+        let f: Bocs<dyn CallOnce<(i32, i32), Output = Vec<i32>>> = unsize_bocs!(Bocs::new(move |a, b| {
+                let mut v = v;
+                v.push(a);
+                v.push(b);
+                v
+            }) => dyn CallOnce<(i32, i32), Output = Vec<i32>>);
+        assert_eq!(f.call_once((3, 4)), [1, 2, 3, 4]);
+
+        // a sized closure works as well, and zero-sized ones don't allocate.
+        assert_eq!(Bocs::new(|| 42).call_once(()), 42);
+    }
+
+    #[test]
+    fn test_call_once_panic() {
+        // no `LeakGuard`: the test harness allocates to capture the panic message.
+        let tracking = TrackingAllocator::new();
+        let s = String::from("captured");
+        let f: Bocs<dyn CallOnce<(), Output = ()>, _> = unsize_bocs!(Bocs::new_in(move || {
+            let _s = s;
+            panic!("task panicked");
+        }, &tracking) => dyn CallOnce<(), Output = ()>);
+
+        let result = catch_unwind(AssertUnwindSafe(|| f.call_once(())));
+        assert!(result.is_err());
+        assert_eq!(tracking.stats().live_allocations(), 0);
+    }
+
+    #[test]
+    fn test_call_mut_and_call() {
+        let _leaks = LeakGuard::new();
+
+        let mut count = 0;
+        let mut f: Bocs<dyn FnMut(i32) -> i32 + '_> = unsize_bocs!(Bocs::new(|x| {
+            count += x;
+            count
+        }) => dyn FnMut(i32) -> i32);
+        assert_eq!(f.call_mut((1,)), 1);
+        assert_eq!(f.call_mut((2,)), 3);
+        // calling through `DerefMut` works too.
+        assert_eq!((*f)(3), 6);
+        drop(f);
+        assert_eq!(count, 6);
+
+        let offset = String::from("10");
+        let g: Bocs<dyn Fn(&str) -> usize> =
+            unsize_bocs!(Bocs::new(move |s: &str| s.len() + offset.len()) => dyn Fn(&str) -> usize);
+        assert_eq!(g.call(("abc",)), 5);
+        assert_eq!(g.call(("",)), 2);
+    }
+}
@@ -0,0 +1,1114 @@
+use crate::alloc::{handle_alloc_error, Allocator, Global, Layout};
+use crate::vec::Vec;
+use core_orc::iter::traits::{double_ended::DoubleEndedIterator, iterator::Iterator};
+use core_orc::pin::Pin;
+use std::any::Any;
+use std::borrow::{Borrow, BorrowMut};
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop, MaybeUninit};
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+
+mod callable;
+
+pub use callable::{Call, CallMut, CallOnce};
+
+pub struct Bocs<T: ?Sized, A: Allocator = Global> {
+    /// `NonNull` is covariant over `T`, so `Bocs<&'static str>` can be used
+    /// as `Bocs<&'a str>`, like `Unique<T>` in std.
+    /// * https://doc.rust-lang.org/reference/subtyping.html
+    /// * https://www.youtube.com/watch?v=iVYWDIW71jk
+    /// * https://lifetime-variance.sunshowers.io/
+    ///
+    /// If `T` is unsized (`dyn Trait`, `[T]` or `str`), this is a fat pointer
+    /// which also holds the vtable or the length.
+    inner: NonNull<T>,
+    alloc: A,
+    /// Tells the drop checker that `Bocs` owns (and drops) a `T`.
+    _marker: PhantomData<T>,
+}
+
+// `Bocs` owns the value (and the allocator), so it is `Send`/`Sync` if they are.
+unsafe impl<T: ?Sized + Send, A: Allocator + Send> Send for Bocs<T, A> {}
+unsafe impl<T: ?Sized + Sync, A: Allocator + Sync> Sync for Bocs<T, A> {}
+
+/// Converts `Bocs<T, A>` into `Bocs<U, A>`, where `T` can be unsized to `U`,
+/// e.g. `Bocs<[T; N]>` to `Bocs<[T]>`, or `Bocs<T>` to `Bocs<dyn Trait>`.
+///
+/// This is what `CoerceUnsized` does implicitly for `Box`, which can't be
+/// implemented on stable. Only unsizing coercions compile, so this is safe.
+///
+/// ```
+/// use alloc_orc::bocs::Bocs;
+/// use alloc_orc::unsize_bocs;
+/// use std::fmt::Display;
+///
+/// let b: Bocs<dyn Display> = unsize_bocs!(Bocs::new(42) => dyn Display);
+/// assert_eq!(b.to_string(), "42");
+/// ```
+///
+/// Other conversions are rejected:
+///
+/// ```compile_fail
+/// use alloc_orc::bocs::Bocs;
+/// use alloc_orc::unsize_bocs;
+///
+/// let b: Bocs<[u8]> = unsize_bocs!(Bocs::new([0u16; 4]) => [u8]);
+/// ```
+#[macro_export]
+macro_rules! unsize_bocs {
+    ($bocs:expr => $u:ty) => {{
+        let (raw, alloc) = $crate::bocs::Bocs::into_raw_with_allocator($bocs);
+        // only an unsizing coercion (or no-op) is allowed here.
+        let raw: *mut $u = raw;
+        // SAFETY:
+        // `raw` points to the same allocation, and its metadata was created
+        // by the compiler.
+        unsafe { $crate::bocs::Bocs::from_raw_in(raw, alloc) }
+    }};
+}
+
+impl<T> Bocs<T> {
+    pub fn new(x: T) -> Self {
+        Bocs::new_in(x, Global)
+    }
+
+    /// Allocates memory, places `x` into it, and pins it.
+    pub fn pin(x: T) -> Pin<Bocs<T>> {
+        Bocs::into_pin(Bocs::new(x))
+    }
+
+    /// Allocates memory for a `T`, without initializing it.
+    pub fn new_uninit() -> Bocs<MaybeUninit<T>> {
+        Bocs::new_uninit_in(Global)
+    }
+
+    /// Allocates memory for `len` elements, without initializing them.
+    pub fn new_uninit_slice(len: usize) -> Bocs<[MaybeUninit<T>]> {
+        Bocs::new_uninit_slice_in(len, Global)
+    }
+}
+
+impl<T: ?Sized> Bocs<T> {
+    /// Constructs a `Bocs` from a raw pointer returned by `Bocs::into_raw`.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must have been returned by `Bocs::<T>::into_raw` (or point to a
+    /// valid `T` allocated by `Global` with `Layout::for_value(&*raw)`), and
+    /// must not be used to construct another `Bocs`.
+    pub unsafe fn from_raw(raw: *mut T) -> Self {
+        Bocs::from_raw_in(raw, Global)
+    }
+}
+
+impl<T, A: Allocator> Bocs<T, A> {
+    /// Allocates memory in the given allocator, and places `x` into it.
+    ///
+    /// This doesn't allocate if `T` is zero-sized.
+    pub fn new_in(x: T, alloc: A) -> Self {
+        let layout = Layout::new::<T>();
+        let ptr = if mem::size_of::<T>() == 0 {
+            NonNull::dangling()
+        } else {
+            match alloc.allocate(layout) {
+                Ok(ptr) => ptr.cast::<T>(),
+                Err(_) => handle_alloc_error(layout),
+            }
+        };
+
+        // SAFETY:
+        // `ptr` is valid for writes and aligned (any pointer is, if `T` is zero-sized).
+        unsafe { ptr::write(ptr.as_ptr(), x) };
+
+        Bocs {
+            inner: ptr,
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Same as `pin`, but in the given allocator.
+    pub fn pin_in(x: T, alloc: A) -> Pin<Bocs<T, A>>
+    where
+        A: 'static,
+    {
+        Bocs::into_pin(Bocs::new_in(x, alloc))
+    }
+
+    /// Same as `new_uninit`, but in the given allocator.
+    pub fn new_uninit_in(alloc: A) -> Bocs<MaybeUninit<T>, A> {
+        Bocs::new_in(MaybeUninit::uninit(), alloc)
+    }
+
+    /// Same as `new_uninit_slice`, but in the given allocator.
+    pub fn new_uninit_slice_in(len: usize, alloc: A) -> Bocs<[MaybeUninit<T>], A> {
+        let layout = Layout::array::<T>(len).expect("capacity overflow");
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            match alloc.allocate(layout) {
+                Ok(ptr) => ptr.cast::<MaybeUninit<T>>(),
+                Err(_) => handle_alloc_error(layout),
+            }
+        };
+
+        // SAFETY:
+        // the memory was allocated by `alloc` with the layout of `[T]` of
+        // `len` elements, and `MaybeUninit` doesn't need to be initialized.
+        unsafe { Bocs::from_raw_in(ptr::slice_from_raw_parts_mut(ptr.as_ptr(), len), alloc) }
+    }
+
+    /// Moves the value out, and deallocates the memory.
+    pub fn into_inner(b: Self) -> T {
+        let (raw, alloc) = Bocs::into_raw_with_allocator(b);
+        // SAFETY:
+        // `raw` points to a valid `T`, which is moved out here, and the
+        // memory is deallocated without dropping it.
+        unsafe {
+            let value = ptr::read(raw);
+            if mem::size_of::<T>() != 0 {
+                alloc.deallocate(NonNull::new_unchecked(raw).cast(), Layout::new::<T>());
+            }
+            value
+        }
+    }
+}
+
+impl<T, A: Allocator> Bocs<MaybeUninit<T>, A> {
+    /// Converts to `Bocs<T, A>`.
+    ///
+    /// # Safety
+    ///
+    /// The value must be initialized.
+    pub unsafe fn assume_init(self) -> Bocs<T, A> {
+        let (raw, alloc) = Bocs::into_raw_with_allocator(self);
+        Bocs::from_raw_in(raw as *mut T, alloc)
+    }
+
+    /// Writes `value`, and converts to `Bocs<T, A>`.
+    pub fn write(mut b: Self, value: T) -> Bocs<T, A> {
+        (*b).write(value);
+        // SAFETY:
+        // the value was just initialized.
+        unsafe { b.assume_init() }
+    }
+}
+
+impl<T, A: Allocator> Bocs<[MaybeUninit<T>], A> {
+    /// Converts to `Bocs<[T], A>`.
+    ///
+    /// # Safety
+    ///
+    /// All the elements must be initialized.
+    pub unsafe fn assume_init(self) -> Bocs<[T], A> {
+        let (raw, alloc) = Bocs::into_raw_with_allocator(self);
+        Bocs::from_raw_in(raw as *mut [T], alloc)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Bocs<T, A> {
+    /// Constructs a `Bocs` from a raw pointer and its allocator.
+    ///
+    /// # Safety
+    ///
+    /// `raw` must point to a valid `T` allocated by `alloc` with
+    /// `Layout::for_value(&*raw)` (or be dangling if that's zero-sized),
+    /// and nothing else may own it.
+    pub unsafe fn from_raw_in(raw: *mut T, alloc: A) -> Self {
+        Bocs {
+            inner: NonNull::new_unchecked(raw),
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Consumes the `Bocs`, returning the raw pointer. The caller is
+    /// responsible for the memory and the value, e.g. by converting it back
+    /// with `Bocs::from_raw`.
+    pub fn into_raw(b: Self) -> *mut T {
+        Bocs::into_raw_with_allocator(b).0
+    }
+
+    /// Consumes the `Bocs`, returning the raw pointer and the allocator.
+    /// The caller is responsible for the memory and the value.
+    pub fn into_raw_with_allocator(b: Self) -> (*mut T, A) {
+        let b = ManuallyDrop::new(b);
+        // SAFETY:
+        // `b` is never used (nor dropped) again, so the allocator is moved out.
+        let alloc = unsafe { ptr::read(&b.alloc) };
+        (b.inner.as_ptr(), alloc)
+    }
+
+    /// Consumes and leaks the `Bocs`, returning a mutable reference which
+    /// lives as long as the allocator.
+    pub fn leak<'a>(b: Self) -> &'a mut T
+    where
+        A: 'a,
+    {
+        // the allocator is leaked as well, since it may own the memory (e.g.
+        // `Bump`), which `into_raw` would free by dropping it.
+        let b = ManuallyDrop::new(b);
+        // SAFETY:
+        // the value is never dropped nor deallocated.
+        unsafe { &mut *b.inner.as_ptr() }
+    }
+
+    /// Returns a raw pointer to the value, without consuming the `Bocs`.
+    pub fn as_ptr(b: &Self) -> *const T {
+        b.inner.as_ptr()
+    }
+
+    /// Returns a raw mutable pointer to the value, without consuming the `Bocs`.
+    pub fn as_mut_ptr(b: &mut Self) -> *mut T {
+        b.inner.as_ptr()
+    }
+
+    /// Pins the `Bocs`. The value never moves, since it's on the heap.
+    ///
+    /// The allocator has to be `'static`, since the pinned value has to stay
+    /// valid until it's dropped, even if the `Pin` is leaked.
+    pub fn into_pin(b: Self) -> Pin<Bocs<T, A>>
+    where
+        A: 'static,
+    {
+        // SAFETY:
+        // the value is never moved (nor deallocated) until `Bocs` is dropped,
+        // and `Pin` never gives `&mut Bocs` out.
+        unsafe { Pin::new_unchecked(b) }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn allocator(b: &Self) -> &A {
+        &b.alloc
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for Bocs<T, A> {
+    fn clone(&self) -> Self {
+        Bocs::new_in((**self).clone(), self.alloc.clone())
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for Bocs<[T], A> {
+    fn clone(&self) -> Self {
+        clone_slice_in(self, self.alloc.clone())
+    }
+}
+
+impl<A: Allocator + Clone> Clone for Bocs<str, A> {
+    fn clone(&self) -> Self {
+        let bytes = clone_slice_in(self.as_bytes(), self.alloc.clone());
+        // SAFETY:
+        // the bytes are copied from a valid `str`.
+        unsafe { bytes_to_str(bytes) }
+    }
+}
+
+fn clone_slice_in<T: Clone, A: Allocator>(s: &[T], alloc: A) -> Bocs<[T], A> {
+    let mut v = Vec::with_capacity_in(s.len(), alloc);
+    for x in s {
+        v.push(x.clone());
+    }
+    v.into_boxed_slice()
+}
+
+/// # Safety
+///
+/// `bytes` must be valid UTF-8.
+unsafe fn bytes_to_str<A: Allocator>(bytes: Bocs<[u8], A>) -> Bocs<str, A> {
+    let (raw, alloc) = Bocs::into_raw_with_allocator(bytes);
+    Bocs::from_raw_in(raw as *mut str, alloc)
+}
+
+// moving `Bocs` doesn't move the value, so it's always `Unpin`.
+impl<T: ?Sized, A: Allocator> Unpin for Bocs<T, A> {}
+
+impl<T: ?Sized, A: Allocator> Deref for Bocs<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        // SAFETY:
+        // `inner` points to an initialized `T`, owned by `self`.
+        unsafe { self.inner.as_ref() }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> DerefMut for Bocs<T, A> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        // SAFETY:
+        // `inner` points to an initialized `T`, and `&mut self` guarantees
+        // nobody else refers to it.
+        unsafe { self.inner.as_mut() }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for Bocs<T, A> {
+    fn drop(&mut self) {
+        // the layout has to be computed from the value (e.g. the length of
+        // a slice, or the size in the vtable), before it's dropped.
+        // SAFETY:
+        // the value is initialized.
+        let layout = Layout::for_value(unsafe { self.inner.as_ref() });
+        let _guard = DeallocGuard {
+            ptr: self.inner.cast(),
+            layout,
+            alloc: &self.alloc,
+        };
+        // SAFETY:
+        // the value is initialized, and never used again.
+        unsafe { ptr::drop_in_place(self.inner.as_ptr()) };
+    }
+}
+
+/// Deallocates the memory of a `Bocs` when dropped, so that it's freed even
+/// if dropping (or moving out) the value panics.
+struct DeallocGuard<'a, A: Allocator> {
+    ptr: NonNull<u8>,
+    layout: Layout,
+    alloc: &'a A,
+}
+
+impl<A: Allocator> Drop for DeallocGuard<'_, A> {
+    fn drop(&mut self) {
+        if self.layout.size() != 0 {
+            // SAFETY:
+            // `ptr` was allocated by `alloc` with this layout.
+            unsafe { self.alloc.deallocate(self.ptr, self.layout) }
+        }
+    }
+}
+
+//
+// trait forwarding
+//
+
+impl<T: ?Sized + fmt::Debug, A: Allocator> fmt::Debug for Bocs<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display, A: Allocator> fmt::Display for Bocs<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> fmt::Pointer for Bocs<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&Bocs::as_ptr(self), f)
+    }
+}
+
+impl<T: ?Sized + PartialEq, A: Allocator> PartialEq for Bocs<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        PartialEq::eq(&**self, &**other)
+    }
+}
+
+impl<T: ?Sized + Eq, A: Allocator> Eq for Bocs<T, A> {}
+
+impl<T: ?Sized + PartialOrd, A: Allocator> PartialOrd for Bocs<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: ?Sized + Ord, A: Allocator> Ord for Bocs<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+impl<T: ?Sized + Hash, A: Allocator> Hash for Bocs<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<T: Default> Default for Bocs<T> {
+    fn default() -> Self {
+        Bocs::new(T::default())
+    }
+}
+
+impl<T> Default for Bocs<[T]> {
+    fn default() -> Self {
+        // this doesn't allocate.
+        Vec::new().into_boxed_slice()
+    }
+}
+
+impl Default for Bocs<str> {
+    fn default() -> Self {
+        // SAFETY:
+        // an empty slice is valid UTF-8.
+        unsafe { bytes_to_str(Bocs::default()) }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Borrow<T> for Bocs<T, A> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> BorrowMut<T> for Bocs<T, A> {
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> AsRef<T> for Bocs<T, A> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> AsMut<T> for Bocs<T, A> {
+    fn as_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T> From<T> for Bocs<T> {
+    fn from(x: T) -> Self {
+        Bocs::new(x)
+    }
+}
+
+impl<I: ?Sized + Iterator, A: Allocator> Iterator for Bocs<I, A> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        (**self).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+}
+
+impl<I: ?Sized + DoubleEndedIterator, A: Allocator> DoubleEndedIterator for Bocs<I, A> {
+    fn next_back(&mut self) -> Option<I::Item> {
+        (**self).next_back()
+    }
+}
+
+impl<T: Error> Error for Bocs<T> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Error::source(&**self)
+    }
+}
+
+macro_rules! impl_downcast {
+    ($($dyn:tt)*) => {
+        impl<A: Allocator> Bocs<$($dyn)*, A> {
+            /// Downcasts to a concrete type, or returns `self` back if the
+            /// value is not a `T`.
+            pub fn downcast<T: Any>(self) -> Result<Bocs<T, A>, Self> {
+                if (*self).is::<T>() {
+                    // SAFETY:
+                    // the value is a `T`.
+                    unsafe { Ok(self.downcast_unchecked()) }
+                } else {
+                    Err(self)
+                }
+            }
+
+            /// Downcasts to a concrete type, without checking it.
+            ///
+            /// # Safety
+            ///
+            /// The value must be a `T`.
+            pub unsafe fn downcast_unchecked<T: Any>(self) -> Bocs<T, A> {
+                debug_assert!((*self).is::<T>());
+                let (raw, alloc) = Bocs::into_raw_with_allocator(self);
+                Bocs::from_raw_in(raw as *mut T, alloc)
+            }
+        }
+    };
+}
+
+impl_downcast!(dyn Any);
+impl_downcast!(dyn Any + Send);
+impl_downcast!(dyn Any + Send + Sync);
+
+//
+// conversions into unsized `Bocs`
+//
+
+impl<T, const N: usize, A: Allocator> From<Bocs<[T; N], A>> for Bocs<[T], A> {
+    fn from(b: Bocs<[T; N], A>) -> Self {
+        unsize_bocs!(b => [T])
+    }
+}
+
+impl<T: Clone> From<&[T]> for Bocs<[T]> {
+    fn from(s: &[T]) -> Self {
+        clone_slice_in(s, Global)
+    }
+}
+
+impl<T, A: Allocator> From<Vec<T, A>> for Bocs<[T], A> {
+    fn from(v: Vec<T, A>) -> Self {
+        v.into_boxed_slice()
+    }
+}
+
+impl From<&str> for Bocs<str> {
+    fn from(s: &str) -> Self {
+        let bytes = Bocs::<[u8]>::from(s.as_bytes());
+        // SAFETY:
+        // the bytes are copied from a valid `str`.
+        unsafe { bytes_to_str(bytes) }
+    }
+}
+
+impl<A: Allocator> From<Bocs<str, A>> for Bocs<[u8], A> {
+    fn from(s: Bocs<str, A>) -> Self {
+        let (raw, alloc) = Bocs::into_raw_with_allocator(s);
+        // SAFETY:
+        // `str` and `[u8]` have the same layout.
+        unsafe { Bocs::from_raw_in(raw as *mut [u8], alloc) }
+    }
+}
+
+impl<'a, E: Error + 'a> From<E> for Bocs<dyn Error + 'a> {
+    fn from(err: E) -> Self {
+        unsize_bocs!(Bocs::new(err) => dyn Error + 'a)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alloc::{LeakGuard, TrackingAllocator};
+    use crate::arena::Bump;
+    use std::cell::Cell;
+    use std::ffi::c_void;
+    use std::marker::PhantomPinned;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_deref_trait() {
+        let _leaks = LeakGuard::new();
+        let b = Bocs::new(1);
+
+        assert_eq!(b.deref(), &1);
+        assert_eq!(*b.deref(), 1);
+        assert_eq!(*b, 1);
+    }
+
+    #[test]
+    fn test_deref_mut_trait() {
+        let _leaks = LeakGuard::new();
+        let mut b = Bocs::new(1);
+
+        assert_eq!(b.deref_mut(), &mut 1);
+        assert_eq!(*b.deref_mut(), 1);
+
+        *b.deref_mut() = 42;
+
+        assert_eq!(*b, 42);
+    }
+
+    #[test]
+    fn test_drop() {
+        let _leaks = LeakGuard::new();
+        let count = Cell::new(0);
+
+        struct DropCounter<'a>(&'a Cell<usize>);
+
+        impl Drop for DropCounter<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let b = Bocs::new(DropCounter(&count));
+        let s = Bocs::new(String::from("no leak"));
+        assert_eq!(count.get(), 0);
+
+        drop(b);
+        drop(s);
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn test_drop_panic() {
+        // no `LeakGuard`: the test harness allocates to capture the panic message.
+        struct PanicOnDrop(#[allow(dead_code)] u64);
+
+        impl Drop for PanicOnDrop {
+            fn drop(&mut self) {
+                panic!("panic in drop");
+            }
+        }
+
+        let counting = TrackingAllocator::new();
+        let b = Bocs::new_in(PanicOnDrop(1), &counting);
+        assert_eq!(counting.stats().live_allocations(), 1);
+
+        let result = catch_unwind(AssertUnwindSafe(|| drop(b)));
+        assert!(result.is_err());
+        // the memory is deallocated anyway.
+        assert_eq!(counting.stats().live_allocations(), 0);
+    }
+
+    #[test]
+    fn test_zero_sized() {
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+
+        struct Zst;
+
+        impl Drop for Zst {
+            fn drop(&mut self) {
+                DROPPED.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let counting = TrackingAllocator::new();
+        let unit = Bocs::new_in((), &counting);
+        let zst = Bocs::new_in(Zst, &counting);
+        assert_eq!(*unit, ());
+        drop(unit);
+        drop(zst);
+
+        // zero-sized values are dropped, but never allocated.
+        assert_eq!(DROPPED.load(Ordering::Relaxed), 1);
+        assert_eq!(counting.stats().allocations, 0);
+    }
+
+    #[test]
+    fn test_covariance() {
+        fn shorten<'a>(b: Bocs<&'static str>) -> Bocs<&'a str> {
+            b
+        }
+
+        let _leaks = LeakGuard::new();
+        let b = shorten(Bocs::new("static"));
+        assert_eq!(*b, "static");
+    }
+
+    #[test]
+    fn test_dyn_trait() {
+        trait Plugin {
+            fn name(&self) -> String;
+        }
+
+        struct Hello(String);
+
+        impl Plugin for Hello {
+            fn name(&self) -> String {
+                format!("hello {}", self.0)
+            }
+        }
+
+        struct Unit;
+
+        impl Plugin for Unit {
+            fn name(&self) -> String {
+                String::from("unit")
+            }
+        }
+
+        let _leaks = LeakGuard::new();
+        let plugins: [Bocs<dyn Plugin>; 2] = [
+            unsize_bocs!(Bocs::new(Hello(String::from("world"))) => dyn Plugin),
+            unsize_bocs!(Bocs::new(Unit) => dyn Plugin),
+        ];
+
+        assert_eq!(plugins[0].name(), "hello world");
+        assert_eq!(plugins[1].name(), "unit");
+    }
+
+    #[test]
+    fn test_dyn_trait_drop() {
+        let _leaks = LeakGuard::new();
+        let count = Cell::new(0);
+
+        struct DropCounter<'a>(&'a Cell<usize>);
+
+        impl Drop for DropCounter<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        trait Any2 {}
+        impl<T> Any2 for T {}
+
+        let b: Bocs<dyn Any2 + '_> = unsize_bocs!(Bocs::new(DropCounter(&count)) => dyn Any2);
+        drop(b);
+        assert_eq!(count.get(), 1);
+    }
+
+    #[test]
+    fn test_slice() {
+        let _leaks = LeakGuard::new();
+
+        let b: Bocs<[String]> = Bocs::from(Bocs::new([String::from("a"), String::from("b")]));
+        assert_eq!(b.len(), 2);
+        assert_eq!(&b[1], "b");
+
+        let cloned = b.clone();
+        assert_eq!(&*cloned, &*b);
+
+        let from_slice = Bocs::<[i32]>::from(&[1, 2, 3][..]);
+        assert_eq!(&*from_slice, &[1, 2, 3]);
+
+        let empty = Bocs::<[i32]>::from(&[][..]);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_into_boxed_slice() {
+        let _leaks = LeakGuard::new();
+        let tracking = TrackingAllocator::new();
+
+        let mut v = Vec::with_capacity_in(10, &tracking);
+        v.push(String::from("a"));
+        v.push(String::from("b"));
+        let b = v.into_boxed_slice();
+        assert_eq!(&*b, &["a", "b"]);
+        assert_eq!(tracking.stats().live_bytes, 2 * mem::size_of::<String>());
+
+        drop(b);
+        assert_eq!(tracking.stats().live_allocations(), 0);
+
+        let zst = crate::vec![(); 5].into_boxed_slice();
+        assert_eq!(zst.len(), 5);
+    }
+
+    #[test]
+    fn test_str() {
+        let _leaks = LeakGuard::new();
+
+        let mut s = Bocs::<str>::from("hello");
+        s.make_ascii_uppercase();
+        assert_eq!(&*s, "HELLO");
+        assert_eq!(&*s.clone(), "HELLO");
+
+        let bytes = Bocs::<[u8]>::from(s);
+        assert_eq!(&*bytes, b"HELLO");
+    }
+
+    #[test]
+    fn test_dyn_error() {
+        let _leaks = LeakGuard::new();
+
+        fn parse(s: &str) -> Result<i32, Bocs<dyn Error>> {
+            Ok(s.parse::<i32>()?)
+        }
+
+        assert_eq!(parse("42").unwrap(), 42);
+        let err = parse("x").unwrap_err();
+        assert_eq!(err.to_string(), "invalid digit found in string");
+    }
+
+    #[test]
+    fn test_into_raw_and_from_raw() {
+        let _leaks = LeakGuard::new();
+
+        extern "C" fn callback(data: *mut c_void) {
+            // SAFETY:
+            // `data` was created by `Bocs::into_raw` below.
+            let mut b = unsafe { Bocs::from_raw(data as *mut String) };
+            b.push_str(" world");
+            // hand it back to the caller.
+            let _ = Bocs::into_raw(b);
+        }
+
+        let b = Bocs::new(String::from("hello"));
+        let raw = Bocs::into_raw(b);
+        callback(raw as *mut c_void);
+
+        let b = unsafe { Bocs::from_raw(raw) };
+        assert_eq!(&*b, "hello world");
+
+        let s: Bocs<str> = Bocs::from("unsized");
+        let raw = Bocs::into_raw(s);
+        let s = unsafe { Bocs::from_raw(raw) };
+        assert_eq!(&*s, "unsized");
+    }
+
+    #[test]
+    fn test_as_ptr() {
+        let _leaks = LeakGuard::new();
+        let mut b = Bocs::new(1);
+        let ptr = Bocs::as_ptr(&b);
+        assert_eq!(unsafe { *ptr }, 1);
+
+        unsafe { *Bocs::as_mut_ptr(&mut b) = 2 };
+        assert_eq!(*b, 2);
+        assert_eq!(Bocs::as_ptr(&b), ptr);
+    }
+
+    #[test]
+    fn test_leak() {
+        let tracking = TrackingAllocator::new();
+        {
+            let leaked: &mut i32 = Bocs::leak(Bocs::new_in(1, &tracking));
+            *leaked += 1;
+            assert_eq!(*leaked, 2);
+        }
+        assert_eq!(tracking.stats().live_allocations(), 1);
+
+        let leaked: &'static mut str = Bocs::leak(Bocs::from("static"));
+        assert_eq!(leaked, "static");
+        // give it back, not to leak in the test.
+        drop(unsafe { Bocs::from_raw(leaked as *mut str) });
+    }
+
+    #[test]
+    fn test_leak_keeps_owned_allocator_alive() {
+        // no `LeakGuard`: the `Bump` and its chunk are leaked by design.
+        let checkpoint = TrackingAllocator::checkpoint();
+        let leaked: &'static mut [u64; 4] = Bocs::leak(Bocs::new_in([1; 4], Bump::new()));
+        // the chunk of the `Bump` is still allocated, so no other allocation
+        // can reuse it.
+        assert_eq!(checkpoint.leaked_allocations(), 1);
+        let other = Bocs::new([2u64; 4]);
+        leaked[0] = 3;
+        assert_eq!(*leaked, [3, 1, 1, 1]);
+        assert_eq!(*other, [2; 4]);
+        drop(other);
+        assert_eq!(checkpoint.leaked_allocations(), 1);
+    }
+
+    #[test]
+    fn test_into_inner() {
+        let _leaks = LeakGuard::new();
+        let count = Cell::new(0);
+
+        struct DropCounter<'a>(&'a Cell<usize>);
+
+        impl Drop for DropCounter<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1);
+            }
+        }
+
+        let b = Bocs::new(DropCounter(&count));
+        let inner = Bocs::into_inner(b);
+        assert_eq!(count.get(), 0);
+        drop(inner);
+        assert_eq!(count.get(), 1);
+
+        assert_eq!(Bocs::into_inner(Bocs::new(String::from("s"))), "s");
+        Bocs::into_inner(Bocs::new(()));
+    }
+
+    #[test]
+    fn test_new_uninit() {
+        let _leaks = LeakGuard::new();
+
+        let mut b = Bocs::<String>::new_uninit();
+        b.write(String::from("placed"));
+        let b = unsafe { b.assume_init() };
+        assert_eq!(&*b, "placed");
+
+        let b = Bocs::write(Bocs::new_uninit(), [1u8; 4096]);
+        assert_eq!(b[4095], 1);
+
+        let mut s = Bocs::<String>::new_uninit_slice(3);
+        for (i, slot) in s.iter_mut().enumerate() {
+            slot.write(i.to_string());
+        }
+        let s = unsafe { s.assume_init() };
+        assert_eq!(&*s, &["0", "1", "2"]);
+
+        let empty = unsafe { Bocs::<u64>::new_uninit_slice(0).assume_init() };
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_pin() {
+        /// A future-like state machine, which holds a pointer to its own buffer.
+        struct Machine {
+            buf: [u8; 4],
+            cursor: *const u8,
+            _pinned: PhantomPinned,
+        }
+
+        impl Machine {
+            fn step(this: Pin<&mut Self>) -> u8 {
+                // SAFETY:
+                // nothing is moved out.
+                let this = unsafe { this.get_unchecked_mut() };
+                if this.cursor.is_null() {
+                    this.cursor = this.buf.as_ptr();
+                }
+                // SAFETY:
+                // `cursor` points into `buf`, which never moves.
+                unsafe {
+                    let v = *this.cursor;
+                    this.cursor = this.cursor.add(1);
+                    v
+                }
+            }
+        }
+
+        let _leaks = LeakGuard::new();
+        let mut m = Bocs::pin(Machine {
+            buf: [1, 2, 3, 4],
+            cursor: ptr::null(),
+            _pinned: PhantomPinned,
+        });
+        assert_eq!(Machine::step(m.as_mut()), 1);
+
+        // moving the pinned `Bocs` doesn't move the machine.
+        let mut moved = m;
+        assert_eq!(Machine::step(moved.as_mut()), 2);
+        assert_eq!(Machine::step(moved.as_mut()), 3);
+
+        let mut p = Bocs::into_pin(Bocs::new(1));
+        *p += 1;
+        assert_eq!(*p, 2);
+
+        let p = Bocs::pin_in(String::from("in"), Global);
+        assert_eq!(p.as_ref().get_ref(), "in");
+    }
+
+    #[test]
+    fn test_fmt() {
+        let _leaks = LeakGuard::new();
+        let b = Bocs::new(vec![1, 2]);
+        assert_eq!(format!("{:?}", b), "[1, 2]");
+
+        let s: Bocs<str> = Bocs::from("display");
+        assert_eq!(format!("{}", s), "display");
+        assert_eq!(format!("{:?}", s), "\"display\"");
+        assert_eq!(format!("{:p}", s), format!("{:p}", Bocs::as_ptr(&s)));
+    }
+
+    #[test]
+    fn test_cmp_and_hash() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::collections::{BTreeSet, HashMap};
+
+        let _leaks = LeakGuard::new();
+        let a = Bocs::new(1);
+        let b = Bocs::new(2);
+        assert!(a < b);
+        assert_eq!(a.cmp(&b), std::cmp::Ordering::Less);
+        assert_eq!(a, Bocs::new(1));
+        assert_ne!(a, b);
+
+        let hash = |x: &dyn Fn(&mut DefaultHasher)| {
+            let mut h = DefaultHasher::new();
+            x(&mut h);
+            h.finish()
+        };
+        assert_eq!(hash(&|h| a.hash(h)), hash(&|h| 1.hash(h)));
+
+        // as a map key, looked up by `&str` through `Borrow<str>`.
+        let mut map: HashMap<Bocs<str>, i32> = HashMap::new();
+        map.insert(Bocs::from("one"), 1);
+        map.insert(Bocs::from("two"), 2);
+        assert_eq!(map.get("two"), Some(&2));
+
+        let set: BTreeSet<Bocs<[i32]>> = [Bocs::from(&[2][..]), Bocs::from(&[1, 2][..])]
+            .into_iter()
+            .collect();
+        assert_eq!(set.iter().next().map(|b| &**b), Some(&[1, 2][..]));
+    }
+
+    #[test]
+    fn test_default_and_from() {
+        let _leaks = LeakGuard::new();
+        assert_eq!(*Bocs::<i32>::default(), 0);
+        assert!(Bocs::<[String]>::default().is_empty());
+        assert_eq!(&*Bocs::<str>::default(), "");
+
+        let b: Bocs<String> = String::from("from").into();
+        assert_eq!(b.as_ref(), "from");
+    }
+
+    #[test]
+    fn test_borrow_and_as_mut() {
+        let _leaks = LeakGuard::new();
+        let mut b = Bocs::new(String::from("a"));
+        BorrowMut::<String>::borrow_mut(&mut b).push('b');
+        b.as_mut().push('c');
+        assert_eq!(Borrow::<String>::borrow(&b), "abc");
+    }
+
+    #[test]
+    fn test_iterator() {
+        use core_orc::into_iterator::IntoIterator;
+
+        let _leaks = LeakGuard::new();
+        let mut it: Bocs<dyn DoubleEndedIterator<Item = i32>> = unsize_bocs!(Bocs::new(crate::vec![1, 2, 3, 4].into_iter()) => dyn DoubleEndedIterator<Item = i32>);
+        assert_eq!(it.next(), Some(1));
+        assert_eq!(it.next_back(), Some(4));
+        assert_eq!(it.size_hint(), (2, Some(2)));
+
+        let rest = it.map(|x| x * 10).collect::<Vec<_>>();
+        assert_eq!(rest, [20, 30]);
+
+        let sized = Bocs::new(crate::vec![1, 2, 3].into_iter());
+        assert_eq!(sized.rev().collect::<Vec<_>>(), [3, 2, 1]);
+    }
+
+    #[test]
+    fn test_error() {
+        #[derive(Debug)]
+        struct Inner;
+
+        impl fmt::Display for Inner {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("inner")
+            }
+        }
+
+        impl Error for Inner {}
+
+        #[derive(Debug)]
+        struct Outer(Inner);
+
+        impl fmt::Display for Outer {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("outer")
+            }
+        }
+
+        impl Error for Outer {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                Some(&self.0)
+            }
+        }
+
+        let _leaks = LeakGuard::new();
+        let b = Bocs::new(Outer(Inner));
+        assert_eq!(b.to_string(), "outer");
+        assert_eq!(b.source().unwrap().to_string(), "inner");
+
+        // `Bocs<E>` is itself an error, so it converts into `Bocs<dyn Error>`.
+        let e: Bocs<dyn Error> = Bocs::from(b);
+        assert_eq!(format!("{:?}", e), "Outer(Inner)");
+    }
+
+    #[test]
+    fn test_downcast() {
+        let _leaks = LeakGuard::new();
+        let b: Bocs<dyn Any> = unsize_bocs!(Bocs::new(String::from("any")) => dyn Any);
+
+        let b = match b.downcast::<i32>() {
+            Ok(_) => panic!("not an i32"),
+            Err(b) => b,
+        };
+        let s = b.downcast::<String>().unwrap();
+        assert_eq!(&*s, "any");
+
+        let b: Bocs<dyn Any + Send + Sync> = unsize_bocs!(Bocs::new(1u8) => dyn Any + Send + Sync);
+        assert_eq!(*b.downcast::<u8>().unwrap(), 1);
+    }
+}
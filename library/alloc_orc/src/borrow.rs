@@ -0,0 +1,447 @@
+//! Owned counterparts of borrowed data, and the clone-on-write `Cow`.
+//!
+//! Resources:
+//! * https://doc.rust-lang.org/std/borrow/index.html
+
+use crate::string::String;
+use crate::vec::Vec;
+use core_orc::borrow::Borrow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, AddAssign, Deref};
+
+/// Creates owned data from borrowed data, generalizing `Clone` to e.g.
+/// `[T]` and `str`.
+///
+/// std's `ToOwned` is in the prelude, so its methods have to be called as
+/// `ToOwned::to_owned(x)` on types which implement both.
+pub trait ToOwned {
+    type Owned: Borrow<Self>;
+
+    fn to_owned(&self) -> Self::Owned;
+
+    /// Overwrites `target` with a copy of `self`, reusing its resources if
+    /// possible.
+    fn clone_into(&self, target: &mut Self::Owned) {
+        *target = self.to_owned();
+    }
+}
+
+impl<T: Clone> ToOwned for T {
+    type Owned = T;
+
+    fn to_owned(&self) -> T {
+        self.clone()
+    }
+
+    fn clone_into(&self, target: &mut T) {
+        target.clone_from(self);
+    }
+}
+
+impl<T: Clone> ToOwned for [T] {
+    type Owned = Vec<T>;
+
+    fn to_owned(&self) -> Vec<T> {
+        Vec::from(self)
+    }
+
+    fn clone_into(&self, target: &mut Vec<T>) {
+        target.clear();
+        target.reserve(self.len());
+        for v in self {
+            target.push(v.clone());
+        }
+    }
+}
+
+impl ToOwned for str {
+    type Owned = String;
+
+    fn to_owned(&self) -> String {
+        String::from(self)
+    }
+
+    fn clone_into(&self, target: &mut String) {
+        target.clear();
+        target.push_str(self);
+    }
+}
+
+/// A clone-on-write smart pointer: either borrowed data, or owned data
+/// which is created only once it needs to be mutated or owned.
+///
+/// ```
+/// use alloc_orc::borrow::Cow;
+///
+/// fn normalize(s: &str) -> Cow<'_, str> {
+///     if s.contains('\t') {
+///         Cow::Owned(s.replace('\t', " ").into())
+///     } else {
+///         Cow::Borrowed(s)
+///     }
+/// }
+///
+/// assert!(normalize("a b").is_borrowed());
+/// assert_eq!(normalize("a\tb"), "a b");
+/// ```
+pub enum Cow<'a, B: ?Sized + ToOwned + 'a> {
+    Borrowed(&'a B),
+    Owned(<B as ToOwned>::Owned),
+}
+
+impl<B: ?Sized + ToOwned> Cow<'_, B> {
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self, Cow::Borrowed(_))
+    }
+
+    pub fn is_owned(&self) -> bool {
+        !self.is_borrowed()
+    }
+
+    /// Returns a mutable reference to the owned data, cloning the borrowed
+    /// data first if needed.
+    pub fn to_mut(&mut self) -> &mut <B as ToOwned>::Owned {
+        if let Cow::Borrowed(borrowed) = *self {
+            *self = Cow::Owned(borrowed.to_owned());
+        }
+        match self {
+            Cow::Owned(owned) => owned,
+            Cow::Borrowed(_) => unreachable!(),
+        }
+    }
+
+    /// Returns the owned data, cloning the borrowed data if needed.
+    pub fn into_owned(self) -> <B as ToOwned>::Owned {
+        match self {
+            Cow::Borrowed(borrowed) => borrowed.to_owned(),
+            Cow::Owned(owned) => owned,
+        }
+    }
+}
+
+impl<B: ?Sized + ToOwned> Deref for Cow<'_, B> {
+    type Target = B;
+
+    fn deref(&self) -> &B {
+        match self {
+            Cow::Borrowed(borrowed) => borrowed,
+            Cow::Owned(owned) => owned.borrow(),
+        }
+    }
+}
+
+impl<B: ?Sized + ToOwned> AsRef<B> for Cow<'_, B> {
+    fn as_ref(&self) -> &B {
+        self
+    }
+}
+
+impl<'a, B: ?Sized + ToOwned> Borrow<B> for Cow<'a, B> {
+    fn borrow(&self) -> &B {
+        self
+    }
+}
+
+impl<B: ?Sized + ToOwned> Clone for Cow<'_, B> {
+    fn clone(&self) -> Self {
+        match self {
+            Cow::Borrowed(borrowed) => Cow::Borrowed(borrowed),
+            Cow::Owned(owned) => Cow::Owned(owned.borrow().to_owned()),
+        }
+    }
+}
+
+impl<B: ?Sized + ToOwned> Default for Cow<'_, B>
+where
+    B::Owned: Default,
+{
+    fn default() -> Self {
+        Cow::Owned(B::Owned::default())
+    }
+}
+
+impl<B: ?Sized + ToOwned + fmt::Debug> fmt::Debug for Cow<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<B: ?Sized + ToOwned + fmt::Display> fmt::Display for Cow<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+// `Borrow` guarantees that the owned and the borrowed data compare and hash
+// the same, so everything below goes through `Deref`.
+
+impl<'a, 'b, B, C> PartialEq<Cow<'b, C>> for Cow<'a, B>
+where
+    B: ?Sized + ToOwned + PartialEq<C>,
+    C: ?Sized + ToOwned,
+{
+    fn eq(&self, other: &Cow<'b, C>) -> bool {
+        **self == **other
+    }
+}
+
+impl<B: ?Sized + ToOwned + Eq> Eq for Cow<'_, B> {}
+
+impl<'a, B: ?Sized + ToOwned + PartialOrd> PartialOrd for Cow<'a, B> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<B: ?Sized + ToOwned + Ord> Ord for Cow<'_, B> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+impl<B: ?Sized + ToOwned + Hash> Hash for Cow<'_, B> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(&**self, state)
+    }
+}
+
+macro_rules! impl_cow_eq {
+    ([$($vars:tt)*] $lhs:ty, $rhs:ty) => {
+        impl<'a, 'b, $($vars)*> PartialEq<$rhs> for $lhs
+        where
+            T: PartialEq<U>,
+        {
+            fn eq(&self, other: &$rhs) -> bool {
+                self[..] == other[..]
+            }
+        }
+    };
+    ($lhs:ty, $rhs:ty) => {
+        impl<'a, 'b> PartialEq<$rhs> for $lhs {
+            fn eq(&self, other: &$rhs) -> bool {
+                self[..] == other[..]
+            }
+        }
+
+        impl<'a, 'b> PartialEq<$lhs> for $rhs {
+            fn eq(&self, other: &$lhs) -> bool {
+                self[..] == other[..]
+            }
+        }
+    };
+}
+
+impl_cow_eq! { Cow<'a, str>, str }
+impl_cow_eq! { Cow<'a, str>, &'b str }
+impl_cow_eq! { Cow<'a, str>, String }
+impl_cow_eq! { [T: Clone, U] Cow<'a, [T]>, [U] }
+impl_cow_eq! { [T: Clone, U] Cow<'a, [T]>, &'b [U] }
+impl_cow_eq! { [T: Clone, U] Cow<'a, [T]>, Vec<U> }
+impl_cow_eq! { [T: Clone, U, const N: usize] Cow<'a, [T]>, [U; N] }
+
+/// Appends to the string, cloning it first if it is borrowed, unless `self`
+/// is empty, in which case `rhs` is just borrowed.
+impl<'a> Add<&'a str> for Cow<'a, str> {
+    type Output = Cow<'a, str>;
+
+    fn add(mut self, rhs: &'a str) -> Cow<'a, str> {
+        self += rhs;
+        self
+    }
+}
+
+impl<'a> Add<Cow<'a, str>> for Cow<'a, str> {
+    type Output = Cow<'a, str>;
+
+    fn add(mut self, rhs: Cow<'a, str>) -> Cow<'a, str> {
+        self += rhs;
+        self
+    }
+}
+
+impl<'a> AddAssign<&'a str> for Cow<'a, str> {
+    fn add_assign(&mut self, rhs: &'a str) {
+        if self.is_empty() {
+            *self = Cow::Borrowed(rhs);
+        } else if !rhs.is_empty() {
+            if let Cow::Borrowed(lhs) = *self {
+                let mut s = String::with_capacity(lhs.len() + rhs.len());
+                s.push_str(lhs);
+                *self = Cow::Owned(s);
+            }
+            self.to_mut().push_str(rhs);
+        }
+    }
+}
+
+impl<'a> AddAssign<Cow<'a, str>> for Cow<'a, str> {
+    fn add_assign(&mut self, rhs: Cow<'a, str>) {
+        if self.is_empty() {
+            *self = rhs;
+        } else if !rhs.is_empty() {
+            if let Cow::Borrowed(lhs) = *self {
+                let mut s = String::with_capacity(lhs.len() + rhs.len());
+                s.push_str(lhs);
+                *self = Cow::Owned(s);
+            }
+            self.to_mut().push_str(&rhs);
+        }
+    }
+}
+
+impl<'a> From<&'a str> for Cow<'a, str> {
+    fn from(s: &'a str) -> Self {
+        Cow::Borrowed(s)
+    }
+}
+
+impl<'a> From<&'a String> for Cow<'a, str> {
+    fn from(s: &'a String) -> Self {
+        Cow::Borrowed(s.as_str())
+    }
+}
+
+impl From<String> for Cow<'_, str> {
+    fn from(s: String) -> Self {
+        Cow::Owned(s)
+    }
+}
+
+impl<'a, T: Clone> From<&'a [T]> for Cow<'a, [T]> {
+    fn from(s: &'a [T]) -> Self {
+        Cow::Borrowed(s)
+    }
+}
+
+impl<'a, T: Clone> From<&'a Vec<T>> for Cow<'a, [T]> {
+    fn from(v: &'a Vec<T>) -> Self {
+        Cow::Borrowed(v.as_slice())
+    }
+}
+
+impl<T: Clone> From<Vec<T>> for Cow<'_, [T]> {
+    fn from(v: Vec<T>) -> Self {
+        Cow::Owned(v)
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for String {
+    fn from(s: Cow<'a, str>) -> String {
+        s.into_owned()
+    }
+}
+
+impl<'a, T: Clone> From<Cow<'a, [T]>> for Vec<T> {
+    fn from(s: Cow<'a, [T]>) -> Vec<T> {
+        s.into_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alloc::LeakGuard;
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash<T: Hash + ?Sized>(t: &T) -> u64 {
+        let mut s = DefaultHasher::new();
+        t.hash(&mut s);
+        s.finish()
+    }
+
+    #[test]
+    fn to_owned_links_borrowed_and_owned_types() {
+        let _leaks = LeakGuard::new();
+        // std's `ToOwned` is in the prelude, so the calls are qualified.
+        let v: Vec<i32> = ToOwned::to_owned(&[1, 2, 3][..]);
+        assert_eq!(v, [1, 2, 3]);
+        let s: String = ToOwned::to_owned("abc");
+        assert_eq!(s, "abc");
+        assert_eq!(ToOwned::to_owned(&5), 5);
+
+        let mut target = crate::vec![9; 10];
+        ToOwned::clone_into(&[1, 2][..], &mut target);
+        assert_eq!(target, [1, 2]);
+        let mut target = String::from("xyz");
+        ToOwned::clone_into("ab", &mut target);
+        assert_eq!(target, "ab");
+    }
+
+    #[test]
+    fn to_mut_clones_only_borrowed_data() {
+        let _leaks = LeakGuard::new();
+        let data = [1, 2, 3];
+        let mut cow = Cow::from(&data[..]);
+        assert!(cow.is_borrowed());
+        cow.to_mut().push(4);
+        assert!(cow.is_owned());
+        let ptr = cow.as_ptr();
+        cow.to_mut()[0] = 0;
+        assert_eq!(cow.as_ptr(), ptr);
+        assert_eq!(cow, [0, 2, 3, 4]);
+        assert_eq!(data, [1, 2, 3]);
+    }
+
+    #[test]
+    fn into_owned() {
+        let _leaks = LeakGuard::new();
+        let cow: Cow<str> = Cow::Borrowed("a");
+        assert_eq!(cow.into_owned(), "a");
+        let owned = String::from("b");
+        let ptr = owned.as_ptr();
+        let cow: Cow<str> = Cow::Owned(owned);
+        let owned = String::from(cow);
+        assert_eq!(owned.as_ptr(), ptr);
+        let v: Vec<u8> = Cow::from(&crate::vec![1u8][..]).into();
+        assert_eq!(v, [1]);
+    }
+
+    #[test]
+    fn borrowed_and_owned_compare_and_hash_alike() {
+        let _leaks = LeakGuard::new();
+        let borrowed: Cow<str> = Cow::from("abc");
+        let owned: Cow<str> = Cow::from(String::from("abc"));
+        assert_eq!(borrowed, owned);
+        assert_eq!(hash(&borrowed), hash(&owned));
+        assert_eq!(hash(&borrowed), hash("abc"));
+        assert_eq!(borrowed, "abc");
+        assert_eq!("abc", owned);
+        assert_eq!(owned, String::from("abc"));
+        let greater: Cow<str> = Cow::from(String::from("abd"));
+        assert!(greater > borrowed);
+        assert_eq!(
+            Cow::from("a").cmp(&Cow::from(String::from("b"))),
+            Ordering::Less
+        );
+
+        let v: Cow<[i32]> = Cow::from(crate::vec![1, 2]);
+        assert_eq!(v, Cow::from(&[1, 2][..]));
+        assert_eq!(v, crate::vec![1, 2]);
+        assert_eq!(format!("{:?} {}", v, owned), "[1, 2] abc");
+    }
+
+    #[test]
+    fn add_borrows_as_long_as_possible() {
+        let _leaks = LeakGuard::new();
+        let cow = Cow::from("") + "abc";
+        assert!(cow.is_borrowed());
+        let cow = cow + "";
+        assert!(cow.is_borrowed());
+        let mut cow = cow + Cow::from("def");
+        assert!(cow.is_owned());
+        assert_eq!(cow, "abcdef");
+        cow += "g";
+        cow += Cow::from(String::from("h"));
+        assert_eq!(cow, "abcdefgh");
+    }
+
+    #[test]
+    fn default_is_owned() {
+        let _leaks = LeakGuard::new();
+        let cow: Cow<str> = Cow::default();
+        assert!(cow.is_owned());
+        assert!(cow.is_empty());
+    }
+}
@@ -1,5 +1,16 @@
+pub mod alloc;
+pub mod arena;
 pub mod bocs;
+pub mod borrow;
 #[macro_use]
 pub mod macros;
 pub mod rc;
+mod refcount;
+pub mod string;
+pub mod sync;
 pub mod vec;
+
+// every test in this crate can check for leaks via `alloc::LeakGuard`.
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: alloc::TrackingAllocator = alloc::TrackingAllocator::new();
@@ -1,24 +1,114 @@
-#[allow(unused_macro_rules, unused_macros)]
+/// Creates a `alloc_orc::vec::Vec` containing the arguments.
+///
+/// * `vec![]` creates an empty vector.
+/// * `vec![a, b, c]` creates a vector with the given elements.
+/// * `vec![elem; n]` creates a vector with `n` clones of `elem`.
+///
+/// The vector is allocated only once, with the exact capacity.
+#[macro_export]
 macro_rules! vec {
     // パターン: 空のリスト
     () => {
-        Vec::new()
+        $crate::vec::Vec::new()
+    };
+
+    // パターン: 要素と個数
+    ($elem:expr; $n:expr) => {
+        $crate::vec::from_elem($elem, $n)
     };
 
     // パターン: コンマ区切りの要素
     ($($x:expr),+ $(,)?) => {
         {
-            let mut temp_vec = crate::vec::Vec::new();
+            // count the elements at compile time, without evaluating them.
+            let mut temp_vec = $crate::vec::Vec::with_capacity(
+                <[()]>::len(&[$($crate::vec!(@unit $x)),+])
+            );
             $(
                 temp_vec.push($x);
             )+
             temp_vec
         }
     };
+
+    // 内部用: 要素を `()` に置き換える
+    (@unit $x:expr) => {
+        ()
+    };
 }
 
-#[test]
-fn s() {
-    let _: crate::vec::Vec<i32> = vec![1, 2, 3];
-    let _: crate::vec::Vec<&str> = vec!["a", "b", "c"];
+#[cfg(test)]
+mod test {
+    use crate::alloc::LeakGuard;
+    use std::rc::Rc;
+
+    #[test]
+    fn s() {
+        let _leaks = LeakGuard::new();
+
+        let _: crate::vec::Vec<i32> = vec![1, 2, 3];
+        let _: crate::vec::Vec<&str> = vec!["a", "b", "c"];
+    }
+
+    #[test]
+    fn test_empty() {
+        let _leaks = LeakGuard::new();
+
+        let v: crate::vec::Vec<i32> = vec![];
+        assert!(v.is_empty());
+        assert_eq!(v.capacity(), 0);
+    }
+
+    #[test]
+    fn test_list_is_pre_sized() {
+        let _leaks = LeakGuard::new();
+
+        let v = vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        assert_eq!(v, [1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(v.capacity(), 10);
+
+        let v = vec!["trailing", "comma"];
+        assert_eq!(v.capacity(), 2);
+    }
+
+    #[test]
+    fn test_repeat() {
+        let _leaks = LeakGuard::new();
+
+        let v = vec![String::from("a"); 3];
+        assert_eq!(v, ["a", "a", "a"]);
+        assert_eq!(v.capacity(), 3);
+
+        let v: crate::vec::Vec<i32> = vec![42; 0];
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn test_repeat_clones_n_minus_one_times() {
+        let _leaks = LeakGuard::new();
+
+        let rc = Rc::new(());
+        let v = vec![rc.clone(); 4];
+
+        // the last element is the moved `elem` itself, not a clone.
+        assert_eq!(Rc::strong_count(&rc), 5);
+        drop(v);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        let _ = vec![rc.clone(); 0];
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn test_elements_are_evaluated_once() {
+        let _leaks = LeakGuard::new();
+
+        let mut count = 0;
+        let mut next = || {
+            count += 1;
+            count
+        };
+        let v = vec![next(), next(), next()];
+        assert_eq!(v, [1, 2, 3]);
+    }
 }
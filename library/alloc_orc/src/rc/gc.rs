@@ -0,0 +1,807 @@
+//! A reference-counting pointer whose cycles can be collected.
+//!
+//! `Gc` counts its references like `Rc`. In addition, a value whose count is
+//! decremented without reaching zero is remembered as a possible root of a
+//! garbage cycle. `collect_cycles` runs the synchronous trial deletion of
+//! Bacon and Rajan ("Concurrent Cycle Collection in Reference Counted
+//! Systems", 2001) over those roots: it subtracts the references internal to
+//! the graph reachable from them, and frees the values which are only kept
+//! alive by such references.
+//!
+//! The collector finds the `Gc`s owned by a value through its `Trace` impl.
+//!
+//! ```
+//! use alloc_orc::rc::gc::{collect_cycles, Gc, Trace, Tracer};
+//! use std::cell::RefCell;
+//!
+//! struct Node {
+//!     next: RefCell<Option<Gc<Node>>>,
+//! }
+//!
+//! unsafe impl Trace for Node {
+//!     fn trace(&self, tracer: &mut Tracer) {
+//!         self.next.trace(tracer);
+//!     }
+//! }
+//!
+//! let a = Gc::new(Node { next: RefCell::new(None) });
+//! let b = Gc::new(Node { next: RefCell::new(Some(a.clone())) });
+//! *a.next.borrow_mut() = Some(b);
+//!
+//! drop(a);
+//! assert_eq!(collect_cycles(), 2);
+//! ```
+
+use crate::bocs::Bocs;
+use crate::vec::Vec;
+use core_orc::cell::Cell;
+use std::{
+    fmt,
+    mem::{self, ManuallyDrop},
+    ops::Deref,
+    ptr::{self, NonNull},
+};
+
+/// The colors of the algorithm, plus `Dead` for the values being freed by
+/// `collect_cycles`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+enum Color {
+    /// In use, or free.
+    Black,
+    /// A possible member of a cycle.
+    Gray,
+    /// A member of a garbage cycle.
+    White,
+    /// A possible root of a cycle.
+    Purple,
+    Dead,
+}
+
+struct GcBox<T: ?Sized> {
+    strong_ref_count: Cell<usize>,
+    color: Cell<Color>,
+    /// Whether the box is in `ROOTS`, which it must leave before it is
+    /// deallocated.
+    buffered: Cell<bool>,
+    value: ManuallyDrop<T>,
+}
+
+impl<T: ?Sized> GcBox<T> {
+    fn strong_ref_count(&self) -> usize {
+        self.strong_ref_count.get()
+    }
+    fn decr_strong_ref_count(&self) {
+        self.strong_ref_count.set(self.strong_ref_count() - 1);
+    }
+    fn incr_strong_ref_count(&self) {
+        self.strong_ref_count.set(self.strong_ref_count() + 1)
+    }
+    fn color(&self) -> Color {
+        self.color.get()
+    }
+    fn set_color(&self, color: Color) {
+        self.color.set(color);
+    }
+    fn buffered(&self) -> bool {
+        self.buffered.get()
+    }
+    fn set_buffered(&self, buffered: bool) {
+        self.buffered.set(buffered);
+    }
+}
+
+type Node = NonNull<GcBox<dyn Trace>>;
+
+/// `collect_cycles` runs by itself once this many possible roots are buffered,
+/// so that `ROOTS` doesn't grow without bound.
+const ROOTS_THRESHOLD: usize = 1024;
+
+/// The possible roots of a thread, see `possible_root`.
+struct Roots(Vec<Node>);
+
+impl Drop for Roots {
+    /// Runs at the exit of the thread. The boxes still referenced leave
+    /// `ROOTS`, so they're deallocated once their count reaches zero. Garbage
+    /// cycles which were not collected are leaked, like `Rc` cycles.
+    fn drop(&mut self) {
+        for &node in self.0.iter() {
+            // SAFETY:
+            // the boxes in `ROOTS` are not deallocated.
+            let inner = unsafe { node.as_ref() };
+            inner.set_buffered(false);
+            if inner.strong_ref_count() == 0 {
+                // SAFETY:
+                // the value is dropped, and no `Gc` points to the box.
+                unsafe { deallocate(node) };
+            }
+        }
+    }
+}
+
+thread_local! {
+    static ROOTS: std::cell::RefCell<Roots> = std::cell::RefCell::new(Roots(Vec::new()));
+    static COLLECTING: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+}
+
+/// Reports the `Gc`s owned by a value to the cycle collector.
+///
+/// # Safety
+///
+/// `trace` must visit each `Gc` owned by `self` at most once, and must not
+/// visit a `Gc` which `self` doesn't own. Otherwise, a value can be freed
+/// while it is still in use. Skipping a `Gc` is safe, but a cycle through it
+/// is never collected.
+///
+/// For the same reason, a type which shares its contents, e.g. `Rc<Gc<T>>`,
+/// must not implement `Trace`.
+pub unsafe trait Trace {
+    fn trace(&self, tracer: &mut Tracer);
+}
+
+/// Passed to `Trace::trace`, which visits each owned `Gc` with it.
+pub struct Tracer<'a> {
+    visit: &'a mut dyn FnMut(Node),
+}
+
+impl Tracer<'_> {
+    pub fn visit<T: Trace + 'static>(&mut self, gc: &Gc<T>) {
+        (self.visit)(gc.inner)
+    }
+}
+
+/// A single-threaded reference-counting pointer, whose cycles are freed by
+/// `collect_cycles`.
+///
+/// ```compile_fail,E0277
+/// use alloc_orc::rc::gc::Gc;
+///
+/// let gc = Gc::new(1);
+/// std::thread::spawn(move || drop(gc));
+/// ```
+pub struct Gc<T: Trace + 'static> {
+    inner: NonNull<GcBox<T>>,
+}
+
+impl<T: Trace + 'static> Gc<T> {
+    pub fn new(value: T) -> Self {
+        let inner = Bocs::into_raw(Bocs::new(GcBox {
+            strong_ref_count: Cell::new(1),
+            color: Cell::new(Color::Black),
+            buffered: Cell::new(false),
+            value: ManuallyDrop::new(value),
+        }));
+        Gc {
+            // SAFETY:
+            // `Bocs::into_raw` never returns null.
+            inner: unsafe { NonNull::new_unchecked(inner) },
+        }
+    }
+
+    pub fn strong_count(this: &Self) -> usize {
+        this.count().get()
+    }
+
+    /// Returns `true` if the two `Gc`s point to the same allocation.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        this.inner == other.inner
+    }
+
+    fn inner(&self) -> &GcBox<T> {
+        // SAFETY:
+        // the box is deallocated only once no `Gc` points to it.
+        unsafe { self.inner.as_ref() }
+    }
+
+    /// Returns the strong count, without borrowing the value, which
+    /// `collect_cycles` may be dropping meanwhile.
+    fn count(&self) -> &Cell<usize> {
+        // SAFETY:
+        // the box is deallocated only once no `Gc` points to it.
+        unsafe { &*ptr::addr_of!((*self.inner.as_ptr()).strong_ref_count) }
+    }
+
+    /// Returns the color, without borrowing the value, like `count`.
+    fn color(&self) -> Color {
+        // SAFETY:
+        // the box is deallocated only once no `Gc` points to it.
+        unsafe { (*ptr::addr_of!((*self.inner.as_ptr()).color)).get() }
+    }
+
+    fn assert_alive(&self) {
+        assert!(
+            self.color() != Color::Dead,
+            "used a `Gc` whose value is freed by `collect_cycles`"
+        );
+    }
+}
+
+impl<T: Trace + 'static> Clone for Gc<T> {
+    fn clone(&self) -> Self {
+        // a `Drop` impl run by `collect_cycles` must not resurrect a value
+        // which is freed as well.
+        self.assert_alive();
+        let inner = self.inner();
+        inner.incr_strong_ref_count();
+        inner.set_color(Color::Black);
+        Gc { inner: self.inner }
+    }
+}
+
+impl<T: Trace + 'static> Deref for Gc<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // the values of a garbage cycle are dropped one after another, so
+        // `Drop` impls can see values which are already dropped.
+        self.assert_alive();
+        &self.inner().value
+    }
+}
+
+impl<T: Trace + 'static> Drop for Gc<T> {
+    fn drop(&mut self) {
+        let count = self.count();
+        count.set(count.get() - 1);
+        if self.color() == Color::Dead {
+            // the value is being dropped by `collect_cycles`.
+            return;
+        }
+        let node: Node = self.inner;
+        if count.get() == 0 {
+            // SAFETY:
+            // this was the last reference.
+            unsafe { release(node) }
+        } else {
+            possible_root(node);
+        }
+    }
+}
+
+unsafe impl<T: Trace + 'static> Trace for Gc<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        tracer.visit(self);
+    }
+}
+
+impl<T: Trace + fmt::Debug + 'static> fmt::Debug for Gc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: Trace + fmt::Display + 'static> fmt::Display for Gc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: Trace + 'static> fmt::Pointer for Gc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&self.inner, f)
+    }
+}
+
+/// Frees the unreachable cycles of the current thread, and returns the number
+/// of values freed.
+///
+/// Only the `Gc`s dropped since the last call are considered as roots. It also
+/// runs by itself once 1024 of them are buffered. Called from a
+/// `Drop` impl run by the collector, it does nothing.
+pub fn collect_cycles() -> usize {
+    if COLLECTING.with(|collecting| collecting.replace(true)) {
+        return 0;
+    }
+    let _guard = CollectingGuard;
+    let roots = ROOTS.with(|roots| mem::take(&mut roots.borrow_mut().0));
+
+    // SAFETY:
+    // a box leaves `ROOTS` before it is deallocated, and everything reached
+    // from the roots is kept alive by their values.
+    unsafe {
+        let mut candidates = Vec::new();
+        for &node in roots.iter() {
+            let inner = node.as_ref();
+            if inner.color() == Color::Purple {
+                mark_gray(node);
+                candidates.push(node);
+            } else {
+                inner.set_buffered(false);
+                if inner.color() == Color::Black && inner.strong_ref_count() == 0 {
+                    deallocate(node);
+                }
+            }
+        }
+        for &node in candidates.iter() {
+            scan(node);
+        }
+        let mut garbage = Vec::new();
+        for &node in candidates.iter() {
+            node.as_ref().set_buffered(false);
+            collect_white(node, &mut garbage);
+        }
+
+        // give the references held by the garbage back, so that dropping the
+        // values leaves every count correct.
+        for &node in garbage.iter() {
+            children(node, |child| child.as_ref().incr_strong_ref_count());
+        }
+        for &node in garbage.iter() {
+            ManuallyDrop::drop(&mut (*node.as_ptr()).value);
+        }
+        for &node in garbage.iter() {
+            // a `Drop` impl may have moved a `Gc` out of the garbage, which
+            // then keeps the (dead) box alive.
+            if node.as_ref().strong_ref_count() == 0 {
+                deallocate(node);
+            }
+        }
+        garbage.len()
+    }
+}
+
+/// Resets `COLLECTING`, even if a `Drop` impl panics.
+struct CollectingGuard;
+
+impl Drop for CollectingGuard {
+    fn drop(&mut self) {
+        COLLECTING.with(|collecting| collecting.set(false));
+    }
+}
+
+/// Drops the value of a box without references, and deallocates the box.
+unsafe fn release(node: Node) {
+    node.as_ref().set_color(Color::Black);
+    ManuallyDrop::drop(&mut (*node.as_ptr()).value);
+    // the reference to the box is taken again, since dropping the value
+    // mutated it.
+    if !node.as_ref().buffered() || unbuffer(node) {
+        deallocate(node);
+    }
+}
+
+/// Removes a buffered box from `ROOTS`. Returns `false` if it is not there,
+/// i.e. `collect_cycles` has taken it, and then deallocates it.
+fn unbuffer(node: Node) -> bool {
+    let removed = ROOTS.try_with(|roots| {
+        let roots = &mut roots.borrow_mut().0;
+        // the box was most likely buffered recently.
+        let Some(i) = roots
+            .iter()
+            .rposition(|&root| ptr::addr_eq(root.as_ptr(), node.as_ptr()))
+        else {
+            return false;
+        };
+        let last = roots.len() - 1;
+        roots.as_mut_slice().swap(i, last);
+        roots.pop();
+        if roots.is_empty() {
+            // an idle thread doesn't keep the buffer.
+            roots.shrink_to_fit();
+        }
+        true
+    });
+    // `ROOTS` is destroyed only after unbuffering every box.
+    removed.unwrap_or(true)
+}
+
+fn possible_root(node: Node) {
+    // SAFETY:
+    // the box is still referenced.
+    let inner = unsafe { node.as_ref() };
+    if inner.color() == Color::Purple {
+        return;
+    }
+    inner.set_color(Color::Purple);
+    if inner.buffered() {
+        return;
+    }
+
+    // during the destruction of the thread-locals the box is not buffered,
+    // so a cycle through it is never collected.
+    let len = ROOTS.try_with(|roots| {
+        let roots = &mut roots.borrow_mut().0;
+        roots.push(node);
+        roots.len()
+    });
+    inner.set_buffered(len.is_ok());
+    if len.is_ok_and(|len| len >= ROOTS_THRESHOLD) {
+        // this may free the box, which is not used anymore.
+        collect_cycles();
+    }
+}
+
+/// Subtracts the references internal to the graph reachable from `node`.
+unsafe fn mark_gray(node: Node) {
+    if node.as_ref().color() == Color::Gray {
+        return;
+    }
+    node.as_ref().set_color(Color::Gray);
+    let mut stack = Vec::new();
+    stack.push(node);
+    while let Some(node) = stack.pop() {
+        children(node, |child| {
+            let inner = child.as_ref();
+            inner.decr_strong_ref_count();
+            if inner.color() != Color::Gray {
+                inner.set_color(Color::Gray);
+                stack.push(child);
+            }
+        });
+    }
+}
+
+/// Marks white the gray boxes which have no reference left, and restores the
+/// others together with everything they reach.
+unsafe fn scan(node: Node) {
+    let mut stack = Vec::new();
+    stack.push(node);
+    while let Some(node) = stack.pop() {
+        let inner = node.as_ref();
+        if inner.color() != Color::Gray {
+            continue;
+        }
+        if inner.strong_ref_count() > 0 {
+            scan_black(node);
+        } else {
+            inner.set_color(Color::White);
+            children(node, |child| stack.push(child));
+        }
+    }
+}
+
+unsafe fn scan_black(node: Node) {
+    node.as_ref().set_color(Color::Black);
+    let mut stack = Vec::new();
+    stack.push(node);
+    while let Some(node) = stack.pop() {
+        children(node, |child| {
+            let inner = child.as_ref();
+            inner.incr_strong_ref_count();
+            if inner.color() != Color::Black {
+                inner.set_color(Color::Black);
+                stack.push(child);
+            }
+        });
+    }
+}
+
+/// Moves the white boxes reachable from `node` to `garbage`.
+unsafe fn collect_white(node: Node, garbage: &mut Vec<Node>) {
+    let mut stack = Vec::new();
+    stack.push(node);
+    while let Some(node) = stack.pop() {
+        let inner = node.as_ref();
+        if inner.color() != Color::White || inner.buffered() {
+            continue;
+        }
+        inner.set_color(Color::Dead);
+        garbage.push(node);
+        children(node, |child| stack.push(child));
+    }
+}
+
+unsafe fn children(node: Node, mut f: impl FnMut(Node)) {
+    let value: &dyn Trace = &*node.as_ref().value;
+    value.trace(&mut Tracer { visit: &mut f });
+}
+
+unsafe fn deallocate(node: Node) {
+    // the value is dropped already, and `ManuallyDrop` keeps `Bocs` from
+    // dropping it again.
+    drop(Bocs::from_raw(node.as_ptr()));
+}
+
+macro_rules! impl_trace_for_leaves {
+    ($($t:ty)*) => {
+        $(
+            unsafe impl Trace for $t {
+                fn trace(&self, _: &mut Tracer) {}
+            }
+        )*
+    };
+}
+
+impl_trace_for_leaves! {
+    () bool char String
+    u8 u16 u32 u64 u128 usize
+    i8 i16 i32 i64 i128 isize
+    f32 f64
+}
+
+unsafe impl<T: Copy> Trace for Cell<T> {
+    // a `Copy` type can't own a `Gc`.
+    fn trace(&self, _: &mut Tracer) {}
+}
+
+unsafe impl<T: Trace> Trace for std::cell::RefCell<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        // a mutably borrowed value is reachable from outside anyway, so
+        // skipping it can't free anything in use.
+        if let Ok(value) = self.try_borrow() {
+            value.trace(tracer);
+        }
+    }
+}
+
+unsafe impl<T: Trace> Trace for Option<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        if let Some(value) = self {
+            value.trace(tracer);
+        }
+    }
+}
+
+unsafe impl<T: Trace> Trace for [T] {
+    fn trace(&self, tracer: &mut Tracer) {
+        for value in self {
+            value.trace(tracer);
+        }
+    }
+}
+
+unsafe impl<T: Trace> Trace for Vec<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        (**self).trace(tracer);
+    }
+}
+
+unsafe impl<T: Trace + ?Sized> Trace for Bocs<T> {
+    fn trace(&self, tracer: &mut Tracer) {
+        (**self).trace(tracer);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alloc::LeakGuard;
+    use std::cell::RefCell;
+
+    struct Node {
+        edges: RefCell<Vec<Gc<Node>>>,
+        drops: Option<std::rc::Rc<std::cell::Cell<usize>>>,
+    }
+
+    unsafe impl Trace for Node {
+        fn trace(&self, tracer: &mut Tracer) {
+            self.edges.trace(tracer);
+        }
+    }
+
+    impl Drop for Node {
+        fn drop(&mut self) {
+            if let Some(drops) = &self.drops {
+                drops.set(drops.get() + 1);
+            }
+        }
+    }
+
+    fn node(drops: &std::rc::Rc<std::cell::Cell<usize>>) -> Gc<Node> {
+        Gc::new(Node {
+            edges: RefCell::new(Vec::new()),
+            drops: Some(drops.clone()),
+        })
+    }
+
+    fn link(from: &Gc<Node>, to: &Gc<Node>) {
+        from.edges.borrow_mut().push(to.clone());
+    }
+
+    #[test]
+    fn acyclic_values_are_freed_without_collection() {
+        let _leaks = LeakGuard::new();
+        let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+        let a = node(&drops);
+        let b = node(&drops);
+        link(&a, &b);
+        let b2 = b.clone();
+        drop(b);
+        assert_eq!(Gc::strong_count(&b2), 2);
+
+        drop(a);
+        assert_eq!(drops.get(), 1);
+        assert_eq!(Gc::strong_count(&b2), 1);
+        drop(b2);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn possible_roots_are_collected_past_the_threshold() {
+        let _leaks = LeakGuard::new();
+        let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+        for _ in 0..ROOTS_THRESHOLD - 1 {
+            let a = node(&drops);
+            link(&a, &a);
+        }
+        assert_eq!(drops.get(), 0);
+
+        let a = node(&drops);
+        link(&a, &a);
+        drop(a);
+        assert_eq!(drops.get(), ROOTS_THRESHOLD);
+    }
+
+    #[test]
+    fn roots_are_released_after_the_thread_exits() {
+        // no `LeakGuard`: it doesn't see the destruction of the thread-locals.
+        // Miri checks that the box is deallocated.
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+        struct Kept;
+        unsafe impl Trace for Kept {
+            fn trace(&self, _: &mut Tracer) {}
+        }
+        impl Drop for Kept {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        thread_local! {
+            static KEPT: RefCell<Option<Gc<Kept>>> = const { RefCell::new(None) };
+        }
+
+        std::thread::spawn(|| {
+            let a = Gc::new(Kept);
+            // `a` is buffered, and the thread-locals are destroyed in any order.
+            drop(a.clone());
+            KEPT.with(|kept| *kept.borrow_mut() = Some(a));
+        })
+        .join()
+        .unwrap();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn self_cycle_is_collected() {
+        let _leaks = LeakGuard::new();
+        let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+        let a = node(&drops);
+        link(&a, &a);
+        drop(a);
+        assert_eq!(drops.get(), 0);
+
+        assert_eq!(collect_cycles(), 1);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn cycle_is_collected_once_unreachable() {
+        let _leaks = LeakGuard::new();
+        let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+        let a = node(&drops);
+        let b = node(&drops);
+        link(&a, &b);
+        link(&b, &a);
+        let b2 = b.clone();
+        drop(a);
+        drop(b);
+
+        // `b2` keeps the cycle alive.
+        assert_eq!(collect_cycles(), 0);
+        assert_eq!(drops.get(), 0);
+        assert_eq!(Gc::strong_count(&b2), 2);
+        assert_eq!(Gc::strong_count(&b2.edges.borrow()[0]), 1);
+
+        drop(b2);
+        assert_eq!(collect_cycles(), 2);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn values_reachable_from_garbage_survive() {
+        let _leaks = LeakGuard::new();
+        let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+        let a = node(&drops);
+        let b = node(&drops);
+        let shared = node(&drops);
+        link(&a, &b);
+        link(&b, &a);
+        link(&a, &shared);
+        link(&b, &shared);
+        drop(a);
+        drop(b);
+
+        assert_eq!(collect_cycles(), 2);
+        assert_eq!(drops.get(), 2);
+        assert_eq!(Gc::strong_count(&shared), 1);
+        drop(shared);
+        assert_eq!(drops.get(), 3);
+        assert_eq!(collect_cycles(), 0);
+    }
+
+    #[test]
+    fn long_cycle_does_not_overflow_the_stack() {
+        let _leaks = LeakGuard::new();
+        let drops = std::rc::Rc::new(std::cell::Cell::new(0));
+        let first = node(&drops);
+        let mut last = first.clone();
+        for _ in 1..10_000 {
+            let next = node(&drops);
+            link(&last, &next);
+            last = next;
+        }
+        link(&last, &first);
+        drop(last);
+        drop(first);
+
+        assert_eq!(collect_cycles(), 10_000);
+        assert_eq!(drops.get(), 10_000);
+    }
+
+    #[test]
+    fn untraced_cycle_is_not_collected() {
+        // no `LeakGuard`: the cycle leaks by design.
+        struct Opaque(RefCell<Option<Gc<Opaque>>>);
+        unsafe impl Trace for Opaque {
+            fn trace(&self, _: &mut Tracer) {}
+        }
+
+        let a = Gc::new(Opaque(RefCell::new(None)));
+        *a.0.borrow_mut() = Some(a.clone());
+        let inner = a.inner;
+        drop(a);
+
+        assert_eq!(collect_cycles(), 0);
+        // SAFETY:
+        // the leaked box is never deallocated.
+        assert_eq!(unsafe { inner.as_ref() }.strong_ref_count(), 1);
+    }
+
+    #[test]
+    #[should_panic = "freed by `collect_cycles`"]
+    fn garbage_can_not_be_used_while_dropped() {
+        // no `LeakGuard`: the panic leaks the cycle.
+        struct Peek(RefCell<Option<Gc<Peek>>>);
+        unsafe impl Trace for Peek {
+            fn trace(&self, tracer: &mut Tracer) {
+                self.0.trace(tracer);
+            }
+        }
+        impl Drop for Peek {
+            fn drop(&mut self) {
+                if let Some(other) = &*self.0.borrow() {
+                    let _ = other.0.borrow();
+                }
+            }
+        }
+
+        let a = Gc::new(Peek(RefCell::new(None)));
+        let b = Gc::new(Peek(RefCell::new(Some(a.clone()))));
+        *a.0.borrow_mut() = Some(b);
+        drop(a);
+        collect_cycles();
+    }
+
+    #[test]
+    fn garbage_moved_out_by_drop_is_not_deallocated() {
+        // no `LeakGuard`: the escaped box is leaked.
+        thread_local! {
+            static ESCAPED: RefCell<Option<Gc<Escape>>> = const { RefCell::new(None) };
+        }
+        struct Escape(RefCell<Option<Gc<Escape>>>);
+        unsafe impl Trace for Escape {
+            fn trace(&self, tracer: &mut Tracer) {
+                self.0.trace(tracer);
+            }
+        }
+        impl Drop for Escape {
+            fn drop(&mut self) {
+                let other = self.0.borrow_mut().take();
+                ESCAPED.with(|escaped| *escaped.borrow_mut() = other);
+            }
+        }
+
+        let a = Gc::new(Escape(RefCell::new(None)));
+        *a.0.borrow_mut() = Some(a.clone());
+        drop(a);
+        assert_eq!(collect_cycles(), 1);
+
+        let escaped = ESCAPED.with(|escaped| escaped.borrow_mut().take()).unwrap();
+        assert_eq!(Gc::strong_count(&escaped), 1);
+        let deref = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = &escaped.0;
+        }));
+        assert!(deref.is_err());
+    }
+}
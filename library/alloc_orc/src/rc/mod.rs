@@ -0,0 +1,1567 @@
+use crate::alloc::{handle_alloc_error, Allocator, Global, Layout};
+use crate::bocs::Bocs;
+use crate::refcount::{self, is_dangling};
+use crate::vec::Vec;
+use core_orc::cell::Cell;
+use core_orc::into_iterator::IntoIterator;
+use core_orc::iter::traits::{collector::FromIterator, iterator::Iterator};
+use std::{
+    any::Any,
+    borrow::Borrow,
+    borrow::BorrowMut,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ops::Deref,
+    ptr::{self, NonNull},
+};
+
+pub mod gc;
+
+// `repr(C)` keeps the counts in front, so the offset of `value` only depends
+// on its alignment, see `refcount::data_offset`.
+#[repr(C)]
+struct RcInner<T: ?Sized> {
+    strong_ref_count: Cell<usize>,
+    weak_ref_count: Cell<usize>,
+    value: T,
+}
+
+impl<T: ?Sized> RcInner<T> {
+    fn strong_ref_count(&self) -> usize {
+        self.strong_ref_count.get()
+    }
+    fn decr_strong_ref_count(&self) {
+        self.strong_ref_count.set(self.strong_ref_count() - 1);
+    }
+    fn incr_strong_ref_count(&self) {
+        self.strong_ref_count.set(self.strong_ref_count() + 1)
+    }
+    fn weak_ref_count(&self) -> usize {
+        self.weak_ref_count.get()
+    }
+    fn incr_weak_ref_count(&self) {
+        self.weak_ref_count.set(self.weak_ref_count() + 1)
+    }
+}
+
+/// A single-threaded reference-counting pointer.
+///
+/// A cycle of `Rc`s is never freed. Break it with `Weak`, or use `gc::Gc`,
+/// whose cycles can be collected.
+///
+/// The counts are not atomic, so `Rc` can't be sent to (nor shared with)
+/// another thread. Use `sync::Arc` instead.
+///
+/// ```compile_fail,E0277
+/// use alloc_orc::rc::Rc;
+///
+/// let rc = Rc::new(1);
+/// std::thread::spawn(move || drop(rc));
+/// ```
+pub struct Rc<T: ?Sized, A: Allocator = Global> {
+    /// RcInner is handled only internally, not by the user, so it never moves,
+    /// i.e., there is no need to consider Pinning.
+    inner: NonNull<RcInner<T>>,
+    alloc: A,
+    /// Keeps `Rc` `!Send` and `!Sync`, even if `inner` is changed to a type
+    /// which is `Send`.
+    _marker: PhantomData<*const RcInner<T>>,
+}
+
+/// Converts `Rc<T, A>` into `Rc<U, A>`, where `T` can be unsized to `U`,
+/// e.g. `Rc<[T; N]>` to `Rc<[T]>`, or `Rc<T>` to `Rc<dyn Trait>`.
+///
+/// Like `unsize_bocs!`, only unsizing coercions compile.
+///
+/// ```
+/// use alloc_orc::rc::Rc;
+/// use alloc_orc::unsize_rc;
+/// use std::fmt::Display;
+///
+/// let rc: Rc<dyn Display> = unsize_rc!(Rc::new(42) => dyn Display);
+/// assert_eq!(rc.as_ref().to_string(), "42");
+/// ```
+///
+/// Other conversions are rejected:
+///
+/// ```compile_fail
+/// use alloc_orc::rc::Rc;
+/// use alloc_orc::unsize_rc;
+///
+/// let rc: Rc<[u8]> = unsize_rc!(Rc::new([0u16; 4]) => [u8]);
+/// ```
+#[macro_export]
+macro_rules! unsize_rc {
+    ($rc:expr => $u:ty) => {{
+        let (raw, alloc) = $crate::rc::Rc::into_raw_with_allocator($rc);
+        // only an unsizing coercion (or no-op) is allowed here.
+        let raw: *const $u = raw;
+        // SAFETY:
+        // `raw` points to the same value, and its metadata was created by the
+        // compiler, so the alignment (and the offset of the value) is the same.
+        unsafe { $crate::rc::Rc::from_raw_in(raw, alloc) }
+    }};
+}
+
+impl<T> Rc<T> {
+    pub fn new(v: T) -> Self {
+        Rc::new_in(v, Global)
+    }
+
+    /// Constructs an `Rc` whose value can hold a `Weak` to itself.
+    ///
+    /// The `Weak` passed to `data_fn` doesn't upgrade until this returns.
+    pub fn new_cyclic<F: FnOnce(&Weak<T>) -> T>(data_fn: F) -> Self {
+        Rc::new_cyclic_in(data_fn, Global)
+    }
+
+    /// Constructs an `Rc` with uninitialized contents.
+    pub fn new_uninit() -> Rc<MaybeUninit<T>> {
+        Rc::new_uninit_in(Global)
+    }
+
+    /// Constructs an `Rc` with zero-filled contents.
+    pub fn new_zeroed() -> Rc<MaybeUninit<T>> {
+        Rc::new_zeroed_in(Global)
+    }
+}
+
+impl<T: ?Sized> Rc<T> {
+    /// Constructs an `Rc` from a raw pointer returned by `Rc::into_raw`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Rc::<T>::into_raw`, and each call
+    /// takes over one strong reference.
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        Rc::from_raw_in(ptr, Global)
+    }
+
+    /// Increments the strong count of the `Rc` behind `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Rc::<T>::into_raw`, and its strong
+    /// count must be at least 1.
+    pub unsafe fn increment_strong_count(ptr: *const T) {
+        Rc::increment_strong_count_in(ptr, Global)
+    }
+
+    /// Decrements the strong count of the `Rc` behind `ptr`, dropping it if it
+    /// was the last one.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Rc::<T>::into_raw`, and its strong
+    /// count must be at least 1.
+    pub unsafe fn decrement_strong_count(ptr: *const T) {
+        Rc::decrement_strong_count_in(ptr, Global)
+    }
+}
+
+impl<T, A: Allocator> Rc<T, A> {
+    /// Allocates `RcInner` in the given allocator.
+    pub fn new_in(v: T, alloc: A) -> Self {
+        let inner = Rc::<T, A>::allocate_sized(&alloc, false);
+        // SAFETY:
+        // `inner` is allocated for an `RcInner<T>`.
+        unsafe { ptr::addr_of_mut!((*inner.as_ptr()).value).write(v) };
+        Rc {
+            inner,
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs an `Rc` whose value can hold a `Weak` to itself.
+    ///
+    /// The `Weak` passed to `data_fn` doesn't upgrade until this returns.
+    pub fn new_cyclic_in<F>(data_fn: F, alloc: A) -> Self
+    where
+        F: FnOnce(&Weak<T, A>) -> T,
+        A: Clone,
+    {
+        let inner = Rc::<T, A>::allocate_sized(&alloc, false);
+        // SAFETY:
+        // `inner` is allocated, and the value is not initialized yet.
+        unsafe { inner.as_ref().decr_strong_ref_count() };
+
+        // this owns the implicit weak reference. if `data_fn` panics, it
+        // deallocates `RcInner` on drop.
+        let weak = Weak {
+            inner,
+            alloc: alloc.clone(),
+            _marker: PhantomData,
+        };
+        let value = data_fn(&weak);
+
+        // SAFETY:
+        // the strong count is still zero, so nobody can access the value.
+        unsafe {
+            ptr::addr_of_mut!((*inner.as_ptr()).value).write(value);
+            inner.as_ref().incr_strong_ref_count();
+        }
+        // the implicit weak reference now belongs to the strong one.
+        mem::forget(weak);
+
+        Rc {
+            inner,
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs an `Rc` with uninitialized contents in the given allocator.
+    pub fn new_uninit_in(alloc: A) -> Rc<MaybeUninit<T>, A> {
+        Rc {
+            inner: Rc::allocate_sized(&alloc, false),
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Constructs an `Rc` with zero-filled contents in the given allocator.
+    pub fn new_zeroed_in(alloc: A) -> Rc<MaybeUninit<T>, A> {
+        Rc {
+            inner: Rc::allocate_sized(&alloc, true),
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a mutable reference to the value, cloning it into a new allocation
+    /// first if it is shared.
+    ///
+    /// If only `Weak`s share the allocation, the value is moved out instead,
+    /// and the `Weak`s no longer upgrade.
+    pub fn make_mut(this: &mut Self) -> &mut T
+    where
+        T: Clone,
+        A: Clone,
+    {
+        if Rc::strong_count(this) != 1 {
+            *this = Rc::new_in(this.as_ref().clone(), this.alloc.clone());
+        } else if Rc::weak_count(this) != 0 {
+            // SAFETY:
+            // `this` is the last strong reference, and it gives up the value below.
+            let value = unsafe { ptr::read(&this.inner.as_ref().value) };
+            let old = mem::replace(this, Rc::new_in(value, this.alloc.clone()));
+            // SAFETY:
+            // the value was moved out above.
+            unsafe { Rc::release_last_strong(old) };
+        }
+
+        // SAFETY:
+        // `this` is unique now.
+        unsafe { &mut (*this.inner.as_ptr()).value }
+    }
+
+    /// Returns the value if `this` is the only strong reference, or `this` back.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        if Rc::strong_count(&this) != 1 {
+            return Err(this);
+        }
+
+        // SAFETY:
+        // `this` is the last strong reference, and it gives up the value.
+        unsafe {
+            let value = ptr::read(&this.inner.as_ref().value);
+            Rc::release_last_strong(this);
+            Ok(value)
+        }
+    }
+
+    /// Returns the value if `this` is the only strong reference, and drops it
+    /// otherwise.
+    pub fn into_inner(this: Self) -> Option<T> {
+        Rc::try_unwrap(this).ok()
+    }
+
+    /// Returns the value if `this` is the only strong reference, or a clone of it.
+    pub fn unwrap_or_clone(this: Self) -> T
+    where
+        T: Clone,
+    {
+        Rc::try_unwrap(this).unwrap_or_else(|rc| rc.as_ref().clone())
+    }
+
+    fn allocate_sized(alloc: &A, zeroed: bool) -> NonNull<RcInner<T>> {
+        Rc::allocate_for_layout(alloc, Layout::new::<T>(), zeroed, |mem| mem.cast())
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Rc<T, A> {
+    /// Allocates an `RcInner` with one strong reference, leaving the value
+    /// uninitialized (or zero-filled).
+    ///
+    /// `mem_to_inner` attaches the metadata of the value (e.g. the length of
+    /// a slice) to the allocated memory.
+    fn allocate_for_layout(
+        alloc: &A,
+        value_layout: Layout,
+        zeroed: bool,
+        mem_to_inner: impl FnOnce(*mut u8) -> *mut RcInner<T>,
+    ) -> NonNull<RcInner<T>> {
+        let layout = refcount::inner_layout::<Cell<usize>>(value_layout);
+        let result = if zeroed {
+            alloc.allocate_zeroed(layout)
+        } else {
+            alloc.allocate(layout)
+        };
+        let mem = match result {
+            Ok(ptr) => ptr.cast::<u8>().as_ptr(),
+            Err(_) => handle_alloc_error(layout),
+        };
+
+        // SAFETY:
+        // `mem` is allocated for an `RcInner<T>` whose value fits `value_layout`.
+        unsafe {
+            let inner = mem_to_inner(mem);
+            ptr::addr_of_mut!((*inner).strong_ref_count).write(Cell::new(1));
+            ptr::addr_of_mut!((*inner).weak_ref_count).write(Cell::new(1));
+            NonNull::new_unchecked(inner)
+        }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn allocator(this: &Self) -> &A {
+        &this.alloc
+    }
+
+    /// Returns a mutable reference to the value if there is no other `Rc` or
+    /// `Weak` to the same allocation.
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Rc::is_unique(this) {
+            // SAFETY:
+            // nobody else can access the value.
+            Some(unsafe { &mut (*this.inner.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the two `Rc`s point to the same allocation.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        ptr::addr_eq(this.inner.as_ptr(), other.inner.as_ptr())
+    }
+
+    fn is_unique(this: &Self) -> bool {
+        Rc::strong_count(this) == 1 && Rc::weak_count(this) == 0
+    }
+
+    /// Drops the last strong reference without dropping the value.
+    ///
+    /// # Safety
+    ///
+    /// `this` must be the last strong reference, and the value must have been
+    /// moved out.
+    unsafe fn release_last_strong(this: Self) {
+        let this = ManuallyDrop::new(this);
+        this.inner.as_ref().decr_strong_ref_count();
+        let alloc = ptr::read(&this.alloc);
+        release_weak(this.inner, &alloc);
+    }
+
+    /// Consumes the `Rc`, returning a pointer to the value. The strong reference
+    /// is kept, until it is converted back with `Rc::from_raw`.
+    pub fn into_raw(this: Self) -> *const T {
+        Rc::into_raw_with_allocator(this).0
+    }
+
+    /// Consumes the `Rc`, returning a pointer to the value and the allocator.
+    pub fn into_raw_with_allocator(this: Self) -> (*const T, A) {
+        let this = ManuallyDrop::new(this);
+        // SAFETY:
+        // `this` is never used (nor dropped) again, so the allocator is moved out.
+        let alloc = unsafe { ptr::read(&this.alloc) };
+        (Rc::as_ptr(&this), alloc)
+    }
+
+    /// Constructs an `Rc` from a raw pointer and its allocator.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Rc::<T, A>::into_raw`, allocated by
+    /// `alloc`, and each call takes over one strong reference.
+    pub unsafe fn from_raw_in(ptr: *const T, alloc: A) -> Self {
+        Rc {
+            inner: NonNull::new_unchecked(
+                refcount::inner_from_value::<Cell<usize>, T>(ptr) as *mut RcInner<T>
+            ),
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a pointer to the value, without consuming the `Rc`.
+    pub fn as_ptr(this: &Self) -> *const T {
+        // SAFETY:
+        // `inner` is alive, and no reference to `value` is created.
+        unsafe { ptr::addr_of!((*this.inner.as_ptr()).value) }
+    }
+
+    /// # Safety
+    ///
+    /// Same as `Rc::increment_strong_count`, and `ptr` must be allocated by `alloc`.
+    pub unsafe fn increment_strong_count_in(ptr: *const T, alloc: A)
+    where
+        A: Clone,
+    {
+        let rc = ManuallyDrop::new(Rc::from_raw_in(ptr, alloc));
+        let _rc: ManuallyDrop<_> = rc.clone();
+    }
+
+    /// # Safety
+    ///
+    /// Same as `Rc::decrement_strong_count`, and `ptr` must be allocated by `alloc`.
+    pub unsafe fn decrement_strong_count_in(ptr: *const T, alloc: A) {
+        drop(Rc::from_raw_in(ptr, alloc));
+    }
+
+    pub fn strong_count(this: &Self) -> usize {
+        unsafe { this.inner.as_ref().strong_ref_count() }
+    }
+
+    /// Returns the number of `Weak`s, not counting the implicit weak
+    /// reference held by the strong ones.
+    pub fn weak_count(this: &Self) -> usize {
+        unsafe { this.inner.as_ref().weak_ref_count() - 1 }
+    }
+
+    pub fn downgrade(this: &Self) -> Weak<T, A>
+    where
+        A: Clone,
+    {
+        // increment weak_ref_count
+        unsafe { this.inner.as_ref().borrow_mut().incr_weak_ref_count() };
+        Weak {
+            inner: this.inner,
+            alloc: this.alloc.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, A: Allocator> Rc<MaybeUninit<T>, A> {
+    /// Converts to `Rc<T, A>`.
+    ///
+    /// # Safety
+    ///
+    /// The value must be initialized.
+    pub unsafe fn assume_init(self) -> Rc<T, A> {
+        let (raw, alloc) = Rc::into_raw_with_allocator(self);
+        Rc::from_raw_in(raw.cast::<T>(), alloc)
+    }
+}
+
+impl<T> Rc<[T]> {
+    /// Constructs an `Rc` slice with uninitialized contents.
+    pub fn new_uninit_slice(len: usize) -> Rc<[MaybeUninit<T>]> {
+        Rc::new_uninit_slice_in(len, Global)
+    }
+}
+
+impl<T, A: Allocator> Rc<[T], A> {
+    /// Constructs an `Rc` slice with uninitialized contents in the given allocator.
+    pub fn new_uninit_slice_in(len: usize, alloc: A) -> Rc<[MaybeUninit<T>], A> {
+        Rc {
+            inner: Rc::<[MaybeUninit<T>], A>::allocate_for_slice(len, &alloc),
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Allocates the counts and `len` elements in a single block.
+    fn allocate_for_slice(len: usize, alloc: &A) -> NonNull<RcInner<[T]>> {
+        let value_layout = Layout::array::<T>(len).unwrap();
+        Rc::allocate_for_layout(alloc, value_layout, false, |mem| {
+            ptr::slice_from_raw_parts_mut(mem.cast::<T>(), len) as *mut RcInner<[T]>
+        })
+    }
+}
+
+impl<T, A: Allocator> Rc<[MaybeUninit<T>], A> {
+    /// Converts to `Rc<[T], A>`.
+    ///
+    /// # Safety
+    ///
+    /// All the elements must be initialized.
+    pub unsafe fn assume_init(self) -> Rc<[T], A> {
+        let (raw, alloc) = Rc::into_raw_with_allocator(self);
+        Rc::from_raw_in(raw as *const [T], alloc)
+    }
+}
+
+impl<A: Allocator> Rc<dyn Any, A> {
+    /// Downcasts to a concrete type, or returns `self` back if the value is
+    /// not a `T`.
+    pub fn downcast<T: Any>(self) -> Result<Rc<T, A>, Self> {
+        if self.as_ref().is::<T>() {
+            let (raw, alloc) = Rc::into_raw_with_allocator(self);
+            // SAFETY:
+            // the value is a `T`.
+            unsafe { Ok(Rc::from_raw_in(raw as *const T, alloc)) }
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T: Clone> From<&[T]> for Rc<[T]> {
+    fn from(v: &[T]) -> Self {
+        let mut rc = Rc::new_uninit_slice(v.len());
+        // if `clone` panics, the elements cloned so far are dropped, and then
+        // the allocation is freed by `rc`.
+        refcount::write_clones(Rc::get_mut(&mut rc).unwrap(), v);
+        // SAFETY:
+        // all the elements are written above.
+        unsafe { rc.assume_init() }
+    }
+}
+
+impl<T, A: Allocator + Clone> From<Vec<T, A>> for Rc<[T], A> {
+    /// Moves the elements into a new allocation, and frees the buffer of `v`.
+    fn from(mut v: Vec<T, A>) -> Self {
+        let len = v.len();
+        let alloc = v.allocator().clone();
+        let inner = Rc::<[T], A>::allocate_for_slice(len, &alloc);
+        // SAFETY:
+        // `inner` has room for `len` elements, and they are moved out of `v`.
+        unsafe {
+            let dst = ptr::addr_of_mut!((*inner.as_ptr()).value).cast::<T>();
+            ptr::copy_nonoverlapping(v.as_ptr(), dst, len);
+            v.set_len(0);
+        }
+        Rc {
+            inner,
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> FromIterator<T> for Rc<[T]> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        // the length isn't known upfront, so the elements are collected first.
+        Rc::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl From<&str> for Rc<str> {
+    fn from(s: &str) -> Self {
+        let bytes: Rc<[u8]> = Rc::from(s.as_bytes());
+        let (raw, alloc) = Rc::into_raw_with_allocator(bytes);
+        // SAFETY:
+        // the bytes are copied from a `str`.
+        unsafe { Rc::from_raw_in(raw as *const str, alloc) }
+    }
+}
+
+impl<A: Allocator> From<Rc<str, A>> for Rc<[u8], A> {
+    fn from(rc: Rc<str, A>) -> Self {
+        let (raw, alloc) = Rc::into_raw_with_allocator(rc);
+        // SAFETY:
+        // `str` has the same layout as `[u8]`.
+        unsafe { Rc::from_raw_in(raw as *const [u8], alloc) }
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for Rc<T, A> {
+    /// We just increment strong_ref_count.
+    fn clone(&self) -> Self {
+        unsafe {
+            self.inner.as_ref().incr_strong_ref_count();
+        }
+        Rc {
+            inner: self.inner,
+            alloc: self.alloc.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Deref for Rc<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY:
+        // the value is alive as long as this strong reference is.
+        unsafe { &self.inner.as_ref().value }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> AsRef<T> for Rc<T, A> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Borrow<T> for Rc<T, A> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized + fmt::Debug, A: Allocator> fmt::Debug for Rc<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display, A: Allocator> fmt::Display for Rc<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> fmt::Pointer for Rc<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&Rc::as_ptr(self), f)
+    }
+}
+
+impl<T: ?Sized + PartialEq, A: Allocator> PartialEq for Rc<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        PartialEq::eq(&**self, &**other)
+    }
+}
+
+impl<T: ?Sized + Eq, A: Allocator> Eq for Rc<T, A> {}
+
+impl<T: ?Sized + PartialOrd, A: Allocator> PartialOrd for Rc<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: ?Sized + Ord, A: Allocator> Ord for Rc<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+impl<T: ?Sized + Hash, A: Allocator> Hash for Rc<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<T: Default> Default for Rc<T> {
+    fn default() -> Self {
+        Rc::new(T::default())
+    }
+}
+
+impl<T> Default for Rc<[T]> {
+    fn default() -> Self {
+        Rc::from(Vec::new())
+    }
+}
+
+impl Default for Rc<str> {
+    fn default() -> Self {
+        Rc::from("")
+    }
+}
+
+impl<T> From<T> for Rc<T> {
+    fn from(x: T) -> Self {
+        Rc::new(x)
+    }
+}
+
+impl<T, A: Allocator> From<Bocs<T, A>> for Rc<T, A> {
+    /// Moves the value into a new allocation, and frees the one of the `Bocs`.
+    fn from(b: Bocs<T, A>) -> Self {
+        let (raw, alloc) = Bocs::into_raw_with_allocator(b);
+        // SAFETY:
+        // `raw` points to a valid `T` allocated by `alloc`, which is moved out
+        // here, and the memory is deallocated without dropping it.
+        let value = unsafe {
+            let value = ptr::read(raw);
+            if mem::size_of::<T>() != 0 {
+                alloc.deallocate(NonNull::new_unchecked(raw).cast(), Layout::new::<T>());
+            }
+            value
+        };
+        Rc::new_in(value, alloc)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for Rc<T, A> {
+    /// The last strong reference drops `T`, and releases the implicit weak
+    /// reference shared by all the strong ones. `RcInner` is deallocated when
+    /// the last weak reference goes away.
+    fn drop(&mut self) {
+        // SAFETY:
+        // `inner` is alive as long as this `Rc` is.
+        let inner = unsafe { self.inner.as_ref() };
+        inner.decr_strong_ref_count();
+        if inner.strong_ref_count() != 0 {
+            return;
+        }
+
+        // SAFETY:
+        // this was the last strong reference, so nobody can access `value` anymore.
+        // `Weak`s never touch it once the strong count is zero.
+        unsafe { ptr::drop_in_place(ptr::addr_of_mut!((*self.inner.as_ptr()).value)) };
+
+        // SAFETY:
+        // the implicit weak reference keeps `inner` alive until here.
+        unsafe { release_weak(self.inner, &self.alloc) };
+    }
+}
+
+/// Decrements the weak count, and deallocates `RcInner` if it was the last one.
+///
+/// # Safety
+///
+/// `inner` must be alive, and the caller must own one of its weak references.
+/// `value` must already have been dropped if the weak count reaches zero.
+unsafe fn release_weak<T: ?Sized, A: Allocator>(inner: NonNull<RcInner<T>>, alloc: &A) {
+    // a `Weak` may be dropped by the value's own `drop`, so no reference to
+    // the whole `RcInner` (which covers the value) is created until it's gone.
+    let weak = &*ptr::addr_of!((*inner.as_ptr()).weak_ref_count);
+    weak.set(weak.get() - 1);
+    if weak.get() == 0 {
+        alloc.deallocate(inner.cast(), Layout::for_value(inner.as_ref()));
+    }
+}
+
+/// A weak reference to an `Rc`, which is `!Send` and `!Sync` like `Rc`.
+///
+/// ```compile_fail,E0277
+/// use alloc_orc::rc::Rc;
+///
+/// let weak = Rc::downgrade(&Rc::new(1));
+/// std::thread::spawn(move || drop(weak));
+/// ```
+pub struct Weak<T: ?Sized, A: Allocator = Global> {
+    // this is the `usize::MAX` sentinel when created by `Weak::new()`, and it
+    // is never dereferenced then.
+    inner: NonNull<RcInner<T>>,
+    alloc: A,
+    /// See `Rc::_marker`.
+    _marker: PhantomData<*const RcInner<T>>,
+}
+
+/// The reference counts of an `RcInner`, borrowed without the value, which a
+/// `Weak` may outlive or be used from within the `drop` of.
+struct WeakInner<'a> {
+    strong: &'a Cell<usize>,
+    weak: &'a Cell<usize>,
+}
+
+impl<T> Weak<T> {
+    /// Creates a `Weak` which never upgrades, without allocating.
+    pub fn new() -> Self {
+        Weak::new_in(Global)
+    }
+}
+
+impl<T: ?Sized> Weak<T> {
+    /// Constructs a `Weak` from a raw pointer returned by `Weak::into_raw`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Weak::<T>::into_raw`, and each call
+    /// takes over one weak reference.
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        Weak::from_raw_in(ptr, Global)
+    }
+}
+
+impl<T> Default for Weak<T> {
+    fn default() -> Self {
+        Weak::new()
+    }
+}
+
+impl<T, A: Allocator> Weak<T, A> {
+    /// Creates a `Weak` which never upgrades, without allocating.
+    pub fn new_in(alloc: A) -> Self {
+        Weak {
+            // SAFETY:
+            // `usize::MAX` is not null.
+            inner: unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(usize::MAX)) },
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Weak<T, A> {
+    /// Returns the reference counts, or `None` if this came from `Weak::new`.
+    fn inner(&self) -> Option<WeakInner<'_>> {
+        let ptr = self.inner.as_ptr();
+        if is_dangling(ptr) {
+            None
+        } else {
+            // SAFETY:
+            // `inner` is kept alive by this weak reference, and no reference
+            // to `value` is created.
+            unsafe {
+                Some(WeakInner {
+                    strong: &*ptr::addr_of!((*ptr).strong_ref_count),
+                    weak: &*ptr::addr_of!((*ptr).weak_ref_count),
+                })
+            }
+        }
+    }
+
+    /// Returns an `Rc` to the value, or `None` if it has already been dropped.
+    pub fn upgrade(&self) -> Option<Rc<T, A>>
+    where
+        A: Clone,
+    {
+        let inner = self.inner()?;
+        if inner.strong.get() == 0 {
+            return None;
+        }
+
+        inner.strong.set(inner.strong.get() + 1);
+        Some(Rc {
+            inner: self.inner,
+            alloc: self.alloc.clone(),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Returns the number of `Rc`s pointing to the value.
+    pub fn strong_count(&self) -> usize {
+        self.inner().map_or(0, |inner| inner.strong.get())
+    }
+
+    /// Returns the number of `Weak`s pointing to the value, or zero if there is
+    /// no strong reference left.
+    pub fn weak_count(&self) -> usize {
+        match self.inner() {
+            // the strong ones hold one implicit weak reference.
+            Some(inner) if inner.strong.get() > 0 => inner.weak.get() - 1,
+            _ => 0,
+        }
+    }
+
+    /// Returns `true` if the two `Weak`s point to the same allocation, or both
+    /// came from `Weak::new`.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        ptr::addr_eq(self.inner.as_ptr(), other.inner.as_ptr())
+    }
+
+    /// Consumes the `Weak`, returning a pointer to the value. The weak reference
+    /// is kept, until it is converted back with `Weak::from_raw`.
+    pub fn into_raw(self) -> *const T {
+        self.into_raw_with_allocator().0
+    }
+
+    /// Consumes the `Weak`, returning a pointer to the value and the allocator.
+    pub fn into_raw_with_allocator(self) -> (*const T, A) {
+        let this = ManuallyDrop::new(self);
+        // SAFETY:
+        // `this` is never used (nor dropped) again, so the allocator is moved out.
+        let alloc = unsafe { ptr::read(&this.alloc) };
+        (this.as_ptr(), alloc)
+    }
+
+    /// Constructs a `Weak` from a raw pointer and its allocator.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Weak::<T, A>::into_raw`, allocated by
+    /// `alloc`, and each call takes over one weak reference.
+    pub unsafe fn from_raw_in(ptr: *const T, alloc: A) -> Self {
+        let inner = if is_dangling(ptr) {
+            ptr as *mut RcInner<T>
+        } else {
+            refcount::inner_from_value::<Cell<usize>, T>(ptr) as *mut RcInner<T>
+        };
+        Weak {
+            inner: NonNull::new_unchecked(inner),
+            alloc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a pointer to the value.
+    ///
+    /// The pointer is only valid to dereference while there is a strong reference,
+    /// and it is dangling if this came from `Weak::new`.
+    pub fn as_ptr(&self) -> *const T {
+        let ptr = self.inner.as_ptr();
+        if is_dangling(ptr) {
+            ptr as *const T
+        } else {
+            // SAFETY:
+            // `inner` is alive, and no reference to `value` is created.
+            unsafe { ptr::addr_of!((*ptr).value) }
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for Weak<T, A> {
+    fn clone(&self) -> Self {
+        if let Some(inner) = self.inner() {
+            inner.weak.set(inner.weak.get() + 1);
+        }
+        Weak {
+            inner: self.inner,
+            alloc: self.alloc.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for Weak<T, A> {
+    fn drop(&mut self) {
+        if is_dangling(self.inner.as_ptr()) {
+            // there is no allocation to release.
+            return;
+        }
+
+        // SAFETY:
+        // `inner` is kept alive by this weak reference, and the strong references
+        // hold the implicit one until `value` is dropped.
+        unsafe { release_weak(self.inner, &self.alloc) };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::alloc::{LeakGuard, TrackingAllocator};
+    use core_orc::cell::RefCell;
+
+    use super::*;
+    #[test]
+    fn inner_strong_count_is_updated_collectly() {
+        let _leaks = LeakGuard::new();
+        let rc = Rc::new(42);
+        assert!(rc.as_ref() == &42);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        {
+            let rc2 = rc.clone();
+            assert_eq!(Rc::strong_count(&rc2), 2);
+            assert_eq!(Rc::strong_count(&rc), 2);
+        }
+
+        assert_eq!(Rc::strong_count(&rc), 1)
+    }
+
+    #[allow(dead_code)]
+    #[test]
+    fn cycle_reference_by_weak_ref_work_collectly() {
+        // no `LeakGuard`: the strong cycle below leaks by design.
+        // leak example
+        {
+            struct Node {
+                value: i32,
+                next: RefCell<Option<Rc<Node>>>,
+            }
+
+            let node1 = Rc::new(Node {
+                value: 1,
+                next: RefCell::new(None),
+            });
+
+            let node2 = Rc::new(Node {
+                value: 2,
+                next: RefCell::new(Some(Rc::clone(&node1))),
+            });
+
+            // Create a circular reference
+            *node1.as_ref().next.borrow_mut() = Some(Rc::clone(&node2));
+
+            // drop node1 here.
+            drop(node1);
+
+            // but, strong ref count in node2 is still two.
+            assert_eq!(Rc::strong_count(&node2), 2);
+        }
+
+        {
+            struct Node {
+                value: i32,
+                next: RefCell<Option<Weak<Node>>>,
+            }
+
+            let node1 = Rc::new(Node {
+                value: 1,
+                next: RefCell::new(None),
+            });
+
+            let node2 = Rc::new(Node {
+                value: 2,
+                next: RefCell::new(Some(Rc::downgrade(&Rc::clone(&node1)))),
+            });
+
+            // Create a circular reference
+            *node1.as_ref().next.borrow_mut() = Some(Rc::downgrade(&Rc::clone(&node2)));
+            // drop node1 here.
+            drop(node1);
+
+            // node1 is gone, and so is its weak reference to node2.
+            assert_eq!(Rc::strong_count(&node2), 1);
+            assert_eq!(Rc::weak_count(&node2), 0);
+        }
+    }
+
+    #[test]
+    fn rc_is_deallocated_with_the_last_strong_ref() {
+        let _leaks = LeakGuard::new();
+        let rc = Rc::new(String::from("rc"));
+        let rc2 = rc.clone();
+        drop(rc);
+        assert_eq!(rc2.as_ref(), "rc");
+    }
+
+    #[derive(Clone, Debug)]
+    struct DropCounter<'a>(&'a std::cell::Cell<usize>);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn value_is_dropped_once_by_the_last_strong_ref() {
+        let _leaks = LeakGuard::new();
+        let drops = std::cell::Cell::new(0);
+        let rc = Rc::new(DropCounter(&drops));
+        let rc2 = rc.clone();
+        let weak = Rc::downgrade(&rc);
+
+        drop(rc);
+        assert_eq!(drops.get(), 0);
+        drop(rc2);
+        assert_eq!(drops.get(), 1);
+        drop(weak);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn inner_is_deallocated_with_the_last_weak_ref() {
+        let _leaks = LeakGuard::new();
+        let rc = Rc::new(String::from("inner"));
+        let weak = Rc::downgrade(&rc);
+        let weak2 = Rc::downgrade(&rc);
+        assert_eq!(Rc::weak_count(&rc), 2);
+
+        let checkpoint = TrackingAllocator::checkpoint();
+        drop(rc);
+        // the string is freed, but `RcInner` is kept alive by the `Weak`s.
+        assert_eq!(checkpoint.leaked_allocations(), -1);
+        drop(weak);
+        assert_eq!(checkpoint.leaked_allocations(), -1);
+        drop(weak2);
+        assert_eq!(checkpoint.leaked_allocations(), -2);
+    }
+
+    #[test]
+    fn churn_does_not_leak() {
+        let _leaks = LeakGuard::new();
+        let drops = std::cell::Cell::new(0);
+        let mut weaks = crate::vec::Vec::new();
+        for _ in 0..100 {
+            let rc = Rc::new(DropCounter(&drops));
+            let rc2 = rc.clone();
+            weaks.push(Rc::downgrade(&rc2));
+            drop(rc2);
+        }
+        assert_eq!(drops.get(), 100);
+    }
+
+    #[test]
+    fn weak_new_never_upgrades() {
+        let _leaks = LeakGuard::new();
+        let checkpoint = TrackingAllocator::checkpoint();
+        let weak: Weak<String> = Weak::new();
+        let weak2 = weak.clone();
+        assert_eq!(checkpoint.allocations(), 0);
+
+        assert!(weak.upgrade().is_none());
+        assert_eq!(weak.strong_count(), 0);
+        assert_eq!(weak.weak_count(), 0);
+        assert!(weak.ptr_eq(&weak2));
+        assert!(!weak.ptr_eq(&Rc::downgrade(&Rc::new(String::new()))));
+    }
+
+    #[test]
+    fn weak_upgrades_while_a_strong_ref_is_alive() {
+        let _leaks = LeakGuard::new();
+        let rc = Rc::new(String::from("parent"));
+        let weak = Rc::downgrade(&rc);
+        let weak2 = weak.clone();
+        assert_eq!(weak.strong_count(), 1);
+        assert_eq!(weak.weak_count(), 2);
+        assert!(weak.ptr_eq(&weak2));
+        assert_eq!(weak.as_ptr(), rc.as_ref() as *const String);
+
+        {
+            let upgraded = weak.upgrade().unwrap();
+            assert_eq!(upgraded.as_ref(), "parent");
+            assert_eq!(Rc::strong_count(&rc), 2);
+        }
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        drop(rc);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(weak2.strong_count(), 0);
+        assert_eq!(weak2.weak_count(), 0);
+    }
+
+    #[test]
+    fn parent_back_pointer() {
+        struct TreeNode {
+            name: &'static str,
+            parent: Weak<TreeNode>,
+            children: std::cell::RefCell<crate::vec::Vec<Rc<TreeNode>>>,
+        }
+
+        let _leaks = LeakGuard::new();
+        let root = Rc::new(TreeNode {
+            name: "root",
+            parent: Weak::new(),
+            children: std::cell::RefCell::new(crate::vec::Vec::new()),
+        });
+        let leaf = Rc::new(TreeNode {
+            name: "leaf",
+            parent: Rc::downgrade(&root),
+            children: std::cell::RefCell::new(crate::vec::Vec::new()),
+        });
+        root.as_ref().children.borrow_mut().push(leaf.clone());
+
+        assert_eq!(
+            leaf.as_ref().parent.upgrade().unwrap().as_ref().name,
+            "root"
+        );
+        assert!(root.as_ref().parent.upgrade().is_none());
+        assert_eq!(root.as_ref().children.borrow().len(), 1);
+    }
+
+    #[test]
+    fn weak_is_usable_from_the_drop_of_the_value() {
+        struct Node<'a> {
+            this: std::cell::RefCell<Weak<Node<'a>>>,
+            dropped: &'a std::cell::Cell<bool>,
+        }
+
+        impl Drop for Node<'_> {
+            fn drop(&mut self) {
+                // the value is being dropped in place, so these must not
+                // create a reference which covers it.
+                let this = self.this.borrow();
+                assert!(this.upgrade().is_none());
+                drop(this.clone());
+                assert_eq!(this.strong_count(), 0);
+                assert_eq!(this.weak_count(), 0);
+                self.dropped.set(true);
+            }
+        }
+
+        let _leaks = LeakGuard::new();
+        let dropped = std::cell::Cell::new(false);
+        let rc = Rc::new(Node {
+            this: std::cell::RefCell::new(Weak::new()),
+            dropped: &dropped,
+        });
+        *rc.as_ref().this.borrow_mut() = Rc::downgrade(&rc);
+        drop(rc);
+        assert!(dropped.get());
+    }
+
+    #[test]
+    fn get_mut_requires_unique_ownership() {
+        let _leaks = LeakGuard::new();
+        let mut rc = Rc::new(1);
+        *Rc::get_mut(&mut rc).unwrap() += 1;
+        assert_eq!(*rc.as_ref(), 2);
+
+        let rc2 = rc.clone();
+        assert!(Rc::get_mut(&mut rc).is_none());
+        drop(rc2);
+
+        let weak = Rc::downgrade(&rc);
+        assert!(Rc::get_mut(&mut rc).is_none());
+        drop(weak);
+        assert!(Rc::get_mut(&mut rc).is_some());
+    }
+
+    #[test]
+    fn make_mut_clones_on_write() {
+        let _leaks = LeakGuard::new();
+        let mut a = Rc::new(String::from("persistent"));
+        let b = a.clone();
+
+        Rc::make_mut(&mut a).push_str(" copy");
+        assert_eq!(a.as_ref(), "persistent copy");
+        assert_eq!(b.as_ref(), "persistent");
+        assert!(!Rc::ptr_eq(&a, &b));
+
+        // `a` is unique now, so it is mutated in place.
+        let ptr = a.as_ref() as *const String;
+        Rc::make_mut(&mut a).push('!');
+        assert_eq!(a.as_ref() as *const String, ptr);
+    }
+
+    #[test]
+    fn make_mut_disassociates_weaks() {
+        let _leaks = LeakGuard::new();
+        let drops = std::cell::Cell::new(0);
+        let mut rc = Rc::new(DropCounter(&drops));
+        let weak = Rc::downgrade(&rc);
+
+        Rc::make_mut(&mut rc);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(Rc::weak_count(&rc), 0);
+        assert_eq!(drops.get(), 0);
+
+        drop(rc);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn try_unwrap_and_into_inner() {
+        let _leaks = LeakGuard::new();
+        let drops = std::cell::Cell::new(0);
+        let rc = Rc::new(DropCounter(&drops));
+        let rc2 = rc.clone();
+        let weak = Rc::downgrade(&rc);
+
+        let rc = Rc::try_unwrap(rc).err().unwrap();
+        assert!(Rc::into_inner(rc2).is_none());
+        assert_eq!(drops.get(), 0);
+
+        let value = Rc::into_inner(rc).unwrap();
+        assert!(weak.upgrade().is_none());
+        assert_eq!(drops.get(), 0);
+        drop(value);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn unwrap_or_clone() {
+        let _leaks = LeakGuard::new();
+        let rc = Rc::new(String::from("value"));
+        let rc2 = rc.clone();
+        assert_eq!(Rc::unwrap_or_clone(rc), "value");
+        assert_eq!(Rc::strong_count(&rc2), 1);
+        assert_eq!(Rc::unwrap_or_clone(rc2), "value");
+    }
+
+    #[test]
+    fn into_raw_and_from_raw() {
+        use std::ffi::c_void;
+
+        let _leaks = LeakGuard::new();
+
+        extern "C" fn callback(data: *mut c_void) {
+            // SAFETY:
+            // `data` was created by `Rc::into_raw` below. the callback takes its
+            // own strong reference, and releases it when `rc` is dropped.
+            unsafe { Rc::increment_strong_count(data as *const String) };
+            let rc = unsafe { Rc::from_raw(data as *const String) };
+            assert_eq!(rc.as_ref(), "user data");
+            assert_eq!(Rc::strong_count(&rc), 3);
+        }
+
+        let rc = Rc::new(String::from("user data"));
+        let raw = Rc::into_raw(rc.clone());
+        assert_eq!(raw, Rc::as_ptr(&rc));
+        callback(raw as *mut c_void);
+        assert_eq!(Rc::strong_count(&rc), 2);
+
+        unsafe { Rc::decrement_strong_count(raw) };
+        assert_eq!(Rc::strong_count(&rc), 1);
+    }
+
+    #[test]
+    fn from_raw_with_any_alignment() {
+        #[repr(align(64))]
+        struct Aligned(u8);
+
+        let _leaks = LeakGuard::new();
+        let raw = Rc::into_raw(Rc::new(Aligned(1)));
+        assert_eq!(raw as usize % 64, 0);
+        let rc = unsafe { Rc::from_raw(raw) };
+        assert_eq!(rc.as_ref().0, 1);
+        assert_eq!(Rc::strong_count(&rc), 1);
+
+        let raw = Rc::into_raw(Rc::new(1u8));
+        let rc = unsafe { Rc::from_raw(raw) };
+        assert_eq!(*rc.as_ref(), 1);
+    }
+
+    #[test]
+    fn weak_into_raw_and_from_raw() {
+        let _leaks = LeakGuard::new();
+        let rc = Rc::new(String::from("weak"));
+        let raw = Rc::downgrade(&rc).into_raw();
+        assert_eq!(raw, Rc::as_ptr(&rc));
+        assert_eq!(Rc::weak_count(&rc), 1);
+
+        let weak = unsafe { Weak::from_raw(raw) };
+        assert_eq!(weak.upgrade().unwrap().as_ref(), "weak");
+        drop(rc);
+        assert!(weak.upgrade().is_none());
+
+        let raw = Weak::<String>::new().into_raw();
+        let weak = unsafe { Weak::from_raw(raw) };
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn new_cyclic_builds_self_referential_nodes() {
+        struct Node {
+            value: i32,
+            this: Weak<Node>,
+        }
+
+        let _leaks = LeakGuard::new();
+        let node = Rc::new_cyclic(|weak| {
+            // the value is not constructed yet.
+            assert!(weak.upgrade().is_none());
+            Node {
+                value: 1,
+                this: weak.clone(),
+            }
+        });
+
+        let this = node.as_ref().this.upgrade().unwrap();
+        assert!(Rc::ptr_eq(&node, &this));
+        assert_eq!(this.as_ref().value, 1);
+        assert_eq!(Rc::strong_count(&node), 2);
+        assert_eq!(Rc::weak_count(&node), 1);
+    }
+
+    #[test]
+    fn new_cyclic_panic() {
+        // no `LeakGuard`: the test harness allocates to capture the panic message.
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let stash = std::cell::RefCell::new(None);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            Rc::<String>::new_cyclic(|weak| {
+                *stash.borrow_mut() = Some(weak.clone());
+                panic!("construction failed");
+            })
+        }));
+        assert!(result.is_err());
+
+        // the allocation is kept alive by the stashed `Weak`, but never upgrades.
+        let weak = stash.borrow_mut().take().unwrap();
+        assert!(weak.upgrade().is_none());
+        assert_eq!(weak.strong_count(), 0);
+    }
+
+    #[test]
+    fn new_uninit_and_new_zeroed() {
+        let _leaks = LeakGuard::new();
+        let mut rc = Rc::<String>::new_uninit();
+        Rc::get_mut(&mut rc).unwrap().write(String::from("init"));
+        let rc = unsafe { rc.assume_init() };
+        assert_eq!(rc.as_ref(), "init");
+
+        let rc = Rc::<[u64; 4]>::new_zeroed();
+        let rc = unsafe { rc.assume_init() };
+        assert_eq!(*rc.as_ref(), [0; 4]);
+    }
+
+    #[test]
+    fn rc_slice_from_vec_and_iter() {
+        use core_orc::into_iterator::IntoIterator;
+        use core_orc::iter::traits::iterator::Iterator;
+
+        let _leaks = LeakGuard::new();
+        let drops = std::cell::Cell::new(0);
+        let v = crate::vec![DropCounter(&drops), DropCounter(&drops)];
+        let rc: Rc<[DropCounter]> = Rc::from(v);
+        assert_eq!(rc.as_ref().len(), 2);
+        assert_eq!(drops.get(), 0);
+        let rc2 = rc.clone();
+        drop(rc);
+        assert_eq!(drops.get(), 0);
+        drop(rc2);
+        assert_eq!(drops.get(), 2);
+
+        let rc: Rc<[String]> = crate::vec![1, 2, 3]
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect();
+        assert_eq!(rc.as_ref(), ["1", "2", "3"]);
+
+        let rc: Rc<[String]> = Rc::from(&[String::from("a")][..]);
+        assert_eq!(rc.as_ref(), ["a"]);
+
+        let rc: Rc<[u8]> = Rc::from(&[][..]);
+        assert!(rc.as_ref().is_empty());
+        let rc: Rc<[()]> = Rc::from(crate::vec![(), ()]);
+        assert_eq!(rc.as_ref().len(), 2);
+    }
+
+    #[test]
+    fn rc_from_slice_drops_the_clones_if_clone_panics() {
+        // no `LeakGuard`: the test harness allocates to capture the panic message.
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        struct PanicOnClone<'a> {
+            clones: &'a std::cell::Cell<usize>,
+            drops: &'a std::cell::Cell<usize>,
+        }
+
+        impl Clone for PanicOnClone<'_> {
+            fn clone(&self) -> Self {
+                if self.clones.get() == 2 {
+                    panic!("clone failed");
+                }
+                self.clones.set(self.clones.get() + 1);
+                PanicOnClone {
+                    clones: self.clones,
+                    drops: self.drops,
+                }
+            }
+        }
+
+        impl Drop for PanicOnClone<'_> {
+            fn drop(&mut self) {
+                self.drops.set(self.drops.get() + 1);
+            }
+        }
+
+        let clones = std::cell::Cell::new(0);
+        let drops = std::cell::Cell::new(0);
+        let v = [(); 3].map(|_| PanicOnClone {
+            clones: &clones,
+            drops: &drops,
+        });
+        let result = catch_unwind(AssertUnwindSafe(|| Rc::<[_]>::from(&v[..])));
+        assert!(result.is_err());
+        assert_eq!(clones.get(), 2);
+        assert_eq!(drops.get(), 2);
+    }
+
+    #[test]
+    fn rc_slice_is_a_single_allocation() {
+        let _leaks = LeakGuard::new();
+        let checkpoint = TrackingAllocator::checkpoint();
+        let rc: Rc<[u64]> = Rc::from(&[1, 2, 3][..]);
+        assert_eq!(checkpoint.allocations(), 1);
+        assert_eq!(rc.as_ref(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn rc_str() {
+        let _leaks = LeakGuard::new();
+        let interned: Rc<str> = Rc::from("interned");
+        let other = interned.clone();
+        assert!(Rc::ptr_eq(&interned, &other));
+        assert_eq!(other.as_ref(), "interned");
+
+        let weak = Rc::downgrade(&interned);
+        let raw = Rc::into_raw(interned);
+        let interned = unsafe { Rc::from_raw(raw) };
+        assert_eq!(weak.upgrade().unwrap().as_ref(), "interned");
+
+        drop(other);
+        let bytes: Rc<[u8]> = Rc::from(interned);
+        assert_eq!(bytes.as_ref(), b"interned");
+        drop(bytes);
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn rc_dyn_trait() {
+        use std::fmt::Debug;
+
+        let _leaks = LeakGuard::new();
+        let drops = std::cell::Cell::new(0);
+        let rc: Rc<dyn Debug> = unsize_rc!(Rc::new(String::from("dyn")) => dyn Debug);
+        assert_eq!(format!("{:?}", rc.as_ref()), "\"dyn\"");
+
+        #[derive(Debug)]
+        #[repr(align(64))]
+        struct Aligned<'a>(#[allow(dead_code)] DropCounter<'a>);
+
+        let rc = Rc::new(Aligned(DropCounter(&drops)));
+        let weak = Rc::downgrade(&rc);
+        let rc: Rc<dyn Debug + '_> = unsize_rc!(rc => dyn Debug + '_);
+        let raw = Rc::into_raw(rc);
+        assert_eq!(raw as *const u8 as usize % 64, 0);
+        let rc = unsafe { Rc::from_raw(raw) };
+        assert_eq!(weak.strong_count(), 1);
+        drop(rc);
+        assert_eq!(drops.get(), 1);
+    }
+
+    #[test]
+    fn rc_downcast() {
+        let _leaks = LeakGuard::new();
+        let rc: Rc<dyn Any> = unsize_rc!(Rc::new(String::from("any")) => dyn Any);
+        let rc = match rc.downcast::<i32>() {
+            Ok(_) => panic!("not an i32"),
+            Err(rc) => rc,
+        };
+        let s = rc.downcast::<String>().unwrap();
+        assert_eq!(s.as_ref(), "any");
+    }
+
+    #[test]
+    fn rc_derefs_and_prints() {
+        let _leaks = LeakGuard::new();
+        let rc = Rc::new(String::from("deref"));
+        assert_eq!(rc.len(), 5);
+        assert!(rc.starts_with("de"));
+        assert_eq!(format!("{} {:?}", rc, rc), "deref \"deref\"");
+        assert_eq!(format!("{:p}", rc), format!("{:p}", Rc::as_ptr(&rc)));
+
+        let rc: Rc<dyn Any> = unsize_rc!(Rc::new(1) => dyn Any);
+        assert_eq!(*rc.downcast::<i32>().unwrap(), 1);
+    }
+
+    #[test]
+    fn rc_compares_and_hashes_by_value() {
+        use std::collections::{BTreeSet, HashMap};
+
+        let _leaks = LeakGuard::new();
+        let a = Rc::new(1);
+        let b = Rc::new(2);
+        assert!(a < b);
+        assert_eq!(a.cmp(&b), Ordering::Less);
+        assert_eq!(a, Rc::new(1));
+        assert!(!Rc::ptr_eq(&a, &Rc::new(1)));
+
+        // as a map key, looked up by `&str` through `Borrow<str>`.
+        let mut map: HashMap<Rc<str>, i32> = HashMap::new();
+        let key: Rc<str> = Rc::from("key");
+        map.insert(key.clone(), 1);
+        assert_eq!(map.get("key"), Some(&1));
+        assert_eq!(map.get(&key), Some(&1));
+
+        let set: BTreeSet<Rc<[i32]>> = [Rc::from(&[2][..]), Rc::from(&[1, 2][..])]
+            .into_iter()
+            .collect();
+        assert_eq!(set.first().map(|rc| &**rc), Some(&[1, 2][..]));
+    }
+
+    #[test]
+    fn rc_default_and_from() {
+        let _leaks = LeakGuard::new();
+        assert_eq!(*Rc::<i32>::default(), 0);
+        assert!(Rc::<[String]>::default().is_empty());
+        assert_eq!(&*Rc::<str>::default(), "");
+
+        let rc: Rc<String> = String::from("from").into();
+        assert_eq!(*rc, "from");
+
+        let rc: Rc<String> = Rc::from(Bocs::new(String::from("bocs")));
+        assert_eq!(*rc, "bocs");
+        let rc: Rc<()> = Rc::from(Bocs::new(()));
+        assert_eq!(*rc, ());
+
+        let rc: Rc<[i32]> = Rc::from(crate::vec![1, 2]);
+        assert_eq!(*rc, [1, 2]);
+    }
+}
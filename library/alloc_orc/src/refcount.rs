@@ -0,0 +1,74 @@
+//! Helpers shared by `rc` and `sync`.
+//!
+//! `RcInner` and `ArcInner` are both `repr(C)` structs of a strong and a weak
+//! count of type `C`, followed by the value, so their layout only depends on
+//! `C` and on the value.
+
+use crate::alloc::Layout;
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+
+/// Returns the layout of an inner block with counts of type `C`, whose value
+/// has `value_layout`, the same as `Layout::for_value` on it.
+pub(crate) fn inner_layout<C>(value_layout: Layout) -> Layout {
+    let counts = Layout::new::<[C; 2]>();
+    counts.extend(value_layout).unwrap().0.pad_to_align()
+}
+
+/// Returns the offset of the value in an inner block with counts of type `C`,
+/// for a value aligned to `align`.
+pub(crate) fn data_offset<C>(align: usize) -> usize {
+    let counts = Layout::new::<[C; 2]>();
+    let value = Layout::from_size_align(0, align).unwrap();
+    counts.extend(value).unwrap().1
+}
+
+/// Recovers the inner block from a pointer to its value. The metadata of
+/// `ptr` is kept, so the result can be cast to the inner type.
+///
+/// # Safety
+///
+/// `ptr` must point to the value of an inner block with counts of type `C`.
+/// the value may have been dropped, since only the metadata is read to get
+/// the alignment.
+pub(crate) unsafe fn inner_from_value<C, T: ?Sized>(ptr: *const T) -> *const T {
+    let align = mem::align_of_val(&*ptr);
+    ptr.byte_sub(data_offset::<C>(align))
+}
+
+/// `Weak::new` uses `usize::MAX` as a sentinel, which can never be the address
+/// of an inner block since it is aligned to at least `usize`.
+pub(crate) fn is_dangling<T: ?Sized>(ptr: *const T) -> bool {
+    ptr.cast::<()>() as usize == usize::MAX
+}
+
+/// Clones the elements of `src` into `slots`, which has the same length.
+///
+/// If a `clone` panics, the elements written so far are dropped.
+pub(crate) fn write_clones<T: Clone>(slots: &mut [MaybeUninit<T>], src: &[T]) {
+    struct Guard<'a, T> {
+        slots: &'a mut [MaybeUninit<T>],
+        len: usize,
+    }
+
+    impl<T> Drop for Guard<'_, T> {
+        fn drop(&mut self) {
+            // SAFETY:
+            // the first `len` slots are initialized.
+            unsafe {
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                    self.slots.as_mut_ptr().cast::<T>(),
+                    self.len,
+                ))
+            };
+        }
+    }
+
+    assert_eq!(slots.len(), src.len());
+    let mut guard = Guard { slots, len: 0 };
+    for x in src {
+        guard.slots[guard.len].write(x.clone());
+        guard.len += 1;
+    }
+    mem::forget(guard);
+}
@@ -0,0 +1,533 @@
+//! A growable UTF-8 string.
+//!
+//! Resources:
+//! * https://doc.rust-lang.org/std/string/struct.String.html
+
+use crate::borrow::Cow;
+use crate::vec::Vec;
+use core_orc::borrow::{Borrow, BorrowMut};
+use core_orc::into_iterator::IntoIterator;
+use core_orc::iter::traits::{
+    collector::FromIterator, double_ended::DoubleEndedIterator, iterator::Iterator,
+};
+use core_orc::str::{self, CharIndices, Chars, Utf8Error};
+use std::error::Error;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, AddAssign, Deref, DerefMut};
+use std::ptr;
+
+/// A growable string, which is always valid UTF-8.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Default)]
+pub struct String {
+    vec: Vec<u8>,
+}
+
+impl String {
+    pub fn new() -> Self {
+        String { vec: Vec::new() }
+    }
+
+    pub fn with_capacity(cap: usize) -> Self {
+        String {
+            vec: Vec::with_capacity(cap),
+        }
+    }
+
+    /// Converts a vector of bytes to a `String`, if it is valid UTF-8.
+    ///
+    /// The bytes are not copied, and are given back by the error otherwise.
+    pub fn from_utf8(vec: Vec<u8>) -> Result<String, FromUtf8Error> {
+        match str::from_utf8(&vec) {
+            Ok(_) => Ok(String { vec }),
+            Err(error) => Err(FromUtf8Error { bytes: vec, error }),
+        }
+    }
+
+    /// Converts a slice of bytes to a string, replacing each invalid sequence
+    /// with U+FFFD REPLACEMENT CHARACTER.
+    ///
+    /// Valid input is borrowed as is.
+    ///
+    /// ```
+    /// use alloc_orc::string::String;
+    ///
+    /// assert!(String::from_utf8_lossy(b"abc").is_borrowed());
+    /// assert_eq!(String::from_utf8_lossy(b"a\xF0\x9Fb\xE2"), "a\u{FFFD}b\u{FFFD}");
+    /// ```
+    pub fn from_utf8_lossy(mut v: &[u8]) -> Cow<'_, str> {
+        let mut error = match str::from_utf8(v) {
+            Ok(s) => return Cow::Borrowed(s),
+            Err(error) => error,
+        };
+
+        let mut res = String::with_capacity(v.len());
+        loop {
+            let (valid, rest) = v.split_at(error.valid_up_to());
+            // SAFETY:
+            // `valid_up_to` is the length of the valid prefix.
+            res.push_str(unsafe { std::str::from_utf8_unchecked(valid) });
+            res.push(char::REPLACEMENT_CHARACTER);
+            // an incomplete sequence can only be at the end.
+            v = &rest[error.error_len().unwrap_or(rest.len())..];
+            match str::from_utf8(v) {
+                Ok(s) => {
+                    res.push_str(s);
+                    return Cow::Owned(res);
+                }
+                Err(e) => error = e,
+            }
+        }
+    }
+
+    /// Converts a vector of bytes to a `String` without checking that it is
+    /// valid UTF-8.
+    ///
+    /// # Safety
+    ///
+    /// `bytes` must be valid UTF-8.
+    pub unsafe fn from_utf8_unchecked(bytes: Vec<u8>) -> String {
+        String { vec: bytes }
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.vec
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY:
+        // `vec` is always valid UTF-8.
+        unsafe { std::str::from_utf8_unchecked(&self.vec) }
+    }
+
+    pub fn as_mut_str(&mut self) -> &mut str {
+        // SAFETY:
+        // `vec` is always valid UTF-8, and `str` keeps it so.
+        unsafe { std::str::from_utf8_unchecked_mut(&mut self.vec) }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.vec
+    }
+
+    /// Returns the length in bytes, not in chars.
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    pub fn reserve(&mut self, additional: usize) {
+        self.vec.reserve(additional)
+    }
+
+    pub fn clear(&mut self) {
+        self.vec.clear()
+    }
+
+    pub fn push(&mut self, ch: char) {
+        self.push_str(ch.encode_utf8(&mut [0; 4]))
+    }
+
+    pub fn push_str(&mut self, s: &str) {
+        self.insert_bytes(self.len(), s.as_bytes())
+    }
+
+    /// Inserts `ch` at the byte position `idx`.
+    ///
+    /// Panics if `idx` is out of bounds or not on a char boundary.
+    pub fn insert(&mut self, idx: usize, ch: char) {
+        self.insert_str(idx, ch.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Inserts `s` at the byte position `idx`.
+    ///
+    /// Panics if `idx` is out of bounds or not on a char boundary.
+    pub fn insert_str(&mut self, idx: usize, s: &str) {
+        assert!(self.is_char_boundary(idx), "not a char boundary");
+        self.insert_bytes(idx, s.as_bytes())
+    }
+
+    /// Removes the char at the byte position `idx` and returns it.
+    ///
+    /// Panics if `idx` is out of bounds or not on a char boundary.
+    pub fn remove(&mut self, idx: usize) -> char {
+        let ch = match str::chars(&self[idx..]).next() {
+            Some(ch) => ch,
+            None => panic!("cannot remove a char from the end of a string"),
+        };
+        self.vec.drain(idx..idx + ch.len_utf8());
+        ch
+    }
+
+    /// Shortens the string to `new_len` bytes. Does nothing if `new_len` is
+    /// greater than the current length.
+    ///
+    /// Panics if `new_len` is not on a char boundary.
+    pub fn truncate(&mut self, new_len: usize) {
+        if new_len <= self.len() {
+            assert!(self.is_char_boundary(new_len), "not a char boundary");
+            self.vec.truncate(new_len)
+        }
+    }
+
+    /// Removes the last char and returns it.
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = str::chars(self).next_back()?;
+        self.vec.truncate(self.len() - ch.len_utf8());
+        Some(ch)
+    }
+
+    /// Returns an iterator over the chars.
+    pub fn chars(&self) -> Chars<'_> {
+        str::chars(self)
+    }
+
+    /// Returns an iterator over the chars and their byte positions.
+    pub fn char_indices(&self) -> CharIndices<'_> {
+        str::char_indices(self)
+    }
+
+    /// Copies `bytes` to `idx`, which must be a char boundary.
+    fn insert_bytes(&mut self, idx: usize, bytes: &[u8]) {
+        let len = self.len();
+        let amt = bytes.len();
+        self.vec.reserve(amt);
+
+        // SAFETY:
+        // the capacity is enough for `len + amt` bytes, and `idx <= len` as
+        // it is a char boundary. `bytes` can't be in `vec`, which is borrowed
+        // mutably.
+        unsafe {
+            let p = self.vec.as_mut_ptr().add(idx);
+            ptr::copy(p, p.add(amt), len - idx);
+            ptr::copy_nonoverlapping(bytes.as_ptr(), p, amt);
+            self.vec.set_len(len + amt);
+        }
+    }
+}
+
+impl Deref for String {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl DerefMut for String {
+    fn deref_mut(&mut self) -> &mut str {
+        self.as_mut_str()
+    }
+}
+
+impl AsRef<str> for String {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl AsRef<[u8]> for String {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl Borrow<str> for String {
+    fn borrow(&self) -> &str {
+        self
+    }
+}
+
+impl BorrowMut<str> for String {
+    fn borrow_mut(&mut self) -> &mut str {
+        self
+    }
+}
+
+impl fmt::Debug for String {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for String {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self.as_str(), f)
+    }
+}
+
+/// Lets `write!` append to the string.
+impl fmt::Write for String {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.push_str(s);
+        Ok(())
+    }
+}
+
+/// Hashes the same as `str`, so that `Borrow<str>`-based lookups work.
+impl Hash for String {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(self.as_str(), state)
+    }
+}
+
+macro_rules! impl_str_eq {
+    ($lhs:ty, $rhs:ty) => {
+        impl<'a> PartialEq<$rhs> for $lhs {
+            fn eq(&self, other: &$rhs) -> bool {
+                self[..] == other[..]
+            }
+        }
+
+        impl<'a> PartialEq<$lhs> for $rhs {
+            fn eq(&self, other: &$lhs) -> bool {
+                self[..] == other[..]
+            }
+        }
+    };
+}
+
+impl_str_eq! { String, str }
+impl_str_eq! { String, &'a str }
+impl_str_eq! { String, std::string::String }
+
+impl From<&str> for String {
+    fn from(s: &str) -> Self {
+        let mut res = String::with_capacity(s.len());
+        res.push_str(s);
+        res
+    }
+}
+
+impl From<char> for String {
+    fn from(ch: char) -> Self {
+        let mut res = String::new();
+        res.push(ch);
+        res
+    }
+}
+
+/// Takes over the buffer of `std::string::String` without copying the bytes.
+impl From<std::string::String> for String {
+    fn from(s: std::string::String) -> Self {
+        String {
+            vec: Vec::from(s.into_bytes()),
+        }
+    }
+}
+
+impl Add<&str> for String {
+    type Output = String;
+
+    fn add(mut self, rhs: &str) -> String {
+        self.push_str(rhs);
+        self
+    }
+}
+
+impl AddAssign<&str> for String {
+    fn add_assign(&mut self, rhs: &str) {
+        self.push_str(rhs);
+    }
+}
+
+impl FromIterator<char> for String {
+    fn from_iter<T: IntoIterator<Item = char>>(iter: T) -> Self {
+        let mut iter = iter.into_iter();
+
+        let (size, _) = iter.size_hint();
+        let mut res = String::with_capacity(size);
+        while let Some(ch) = iter.next() {
+            res.push(ch)
+        }
+        res
+    }
+}
+
+/// The error of `String::from_utf8`, which gives the bytes back.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FromUtf8Error {
+    bytes: Vec<u8>,
+    error: Utf8Error,
+}
+
+impl FromUtf8Error {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.error
+    }
+}
+
+impl fmt::Display for FromUtf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl Error for FromUtf8Error {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alloc::LeakGuard;
+    use std::collections::hash_map::DefaultHasher;
+    use std::fmt::Write;
+
+    #[test]
+    fn push_and_pop_chars_of_any_width() {
+        let _leaks = LeakGuard::new();
+        let mut s = String::new();
+        for ch in ['a', '\u{E9}', '\u{20AC}', '\u{1F600}'] {
+            s.push(ch);
+        }
+        s.push_str("!");
+        assert_eq!(s, "a\u{E9}\u{20AC}\u{1F600}!");
+        assert_eq!(s.len(), 11);
+
+        assert_eq!(s.pop(), Some('!'));
+        assert_eq!(s.pop(), Some('\u{1F600}'));
+        assert_eq!(s.pop(), Some('\u{20AC}'));
+        assert_eq!(s, "a\u{E9}");
+        s.clear();
+        assert_eq!(s.pop(), None);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let _leaks = LeakGuard::new();
+        let mut s = String::from("ac");
+        s.insert(1, '\u{20AC}');
+        s.insert(0, 'x');
+        s.insert_str(s.len(), "yz");
+        assert_eq!(s, "xa\u{20AC}cyz");
+
+        assert_eq!(s.remove(2), '\u{20AC}');
+        assert_eq!(s.remove(0), 'x');
+        assert_eq!(s, "acyz");
+    }
+
+    #[test]
+    #[should_panic = "not a char boundary"]
+    fn insert_inside_char_panics() {
+        // no `LeakGuard`: the panic skips its check.
+        let mut s = String::from("\u{E9}");
+        s.insert(1, 'a');
+    }
+
+    #[test]
+    #[should_panic = "not a char boundary"]
+    fn truncate_inside_char_panics() {
+        // no `LeakGuard`: the panic skips its check.
+        let mut s = String::from("\u{E9}");
+        s.truncate(1);
+    }
+
+    #[test]
+    fn truncate() {
+        let _leaks = LeakGuard::new();
+        let mut s = String::from("a\u{E9}b");
+        s.truncate(10);
+        assert_eq!(s, "a\u{E9}b");
+        s.truncate(3);
+        assert_eq!(s, "a\u{E9}");
+        s.truncate(0);
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn from_utf8_keeps_the_bytes() {
+        let _leaks = LeakGuard::new();
+        let mut bytes = crate::vec![b'o', b'k', 0xE2, 0x82];
+        // the last byte is pushed below, without reallocating.
+        bytes.reserve(1);
+        let ptr = bytes.as_ptr();
+        let err = String::from_utf8(bytes).unwrap_err();
+        assert_eq!(err.utf8_error().valid_up_to(), 2);
+        assert_eq!(err.utf8_error().error_len(), None);
+        assert_eq!(
+            err.to_string(),
+            "incomplete utf-8 byte sequence from index 2"
+        );
+
+        let mut bytes = err.into_bytes();
+        assert_eq!(bytes.as_ptr(), ptr);
+        bytes.push(0xAC);
+        let s = String::from_utf8(bytes).unwrap();
+        assert_eq!(s, "ok\u{20AC}");
+        assert_eq!(s.as_ptr(), ptr);
+    }
+
+    #[test]
+    fn from_utf8_lossy_replaces_each_invalid_sequence() {
+        let _leaks = LeakGuard::new();
+        let s = String::from_utf8_lossy(b"\xFFa\xED\xA0\x80b\xF0\x9F\x98");
+        assert!(s.is_owned());
+        // std replaces the same sequences.
+        assert_eq!(
+            s,
+            std::string::String::from_utf8_lossy(b"\xFFa\xED\xA0\x80b\xF0\x9F\x98").as_ref()
+        );
+        assert_eq!(s, "\u{FFFD}a\u{FFFD}\u{FFFD}\u{FFFD}b\u{FFFD}");
+    }
+
+    #[test]
+    fn chars_and_char_indices() {
+        let _leaks = LeakGuard::new();
+        let s = String::from("a\u{E9}\u{1F600}");
+        assert_eq!(s.chars().rev().collect::<String>(), "\u{1F600}\u{E9}a");
+        let mut iter = s.char_indices();
+        assert_eq!(iter.next(), Some((0, 'a')));
+        assert_eq!(iter.next_back(), Some((3, '\u{1F600}')));
+        assert_eq!(iter.next(), Some((1, '\u{E9}')));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn add_and_write() {
+        let _leaks = LeakGuard::new();
+        let mut s = String::from("a") + "b";
+        s += "c";
+        write!(s, "{}-{:?}", 1, 'x').unwrap();
+        assert_eq!(s, "abc1-'x'");
+        assert_eq!(format!("{} {:?}", s, s), "abc1-'x' \"abc1-'x'\"");
+    }
+
+    #[test]
+    fn deref_to_str() {
+        let _leaks = LeakGuard::new();
+        let mut s = String::from("Hello");
+        assert!(s.starts_with("He"));
+        assert_eq!(&s[1..3], "el");
+        s.make_ascii_uppercase();
+        assert_eq!(s, "HELLO");
+        assert_eq!(std::string::String::from("HELLO"), s);
+    }
+
+    #[test]
+    fn hashes_like_str() {
+        let _leaks = LeakGuard::new();
+        let hash = |x: &dyn Fn(&mut DefaultHasher)| {
+            let mut state = DefaultHasher::new();
+            x(&mut state);
+            state.finish()
+        };
+        assert_eq!(
+            hash(&|state| String::from("a").hash(state)),
+            hash(&|state| "a".hash(state))
+        );
+    }
+}
@@ -0,0 +1,1312 @@
+//! Thread-safe reference-counting pointers.
+//!
+//! `Arc` is the atomic version of `rc::Rc`, and has the same API.
+//! * https://doc.rust-lang.org/nomicon/arc-mutex/arc.html
+//! * https://marabos.nl/atomics/building-arc.html
+
+use crate::alloc::{handle_alloc_error, Allocator, Global, Layout};
+use crate::bocs::Bocs;
+use crate::refcount::{self, is_dangling};
+use crate::vec::Vec;
+use core_orc::into_iterator::IntoIterator;
+use core_orc::iter::traits::{collector::FromIterator, iterator::Iterator};
+use std::{
+    any::Any,
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt,
+    hash::{Hash, Hasher},
+    hint,
+    mem::{self, ManuallyDrop, MaybeUninit},
+    ops::Deref,
+    process,
+    ptr::{self, NonNull},
+    sync::atomic::{self, AtomicUsize},
+};
+
+/// The counts abort the process beyond this, long before they overflow.
+/// `isize::MAX` clones can't exist, since each `Arc` takes at least a byte,
+/// unless they are leaked with `mem::forget`.
+const MAX_REFCOUNT: usize = isize::MAX as usize;
+
+/// The weak count is set to this while `is_unique` checks the strong count.
+const WEAK_LOCKED: usize = usize::MAX;
+
+// `repr(C)` keeps the counts in front, so the offset of `value` only depends
+// on its alignment, see `refcount::data_offset`.
+#[repr(C)]
+struct ArcInner<T: ?Sized> {
+    strong: AtomicUsize,
+    // every strong reference together holds one implicit weak reference.
+    weak: AtomicUsize,
+    value: T,
+}
+
+// `ArcInner` is shared between the threads through `Arc` and `Weak`.
+unsafe impl<T: ?Sized + Sync + Send> Send for ArcInner<T> {}
+unsafe impl<T: ?Sized + Sync + Send> Sync for ArcInner<T> {}
+
+/// A thread-safe reference-counting pointer.
+///
+/// It is `Send` and `Sync` only if `T` is both, since any thread may access
+/// (and drop) the value, so e.g. `Arc<RefCell<T>>` can't be shared.
+///
+/// ```compile_fail,E0277
+/// use alloc_orc::sync::Arc;
+/// use core_orc::cell::RefCell;
+///
+/// let arc = Arc::new(RefCell::new(1));
+/// std::thread::spawn(move || drop(arc));
+/// ```
+pub struct Arc<T: ?Sized, A: Allocator = Global> {
+    inner: NonNull<ArcInner<T>>,
+    alloc: A,
+}
+
+// the value is shared (`Sync`), and it may be dropped by any thread (`Send`).
+unsafe impl<T: ?Sized + Sync + Send, A: Allocator + Send> Send for Arc<T, A> {}
+unsafe impl<T: ?Sized + Sync + Send, A: Allocator + Sync> Sync for Arc<T, A> {}
+
+/// Converts `Arc<T, A>` into `Arc<U, A>`, where `T` can be unsized to `U`,
+/// e.g. `Arc<[T; N]>` to `Arc<[T]>`, or `Arc<T>` to `Arc<dyn Trait>`.
+///
+/// Like `unsize_bocs!`, only unsizing coercions compile.
+///
+/// ```
+/// use alloc_orc::sync::Arc;
+/// use alloc_orc::unsize_arc;
+/// use std::fmt::Display;
+///
+/// let arc: Arc<dyn Display + Send + Sync> = unsize_arc!(Arc::new(42) => dyn Display + Send + Sync);
+/// assert_eq!(arc.to_string(), "42");
+/// ```
+///
+/// Other conversions are rejected:
+///
+/// ```compile_fail
+/// use alloc_orc::sync::Arc;
+/// use alloc_orc::unsize_arc;
+///
+/// let arc: Arc<[u8]> = unsize_arc!(Arc::new([0u16; 4]) => [u8]);
+/// ```
+#[macro_export]
+macro_rules! unsize_arc {
+    ($arc:expr => $u:ty) => {{
+        let (raw, alloc) = $crate::sync::Arc::into_raw_with_allocator($arc);
+        // only an unsizing coercion (or no-op) is allowed here.
+        let raw: *const $u = raw;
+        // SAFETY:
+        // `raw` points to the same value, and its metadata was created by the
+        // compiler, so the alignment (and the offset of the value) is the same.
+        unsafe { $crate::sync::Arc::from_raw_in(raw, alloc) }
+    }};
+}
+
+impl<T> Arc<T> {
+    pub fn new(v: T) -> Self {
+        Arc::new_in(v, Global)
+    }
+
+    /// Constructs an `Arc` whose value can hold a `Weak` to itself.
+    ///
+    /// The `Weak` passed to `data_fn` doesn't upgrade until this returns.
+    pub fn new_cyclic<F: FnOnce(&Weak<T>) -> T>(data_fn: F) -> Self {
+        Arc::new_cyclic_in(data_fn, Global)
+    }
+
+    /// Constructs an `Arc` with uninitialized contents.
+    pub fn new_uninit() -> Arc<MaybeUninit<T>> {
+        Arc::new_uninit_in(Global)
+    }
+
+    /// Constructs an `Arc` with zero-filled contents.
+    pub fn new_zeroed() -> Arc<MaybeUninit<T>> {
+        Arc::new_zeroed_in(Global)
+    }
+}
+
+impl<T: ?Sized> Arc<T> {
+    /// Constructs an `Arc` from a raw pointer returned by `Arc::into_raw`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Arc::<T>::into_raw`, and each call
+    /// takes over one strong reference.
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        Arc::from_raw_in(ptr, Global)
+    }
+
+    /// Increments the strong count of the `Arc` behind `ptr`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Arc::<T>::into_raw`, and its strong
+    /// count must be at least 1.
+    pub unsafe fn increment_strong_count(ptr: *const T) {
+        Arc::increment_strong_count_in(ptr, Global)
+    }
+
+    /// Decrements the strong count of the `Arc` behind `ptr`, dropping it if it
+    /// was the last one.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Arc::<T>::into_raw`, and its strong
+    /// count must be at least 1.
+    pub unsafe fn decrement_strong_count(ptr: *const T) {
+        Arc::decrement_strong_count_in(ptr, Global)
+    }
+}
+
+impl<T, A: Allocator> Arc<T, A> {
+    /// Allocates `ArcInner` in the given allocator.
+    pub fn new_in(v: T, alloc: A) -> Self {
+        let inner = Arc::<T, A>::allocate_sized(&alloc, false);
+        // SAFETY:
+        // `inner` is allocated for an `ArcInner<T>`.
+        unsafe { ptr::addr_of_mut!((*inner.as_ptr()).value).write(v) };
+        Arc { inner, alloc }
+    }
+
+    /// Constructs an `Arc` whose value can hold a `Weak` to itself.
+    ///
+    /// The `Weak` passed to `data_fn` doesn't upgrade until this returns.
+    pub fn new_cyclic_in<F>(data_fn: F, alloc: A) -> Self
+    where
+        F: FnOnce(&Weak<T, A>) -> T,
+        A: Clone,
+    {
+        let inner = Arc::<T, A>::allocate_sized(&alloc, false);
+        // SAFETY:
+        // `inner` is allocated, and nobody else has seen it yet.
+        unsafe { inner.as_ref().strong.store(0, atomic::Ordering::Relaxed) };
+
+        // this owns the implicit weak reference. if `data_fn` panics, it
+        // deallocates `ArcInner` on drop.
+        let weak = Weak {
+            inner,
+            alloc: alloc.clone(),
+        };
+        let value = data_fn(&weak);
+
+        // SAFETY:
+        // the strong count is still zero, so nobody can access the value.
+        unsafe {
+            ptr::addr_of_mut!((*inner.as_ptr()).value).write(value);
+            // `Release` publishes the value to the threads which upgrade a clone
+            // of `weak` from now on.
+            inner.as_ref().strong.store(1, atomic::Ordering::Release);
+        }
+        // the implicit weak reference now belongs to the strong one.
+        mem::forget(weak);
+
+        Arc { inner, alloc }
+    }
+
+    /// Constructs an `Arc` with uninitialized contents in the given allocator.
+    pub fn new_uninit_in(alloc: A) -> Arc<MaybeUninit<T>, A> {
+        Arc {
+            inner: Arc::allocate_sized(&alloc, false),
+            alloc,
+        }
+    }
+
+    /// Constructs an `Arc` with zero-filled contents in the given allocator.
+    pub fn new_zeroed_in(alloc: A) -> Arc<MaybeUninit<T>, A> {
+        Arc {
+            inner: Arc::allocate_sized(&alloc, true),
+            alloc,
+        }
+    }
+
+    /// Returns a mutable reference to the value, cloning it into a new allocation
+    /// first if it is shared.
+    ///
+    /// If only `Weak`s share the allocation, the value is moved out instead,
+    /// and the `Weak`s no longer upgrade.
+    pub fn make_mut(this: &mut Self) -> &mut T
+    where
+        T: Clone,
+        A: Clone,
+    {
+        // SAFETY:
+        // `inner` is alive as long as `this` is.
+        let inner = unsafe { this.inner.as_ref() };
+
+        // taking the strong count to zero keeps `Weak`s from upgrading while
+        // the weak count is checked. `Acquire` pairs with the `Release` of the
+        // other `Arc`s which were dropped, so their writes to the value are seen.
+        if inner
+            .strong
+            .compare_exchange(1, 0, atomic::Ordering::Acquire, atomic::Ordering::Relaxed)
+            .is_err()
+        {
+            // another `Arc` exists, so the value is cloned.
+            *this = Arc::new_in((**this).clone(), this.alloc.clone());
+        } else if inner.weak.load(atomic::Ordering::Relaxed) != 1 {
+            // only `Weak`s are left, so the value is moved out. the strong count
+            // stays zero for them, and they release the old allocation.
+            // SAFETY:
+            // nobody else can access the value anymore.
+            let value = unsafe { ptr::read(&inner.value) };
+            let old = ManuallyDrop::new(mem::replace(this, Arc::new_in(value, this.alloc.clone())));
+            // SAFETY:
+            // `old` is never used (nor dropped) again, and it holds the implicit
+            // weak reference.
+            unsafe {
+                let alloc = ptr::read(&old.alloc);
+                release_weak(old.inner, &alloc);
+            }
+        } else {
+            // `this` was unique after all, so the strong count is restored.
+            inner.strong.store(1, atomic::Ordering::Release);
+        }
+
+        // SAFETY:
+        // `this` is unique now.
+        unsafe { &mut (*this.inner.as_ptr()).value }
+    }
+
+    /// Returns the value if `this` is the only strong reference, or `this` back.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        // SAFETY:
+        // `inner` is alive as long as `this` is.
+        let inner = unsafe { this.inner.as_ref() };
+        if inner
+            .strong
+            .compare_exchange(1, 0, atomic::Ordering::Relaxed, atomic::Ordering::Relaxed)
+            .is_err()
+        {
+            return Err(this);
+        }
+        // pairs with the `Release` of the other `Arc`s which were dropped.
+        atomic::fence(atomic::Ordering::Acquire);
+
+        // SAFETY:
+        // `this` was the last strong reference, and it gives up the value.
+        unsafe {
+            let value = ptr::read(&inner.value);
+            Arc::release_implicit_weak(this);
+            Ok(value)
+        }
+    }
+
+    /// Returns the value if `this` is the only strong reference, and drops it
+    /// otherwise.
+    ///
+    /// Unlike `Arc::try_unwrap(this).ok()`, exactly one of the `Arc`s dropped
+    /// concurrently gets the value.
+    pub fn into_inner(this: Self) -> Option<T> {
+        // SAFETY:
+        // `inner` is alive as long as `this` is.
+        let inner = unsafe { this.inner.as_ref() };
+        if inner.strong.fetch_sub(1, atomic::Ordering::Release) != 1 {
+            mem::forget(this);
+            return None;
+        }
+        atomic::fence(atomic::Ordering::Acquire);
+
+        // SAFETY:
+        // `this` was the last strong reference, and it gives up the value.
+        unsafe {
+            let value = ptr::read(&inner.value);
+            Arc::release_implicit_weak(this);
+            Some(value)
+        }
+    }
+
+    /// Returns the value if `this` is the only strong reference, or a clone of it.
+    pub fn unwrap_or_clone(this: Self) -> T
+    where
+        T: Clone,
+    {
+        Arc::try_unwrap(this).unwrap_or_else(|arc| (*arc).clone())
+    }
+
+    fn allocate_sized(alloc: &A, zeroed: bool) -> NonNull<ArcInner<T>> {
+        Arc::allocate_for_layout(alloc, Layout::new::<T>(), zeroed, |mem| mem.cast())
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Arc<T, A> {
+    /// Allocates an `ArcInner` with one strong reference, leaving the value
+    /// uninitialized (or zero-filled).
+    ///
+    /// `mem_to_inner` attaches the metadata of the value (e.g. the length of
+    /// a slice) to the allocated memory.
+    fn allocate_for_layout(
+        alloc: &A,
+        value_layout: Layout,
+        zeroed: bool,
+        mem_to_inner: impl FnOnce(*mut u8) -> *mut ArcInner<T>,
+    ) -> NonNull<ArcInner<T>> {
+        let layout = refcount::inner_layout::<AtomicUsize>(value_layout);
+        let result = if zeroed {
+            alloc.allocate_zeroed(layout)
+        } else {
+            alloc.allocate(layout)
+        };
+        let mem = match result {
+            Ok(ptr) => ptr.cast::<u8>().as_ptr(),
+            Err(_) => handle_alloc_error(layout),
+        };
+
+        // SAFETY:
+        // `mem` is allocated for an `ArcInner<T>` whose value fits `value_layout`.
+        unsafe {
+            let inner = mem_to_inner(mem);
+            ptr::addr_of_mut!((*inner).strong).write(AtomicUsize::new(1));
+            ptr::addr_of_mut!((*inner).weak).write(AtomicUsize::new(1));
+            NonNull::new_unchecked(inner)
+        }
+    }
+
+    fn inner(&self) -> &ArcInner<T> {
+        // SAFETY:
+        // `inner` is alive as long as this strong reference is.
+        unsafe { self.inner.as_ref() }
+    }
+
+    /// Returns a reference to the underlying allocator.
+    pub fn allocator(this: &Self) -> &A {
+        &this.alloc
+    }
+
+    /// Returns a mutable reference to the value if there is no other `Arc` or
+    /// `Weak` to the same allocation.
+    pub fn get_mut(this: &mut Self) -> Option<&mut T> {
+        if Arc::is_unique(this) {
+            // SAFETY:
+            // nobody else can access the value.
+            Some(unsafe { &mut (*this.inner.as_ptr()).value })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the two `Arc`s point to the same allocation.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        ptr::addr_eq(this.inner.as_ptr(), other.inner.as_ptr())
+    }
+
+    fn is_unique(this: &Self) -> bool {
+        let inner = this.inner();
+        // locking the weak count keeps `Arc::downgrade` from creating a `Weak`
+        // (which could be upgraded) while the strong count is checked.
+        if inner
+            .weak
+            .compare_exchange(
+                1,
+                WEAK_LOCKED,
+                atomic::Ordering::Acquire,
+                atomic::Ordering::Relaxed,
+            )
+            .is_err()
+        {
+            return false;
+        }
+
+        // `Acquire` pairs with the `Release` of the other `Arc`s which were
+        // dropped, so their writes to the value are seen.
+        let unique = inner.strong.load(atomic::Ordering::Acquire) == 1;
+        inner.weak.store(1, atomic::Ordering::Release);
+        unique
+    }
+
+    /// Releases the implicit weak reference, after the strong count reached
+    /// zero and the value was dropped or moved out.
+    ///
+    /// # Safety
+    ///
+    /// The strong count of `this` must be zero.
+    unsafe fn release_implicit_weak(this: Self) {
+        let this = ManuallyDrop::new(this);
+        let alloc = ptr::read(&this.alloc);
+        release_weak(this.inner, &alloc);
+    }
+
+    /// Consumes the `Arc`, returning a pointer to the value. The strong reference
+    /// is kept, until it is converted back with `Arc::from_raw`.
+    pub fn into_raw(this: Self) -> *const T {
+        Arc::into_raw_with_allocator(this).0
+    }
+
+    /// Consumes the `Arc`, returning a pointer to the value and the allocator.
+    pub fn into_raw_with_allocator(this: Self) -> (*const T, A) {
+        let this = ManuallyDrop::new(this);
+        // SAFETY:
+        // `this` is never used (nor dropped) again, so the allocator is moved out.
+        let alloc = unsafe { ptr::read(&this.alloc) };
+        (Arc::as_ptr(&this), alloc)
+    }
+
+    /// Constructs an `Arc` from a raw pointer and its allocator.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Arc::<T, A>::into_raw`, allocated by
+    /// `alloc`, and each call takes over one strong reference.
+    pub unsafe fn from_raw_in(ptr: *const T, alloc: A) -> Self {
+        Arc {
+            inner: NonNull::new_unchecked(
+                refcount::inner_from_value::<AtomicUsize, T>(ptr) as *mut ArcInner<T>
+            ),
+            alloc,
+        }
+    }
+
+    /// Returns a pointer to the value, without consuming the `Arc`.
+    pub fn as_ptr(this: &Self) -> *const T {
+        // SAFETY:
+        // `inner` is alive, and no reference to `value` is created.
+        unsafe { ptr::addr_of!((*this.inner.as_ptr()).value) }
+    }
+
+    /// # Safety
+    ///
+    /// Same as `Arc::increment_strong_count`, and `ptr` must be allocated by `alloc`.
+    pub unsafe fn increment_strong_count_in(ptr: *const T, alloc: A)
+    where
+        A: Clone,
+    {
+        let arc = ManuallyDrop::new(Arc::from_raw_in(ptr, alloc));
+        let _arc: ManuallyDrop<_> = arc.clone();
+    }
+
+    /// # Safety
+    ///
+    /// Same as `Arc::decrement_strong_count`, and `ptr` must be allocated by `alloc`.
+    pub unsafe fn decrement_strong_count_in(ptr: *const T, alloc: A) {
+        drop(Arc::from_raw_in(ptr, alloc));
+    }
+
+    /// Returns the number of `Arc`s. Other threads may change it at any time.
+    pub fn strong_count(this: &Self) -> usize {
+        this.inner().strong.load(atomic::Ordering::Relaxed)
+    }
+
+    /// Returns the number of `Weak`s, not counting the implicit weak
+    /// reference held by the strong ones. Other threads may change it at any time.
+    pub fn weak_count(this: &Self) -> usize {
+        match this.inner().weak.load(atomic::Ordering::Relaxed) {
+            // `is_unique` is checking, so there is no `Weak`.
+            WEAK_LOCKED => 0,
+            count => count - 1,
+        }
+    }
+
+    pub fn downgrade(this: &Self) -> Weak<T, A>
+    where
+        A: Clone,
+    {
+        let weak = &this.inner().weak;
+        let mut count = weak.load(atomic::Ordering::Relaxed);
+        loop {
+            if count == WEAK_LOCKED {
+                // `is_unique` will unlock it soon.
+                hint::spin_loop();
+                count = weak.load(atomic::Ordering::Relaxed);
+                continue;
+            }
+            if count > MAX_REFCOUNT {
+                process::abort();
+            }
+
+            // `Acquire` pairs with the `Release` in `is_unique`.
+            match weak.compare_exchange_weak(
+                count,
+                count + 1,
+                atomic::Ordering::Acquire,
+                atomic::Ordering::Relaxed,
+            ) {
+                Ok(_) => {
+                    return Weak {
+                        inner: this.inner,
+                        alloc: this.alloc.clone(),
+                    }
+                }
+                Err(current) => count = current,
+            }
+        }
+    }
+}
+
+impl<T, A: Allocator> Arc<MaybeUninit<T>, A> {
+    /// Converts to `Arc<T, A>`.
+    ///
+    /// # Safety
+    ///
+    /// The value must be initialized.
+    pub unsafe fn assume_init(self) -> Arc<T, A> {
+        let (raw, alloc) = Arc::into_raw_with_allocator(self);
+        Arc::from_raw_in(raw.cast::<T>(), alloc)
+    }
+}
+
+impl<T> Arc<[T]> {
+    /// Constructs an `Arc` slice with uninitialized contents.
+    pub fn new_uninit_slice(len: usize) -> Arc<[MaybeUninit<T>]> {
+        Arc::new_uninit_slice_in(len, Global)
+    }
+}
+
+impl<T, A: Allocator> Arc<[T], A> {
+    /// Constructs an `Arc` slice with uninitialized contents in the given allocator.
+    pub fn new_uninit_slice_in(len: usize, alloc: A) -> Arc<[MaybeUninit<T>], A> {
+        Arc {
+            inner: Arc::<[MaybeUninit<T>], A>::allocate_for_slice(len, &alloc),
+            alloc,
+        }
+    }
+
+    /// Allocates the counts and `len` elements in a single block.
+    fn allocate_for_slice(len: usize, alloc: &A) -> NonNull<ArcInner<[T]>> {
+        let value_layout = Layout::array::<T>(len).unwrap();
+        Arc::allocate_for_layout(alloc, value_layout, false, |mem| {
+            ptr::slice_from_raw_parts_mut(mem.cast::<T>(), len) as *mut ArcInner<[T]>
+        })
+    }
+}
+
+impl<T, A: Allocator> Arc<[MaybeUninit<T>], A> {
+    /// Converts to `Arc<[T], A>`.
+    ///
+    /// # Safety
+    ///
+    /// All the elements must be initialized.
+    pub unsafe fn assume_init(self) -> Arc<[T], A> {
+        let (raw, alloc) = Arc::into_raw_with_allocator(self);
+        Arc::from_raw_in(raw as *const [T], alloc)
+    }
+}
+
+impl<A: Allocator> Arc<dyn Any + Send + Sync, A> {
+    /// Downcasts to a concrete type, or returns `self` back if the value is
+    /// not a `T`.
+    pub fn downcast<T: Any + Send + Sync>(self) -> Result<Arc<T, A>, Self> {
+        if (*self).is::<T>() {
+            let (raw, alloc) = Arc::into_raw_with_allocator(self);
+            // SAFETY:
+            // the value is a `T`.
+            unsafe { Ok(Arc::from_raw_in(raw as *const T, alloc)) }
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T: Clone> From<&[T]> for Arc<[T]> {
+    fn from(v: &[T]) -> Self {
+        let mut arc = Arc::new_uninit_slice(v.len());
+        // if `clone` panics, the elements cloned so far are dropped, and then
+        // the allocation is freed by `arc`.
+        refcount::write_clones(Arc::get_mut(&mut arc).unwrap(), v);
+        // SAFETY:
+        // all the elements are written above.
+        unsafe { arc.assume_init() }
+    }
+}
+
+impl<T, A: Allocator + Clone> From<Vec<T, A>> for Arc<[T], A> {
+    /// Moves the elements into a new allocation, and frees the buffer of `v`.
+    fn from(mut v: Vec<T, A>) -> Self {
+        let len = v.len();
+        let alloc = v.allocator().clone();
+        let inner = Arc::<[T], A>::allocate_for_slice(len, &alloc);
+        // SAFETY:
+        // `inner` has room for `len` elements, and they are moved out of `v`.
+        unsafe {
+            let dst = ptr::addr_of_mut!((*inner.as_ptr()).value).cast::<T>();
+            ptr::copy_nonoverlapping(v.as_ptr(), dst, len);
+            v.set_len(0);
+        }
+        Arc { inner, alloc }
+    }
+}
+
+impl<T> FromIterator<T> for Arc<[T]> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        // the length isn't known upfront, so the elements are collected first.
+        Arc::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl From<&str> for Arc<str> {
+    fn from(s: &str) -> Self {
+        let bytes: Arc<[u8]> = Arc::from(s.as_bytes());
+        let (raw, alloc) = Arc::into_raw_with_allocator(bytes);
+        // SAFETY:
+        // the bytes are copied from a `str`.
+        unsafe { Arc::from_raw_in(raw as *const str, alloc) }
+    }
+}
+
+impl<A: Allocator> From<Arc<str, A>> for Arc<[u8], A> {
+    fn from(arc: Arc<str, A>) -> Self {
+        let (raw, alloc) = Arc::into_raw_with_allocator(arc);
+        // SAFETY:
+        // `str` has the same layout as `[u8]`.
+        unsafe { Arc::from_raw_in(raw as *const [u8], alloc) }
+    }
+}
+
+impl<T> From<T> for Arc<T> {
+    fn from(x: T) -> Self {
+        Arc::new(x)
+    }
+}
+
+impl<T, A: Allocator> From<Bocs<T, A>> for Arc<T, A> {
+    /// Moves the value into a new allocation, and frees the one of the `Bocs`.
+    fn from(b: Bocs<T, A>) -> Self {
+        let (raw, alloc) = Bocs::into_raw_with_allocator(b);
+        // SAFETY:
+        // `raw` points to a valid `T` allocated by `alloc`, which is moved out
+        // here, and the memory is deallocated without dropping it.
+        let value = unsafe {
+            let value = ptr::read(raw);
+            if mem::size_of::<T>() != 0 {
+                alloc.deallocate(NonNull::new_unchecked(raw).cast(), Layout::new::<T>());
+            }
+            value
+        };
+        Arc::new_in(value, alloc)
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for Arc<T, A> {
+    fn clone(&self) -> Self {
+        // `Relaxed` is enough, since a new reference can only be made from an
+        // existing one, which already keeps the value alive.
+        let old = self.inner().strong.fetch_add(1, atomic::Ordering::Relaxed);
+        if old > MAX_REFCOUNT {
+            process::abort();
+        }
+        Arc {
+            inner: self.inner,
+            alloc: self.alloc.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for Arc<T, A> {
+    /// The last strong reference drops `T`, and releases the implicit weak
+    /// reference shared by all the strong ones. `ArcInner` is deallocated when
+    /// the last weak reference goes away.
+    fn drop(&mut self) {
+        // `Release` makes the uses of the value by this thread happen before
+        // the value is dropped by whichever thread drops the last `Arc`.
+        if self.inner().strong.fetch_sub(1, atomic::Ordering::Release) != 1 {
+            return;
+        }
+        // pairs with the `Release` above on all the other threads.
+        atomic::fence(atomic::Ordering::Acquire);
+
+        // SAFETY:
+        // this was the last strong reference, so nobody can access `value` anymore.
+        // `Weak`s never touch it once the strong count is zero.
+        unsafe {
+            ptr::drop_in_place(ptr::addr_of_mut!((*self.inner.as_ptr()).value));
+            release_weak(self.inner, &self.alloc);
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Deref for Arc<T, A> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner().value
+    }
+}
+
+impl<T: ?Sized, A: Allocator> AsRef<T> for Arc<T, A> {
+    fn as_ref(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Borrow<T> for Arc<T, A> {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized + fmt::Debug, A: Allocator> fmt::Debug for Arc<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display, A: Allocator> fmt::Display for Arc<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<T: ?Sized, A: Allocator> fmt::Pointer for Arc<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Pointer::fmt(&Arc::as_ptr(self), f)
+    }
+}
+
+impl<T: ?Sized + PartialEq, A: Allocator> PartialEq for Arc<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        PartialEq::eq(&**self, &**other)
+    }
+}
+
+impl<T: ?Sized + Eq, A: Allocator> Eq for Arc<T, A> {}
+
+impl<T: ?Sized + PartialOrd, A: Allocator> PartialOrd for Arc<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PartialOrd::partial_cmp(&**self, &**other)
+    }
+}
+
+impl<T: ?Sized + Ord, A: Allocator> Ord for Arc<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(&**self, &**other)
+    }
+}
+
+impl<T: ?Sized + Hash, A: Allocator> Hash for Arc<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl<T: Default> Default for Arc<T> {
+    fn default() -> Self {
+        Arc::new(T::default())
+    }
+}
+
+impl<T> Default for Arc<[T]> {
+    fn default() -> Self {
+        Arc::from(Vec::new())
+    }
+}
+
+impl Default for Arc<str> {
+    fn default() -> Self {
+        Arc::from("")
+    }
+}
+
+/// Decrements the weak count, and deallocates `ArcInner` if it was the last one.
+///
+/// # Safety
+///
+/// `inner` must be alive, and the caller must own one of its weak references.
+/// `value` must already have been dropped if the weak count reaches zero.
+unsafe fn release_weak<T: ?Sized, A: Allocator>(inner: NonNull<ArcInner<T>>, alloc: &A) {
+    // a `Weak` may be dropped by the value's own `drop`, so no reference to
+    // the whole `ArcInner` (which covers the value) is created until it's gone.
+    let weak = &*ptr::addr_of!((*inner.as_ptr()).weak);
+    if weak.fetch_sub(1, atomic::Ordering::Release) == 1 {
+        // pairs with the `Release` above on the other threads, so nobody
+        // touches `ArcInner` after it is deallocated.
+        atomic::fence(atomic::Ordering::Acquire);
+        alloc.deallocate(inner.cast(), Layout::for_value(inner.as_ref()));
+    }
+}
+
+pub struct Weak<T: ?Sized, A: Allocator = Global> {
+    // this is the `usize::MAX` sentinel when created by `Weak::new()`, and it
+    // is never dereferenced then.
+    inner: NonNull<ArcInner<T>>,
+    alloc: A,
+}
+
+unsafe impl<T: ?Sized + Sync + Send, A: Allocator + Send> Send for Weak<T, A> {}
+unsafe impl<T: ?Sized + Sync + Send, A: Allocator + Sync> Sync for Weak<T, A> {}
+
+impl<T> Weak<T> {
+    /// Creates a `Weak` which never upgrades, without allocating.
+    pub fn new() -> Self {
+        Weak::new_in(Global)
+    }
+}
+
+impl<T: ?Sized> Weak<T> {
+    /// Constructs a `Weak` from a raw pointer returned by `Weak::into_raw`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Weak::<T>::into_raw`, and each call
+    /// takes over one weak reference.
+    pub unsafe fn from_raw(ptr: *const T) -> Self {
+        Weak::from_raw_in(ptr, Global)
+    }
+}
+
+impl<T> Default for Weak<T> {
+    fn default() -> Self {
+        Weak::new()
+    }
+}
+
+impl<T, A: Allocator> Weak<T, A> {
+    /// Creates a `Weak` which never upgrades, without allocating.
+    pub fn new_in(alloc: A) -> Self {
+        Weak {
+            // SAFETY:
+            // `usize::MAX` is not null.
+            inner: unsafe { NonNull::new_unchecked(ptr::without_provenance_mut(usize::MAX)) },
+            alloc,
+        }
+    }
+}
+
+/// The counts of an `ArcInner`, borrowed without its value, which another
+/// thread may be dropping meanwhile.
+struct WeakInner<'a> {
+    strong: &'a AtomicUsize,
+    weak: &'a AtomicUsize,
+}
+
+impl<T: ?Sized, A: Allocator> Weak<T, A> {
+    /// Returns the counts, or `None` if this came from `Weak::new`.
+    fn inner(&self) -> Option<WeakInner<'_>> {
+        let ptr = self.inner.as_ptr();
+        if is_dangling(ptr) {
+            None
+        } else {
+            // SAFETY:
+            // `inner` is kept alive by this weak reference, and no reference
+            // to `value` is created.
+            unsafe {
+                Some(WeakInner {
+                    strong: &*ptr::addr_of!((*ptr).strong),
+                    weak: &*ptr::addr_of!((*ptr).weak),
+                })
+            }
+        }
+    }
+
+    /// Returns an `Arc` to the value, or `None` if it has already been dropped.
+    pub fn upgrade(&self) -> Option<Arc<T, A>>
+    where
+        A: Clone,
+    {
+        let inner = self.inner()?;
+        // the strong count must not be incremented once it reached zero, since
+        // the value may be being dropped. `Acquire` pairs with the `Release` in
+        // `Arc::new_cyclic_in`, so the value is seen.
+        inner
+            .strong
+            .fetch_update(atomic::Ordering::Acquire, atomic::Ordering::Relaxed, |n| {
+                if n == 0 {
+                    return None;
+                }
+                if n > MAX_REFCOUNT {
+                    process::abort();
+                }
+                Some(n + 1)
+            })
+            .ok()?;
+
+        Some(Arc {
+            inner: self.inner,
+            alloc: self.alloc.clone(),
+        })
+    }
+
+    /// Returns the number of `Arc`s pointing to the value.
+    pub fn strong_count(&self) -> usize {
+        self.inner()
+            .map_or(0, |inner| inner.strong.load(atomic::Ordering::Relaxed))
+    }
+
+    /// Returns the number of `Weak`s pointing to the value, or zero if there is
+    /// no strong reference left. Other threads may change it at any time.
+    pub fn weak_count(&self) -> usize {
+        let Some(inner) = self.inner() else {
+            return 0;
+        };
+        let weak = inner.weak.load(atomic::Ordering::Acquire);
+        let strong = inner.strong.load(atomic::Ordering::Relaxed);
+        if strong == 0 {
+            0
+        } else {
+            // the strong ones hold one implicit weak reference. the weak count
+            // can't be locked by `is_unique` since this `Weak` exists.
+            weak - 1
+        }
+    }
+
+    /// Returns `true` if the two `Weak`s point to the same allocation, or both
+    /// came from `Weak::new`.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        ptr::addr_eq(self.inner.as_ptr(), other.inner.as_ptr())
+    }
+
+    /// Consumes the `Weak`, returning a pointer to the value. The weak reference
+    /// is kept, until it is converted back with `Weak::from_raw`.
+    pub fn into_raw(self) -> *const T {
+        self.into_raw_with_allocator().0
+    }
+
+    /// Consumes the `Weak`, returning a pointer to the value and the allocator.
+    pub fn into_raw_with_allocator(self) -> (*const T, A) {
+        let this = ManuallyDrop::new(self);
+        // SAFETY:
+        // `this` is never used (nor dropped) again, so the allocator is moved out.
+        let alloc = unsafe { ptr::read(&this.alloc) };
+        (this.as_ptr(), alloc)
+    }
+
+    /// Constructs a `Weak` from a raw pointer and its allocator.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must have been returned by `Weak::<T, A>::into_raw`, allocated by
+    /// `alloc`, and each call takes over one weak reference.
+    pub unsafe fn from_raw_in(ptr: *const T, alloc: A) -> Self {
+        let inner = if is_dangling(ptr) {
+            ptr as *mut ArcInner<T>
+        } else {
+            refcount::inner_from_value::<AtomicUsize, T>(ptr) as *mut ArcInner<T>
+        };
+        Weak {
+            inner: NonNull::new_unchecked(inner),
+            alloc,
+        }
+    }
+
+    /// Returns a pointer to the value.
+    ///
+    /// The pointer is only valid to dereference while there is a strong reference,
+    /// and it is dangling if this came from `Weak::new`.
+    pub fn as_ptr(&self) -> *const T {
+        let ptr = self.inner.as_ptr();
+        if is_dangling(ptr) {
+            ptr as *const T
+        } else {
+            // SAFETY:
+            // `inner` is alive, and no reference to `value` is created.
+            unsafe { ptr::addr_of!((*ptr).value) }
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator + Clone> Clone for Weak<T, A> {
+    fn clone(&self) -> Self {
+        if let Some(inner) = self.inner() {
+            // the weak count can't be locked by `is_unique` since this `Weak` exists.
+            let old = inner.weak.fetch_add(1, atomic::Ordering::Relaxed);
+            if old > MAX_REFCOUNT {
+                process::abort();
+            }
+        }
+        Weak {
+            inner: self.inner,
+            alloc: self.alloc.clone(),
+        }
+    }
+}
+
+impl<T: ?Sized, A: Allocator> Drop for Weak<T, A> {
+    fn drop(&mut self) {
+        if is_dangling(self.inner.as_ptr()) {
+            // there is no allocation to release.
+            return;
+        }
+
+        // SAFETY:
+        // `inner` is kept alive by this weak reference, and the strong references
+        // hold the implicit one until `value` is dropped.
+        unsafe { release_weak(self.inner, &self.alloc) };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::alloc::{LeakGuard, TrackingAllocator};
+    use std::sync::atomic::AtomicBool;
+    use std::thread;
+
+    #[derive(Debug)]
+    struct DropCounter<'a>(&'a AtomicUsize);
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn counts_are_updated() {
+        let _leaks = LeakGuard::new();
+        let arc = Arc::new(42);
+        assert_eq!(*arc, 42);
+        assert_eq!(Arc::strong_count(&arc), 1);
+
+        let arc2 = arc.clone();
+        let weak = Arc::downgrade(&arc);
+        assert_eq!(Arc::strong_count(&arc), 2);
+        assert_eq!(Arc::weak_count(&arc), 1);
+        assert_eq!(weak.strong_count(), 2);
+        assert_eq!(weak.weak_count(), 1);
+
+        drop(arc2);
+        assert_eq!(Arc::strong_count(&arc), 1);
+        drop(arc);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(weak.weak_count(), 0);
+    }
+
+    #[test]
+    fn shared_across_threads() {
+        // no `LeakGuard`: the threads free memory the test thread allocated, so
+        // the allocator passed to `Arc` is checked instead.
+        let tracking = TrackingAllocator::new();
+        let drops = AtomicUsize::new(0);
+        let arc = Arc::new_in(DropCounter(&drops), &tracking);
+
+        thread::scope(|s| {
+            for _ in 0..8 {
+                let arc = arc.clone();
+                s.spawn(move || {
+                    for _ in 0..1000 {
+                        let clone = arc.clone();
+                        let weak = Arc::downgrade(&clone);
+                        assert!(weak.upgrade().is_some());
+                    }
+                });
+            }
+        });
+
+        assert_eq!(Arc::strong_count(&arc), 1);
+        assert_eq!(Arc::weak_count(&arc), 0);
+        drop(arc);
+        assert_eq!(drops.load(atomic::Ordering::Relaxed), 1);
+        assert_eq!(tracking.stats().live_allocations(), 0);
+    }
+
+    #[test]
+    fn last_thread_drops_the_value() {
+        // no `LeakGuard`: see `shared_across_threads`.
+        let tracking = TrackingAllocator::new();
+        let drops = AtomicUsize::new(0);
+        let arc = Arc::new_in(DropCounter(&drops), &tracking);
+        let weak = Arc::downgrade(&arc);
+
+        thread::scope(|s| {
+            for _ in 0..8 {
+                let arc = arc.clone();
+                s.spawn(move || drop(arc));
+            }
+            drop(arc);
+        });
+
+        assert_eq!(drops.load(atomic::Ordering::Relaxed), 1);
+        assert!(weak.upgrade().is_none());
+        assert_eq!(tracking.stats().live_allocations(), 1);
+        drop(weak);
+        assert_eq!(tracking.stats().live_allocations(), 0);
+    }
+
+    #[test]
+    fn upgrade_races_with_drop() {
+        // no `LeakGuard`: see `shared_across_threads`.
+        let tracking = TrackingAllocator::new();
+        let drops = AtomicUsize::new(0);
+        for _ in 0..100 {
+            let arc = Arc::new_in(DropCounter(&drops), &tracking);
+            let weak = Arc::downgrade(&arc);
+            let done = AtomicBool::new(false);
+
+            thread::scope(|s| {
+                s.spawn(|| {
+                    while let Some(arc) = weak.upgrade() {
+                        assert!(Arc::strong_count(&arc) > 0);
+                    }
+                    done.store(true, atomic::Ordering::Relaxed);
+                });
+                drop(arc);
+            });
+            assert!(done.load(atomic::Ordering::Relaxed));
+        }
+        assert_eq!(drops.load(atomic::Ordering::Relaxed), 100);
+        assert_eq!(tracking.stats().live_allocations(), 0);
+    }
+
+    #[test]
+    fn into_inner_gives_the_value_to_one_thread() {
+        // no `LeakGuard`: see `shared_across_threads`.
+        for _ in 0..100 {
+            let arc = Arc::new(String::from("once"));
+            let got = AtomicUsize::new(0);
+            thread::scope(|s| {
+                for _ in 0..4 {
+                    let arc = arc.clone();
+                    let got = &got;
+                    s.spawn(move || {
+                        if Arc::into_inner(arc).is_some() {
+                            got.fetch_add(1, atomic::Ordering::Relaxed);
+                        }
+                    });
+                }
+                if Arc::into_inner(arc).is_some() {
+                    got.fetch_add(1, atomic::Ordering::Relaxed);
+                }
+            });
+            assert_eq!(got.load(atomic::Ordering::Relaxed), 1);
+        }
+    }
+
+    #[test]
+    fn unique_ownership() {
+        let _leaks = LeakGuard::new();
+        let mut arc = Arc::new(String::from("a"));
+        Arc::get_mut(&mut arc).unwrap().push('b');
+
+        let arc2 = arc.clone();
+        assert!(Arc::get_mut(&mut arc).is_none());
+        Arc::make_mut(&mut arc).push('c');
+        assert_eq!(*arc, "abc");
+        assert_eq!(*arc2, "ab");
+
+        let weak = Arc::downgrade(&arc);
+        assert!(Arc::get_mut(&mut arc).is_none());
+        Arc::make_mut(&mut arc).push('d');
+        assert!(weak.upgrade().is_none());
+        assert_eq!(*arc, "abcd");
+        // `arc` is unique, so this restores the strong count.
+        Arc::make_mut(&mut arc);
+        assert_eq!(Arc::strong_count(&arc), 1);
+
+        let clone = Arc::try_unwrap(arc2.clone()).unwrap_err();
+        drop(clone);
+        assert_eq!(Arc::try_unwrap(arc2).unwrap(), "ab");
+        assert_eq!(Arc::unwrap_or_clone(arc), "abcd");
+    }
+
+    #[test]
+    fn new_cyclic_and_uninit() {
+        struct Node {
+            this: Weak<Node>,
+        }
+
+        let _leaks = LeakGuard::new();
+        let node = Arc::new_cyclic(|weak| {
+            assert!(weak.upgrade().is_none());
+            Node { this: weak.clone() }
+        });
+        assert!(Arc::ptr_eq(&node, &node.this.upgrade().unwrap()));
+
+        let mut arc = Arc::<String>::new_uninit();
+        Arc::get_mut(&mut arc).unwrap().write(String::from("init"));
+        let arc = unsafe { arc.assume_init() };
+        assert_eq!(*arc, "init");
+
+        let arc = unsafe { Arc::<[u64; 2]>::new_zeroed().assume_init() };
+        assert_eq!(*arc, [0; 2]);
+    }
+
+    #[test]
+    fn raw_pointers() {
+        let _leaks = LeakGuard::new();
+        let arc = Arc::new(String::from("raw"));
+        let raw = Arc::into_raw(arc.clone());
+        assert_eq!(raw, Arc::as_ptr(&arc));
+        unsafe { Arc::increment_strong_count(raw) };
+        assert_eq!(Arc::strong_count(&arc), 3);
+        unsafe { Arc::decrement_strong_count(raw) };
+        let arc2 = unsafe { Arc::from_raw(raw) };
+        assert_eq!(*arc2, "raw");
+        assert_eq!(Arc::strong_count(&arc), 2);
+
+        let raw = Arc::downgrade(&arc).into_raw();
+        let weak = unsafe { Weak::from_raw(raw) };
+        assert_eq!(*weak.upgrade().unwrap(), "raw");
+
+        let weak = unsafe { Weak::<String>::from_raw(Weak::new().into_raw()) };
+        assert!(weak.upgrade().is_none());
+    }
+
+    #[test]
+    fn unsized_arc() {
+        use std::fmt::Debug;
+
+        let _leaks = LeakGuard::new();
+        let arc: Arc<[String]> = Arc::from(crate::vec![String::from("a"), String::from("b")]);
+        assert_eq!(*arc, ["a", "b"]);
+        let arc: Arc<[i32]> = crate::vec![1, 2].into_iter().map(|x| x * 2).collect();
+        assert_eq!(*arc, [2, 4]);
+
+        let s: Arc<str> = Arc::from("shared");
+        let bytes: Arc<[u8]> = Arc::from(s.clone());
+        assert_eq!(&*bytes, b"shared");
+
+        let arc: Arc<dyn Debug + Send + Sync> = unsize_arc!(Arc::new(1) => dyn Debug + Send + Sync);
+        assert_eq!(format!("{:?}", arc), "1");
+
+        let any: Arc<dyn Any + Send + Sync> =
+            unsize_arc!(Arc::new(String::from("any")) => dyn Any + Send + Sync);
+        let any = any.downcast::<i32>().unwrap_err();
+        assert_eq!(*any.downcast::<String>().unwrap(), "any");
+    }
+
+    #[test]
+    fn arc_from_slice_drops_the_clones_if_clone_panics() {
+        // no `LeakGuard`: the test harness allocates to capture the panic message.
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        struct PanicOnClone<'a> {
+            clones: &'a AtomicUsize,
+            drops: &'a AtomicUsize,
+        }
+
+        impl Clone for PanicOnClone<'_> {
+            fn clone(&self) -> Self {
+                if self.clones.load(atomic::Ordering::Relaxed) == 2 {
+                    panic!("clone failed");
+                }
+                self.clones.fetch_add(1, atomic::Ordering::Relaxed);
+                PanicOnClone {
+                    clones: self.clones,
+                    drops: self.drops,
+                }
+            }
+        }
+
+        impl Drop for PanicOnClone<'_> {
+            fn drop(&mut self) {
+                self.drops.fetch_add(1, atomic::Ordering::Relaxed);
+            }
+        }
+
+        let clones = AtomicUsize::new(0);
+        let drops = AtomicUsize::new(0);
+        let v = [(); 3].map(|_| PanicOnClone {
+            clones: &clones,
+            drops: &drops,
+        });
+        let result = catch_unwind(AssertUnwindSafe(|| Arc::<[_]>::from(&v[..])));
+        assert!(result.is_err());
+        assert_eq!(clones.load(atomic::Ordering::Relaxed), 2);
+        assert_eq!(drops.load(atomic::Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn std_traits() {
+        use std::collections::HashMap;
+
+        let _leaks = LeakGuard::new();
+        let mut map: HashMap<Arc<str>, i32> = HashMap::new();
+        map.insert(Arc::from("key"), 1);
+        assert_eq!(map.get("key"), Some(&1));
+
+        assert!(Arc::new(1) < Arc::new(2));
+        assert_eq!(Arc::new(1), Arc::new(1));
+        assert_eq!(*Arc::<i32>::default(), 0);
+        assert_eq!(&*Arc::<str>::default(), "");
+        assert_eq!(format!("{}", Arc::new("display")), "display");
+        let arc: Arc<i32> = Arc::from(Bocs::new(3));
+        assert_eq!(*arc, 3);
+    }
+}
@@ -0,0 +1,296 @@
+use super::Vec;
+use crate::alloc::{Allocator, Global};
+use core_orc::iter::traits::{double_ended::DoubleEndedIterator, iterator::Iterator};
+use std::marker::PhantomData;
+use std::ptr::{self, NonNull};
+
+/// An iterator which removes a range of elements from a `Vec`.
+/// It is created by `Vec::drain`.
+pub struct Drain<'a, T, A: Allocator = Global> {
+    /// Index of the first element of the tail, which is preserved.
+    pub(super) tail_start: usize,
+    /// Length of the tail.
+    pub(super) tail_len: usize,
+    /// Index of the next element to yield from the front.
+    cur: usize,
+    /// One past the index of the next element to yield from the back.
+    end: usize,
+    /// `&mut Vec<T>` can't be held directly, because `Splice` needs to write
+    /// to the vector while the `Drain` is alive.
+    pub(super) vec: NonNull<Vec<T, A>>,
+    _marker: PhantomData<&'a mut Vec<T, A>>,
+}
+
+// `Drain` borrows the `Vec` mutably, like `&'a mut Vec<T, A>`.
+unsafe impl<T: Send, A: Allocator + Send> Send for Drain<'_, T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Drain<'_, T, A> {}
+
+impl<'a, T, A: Allocator> Drain<'a, T, A> {
+    /// `vec.len` has to be already shortened to `start`.
+    pub(super) fn new(vec: &'a mut Vec<T, A>, start: usize, end: usize, old_len: usize) -> Self {
+        Drain {
+            tail_start: end,
+            tail_len: old_len - end,
+            cur: start,
+            end,
+            vec: NonNull::from(vec),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the elements which have not been yielded yet.
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY:
+        // `cur..end` are initialized and not yet moved out.
+        unsafe {
+            let ptr = self.vec.as_ref().as_ptr().add(self.cur);
+            std::slice::from_raw_parts(ptr, self.end - self.cur)
+        }
+    }
+
+    /// Fills the gap between `vec.len` and `tail_start` with the elements of `replace_with`.
+    /// Returns `true` if the whole gap was filled.
+    ///
+    /// # Safety
+    ///
+    /// All the elements of the drained range must have been yielded already.
+    pub(super) unsafe fn fill<I: Iterator<Item = T>>(&mut self, replace_with: &mut I) -> bool {
+        let vec = self.vec.as_mut();
+        let range_start = vec.len;
+        let range_end = self.tail_start;
+
+        for idx in range_start..range_end {
+            match replace_with.next() {
+                Some(new_item) => {
+                    ptr::write(vec.as_mut_ptr().add(idx), new_item);
+                    vec.len += 1;
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Moves the tail `additional` elements backwards, growing the buffer if needed.
+    ///
+    /// # Safety
+    ///
+    /// Same as `fill`.
+    pub(super) unsafe fn move_tail(&mut self, additional: usize) {
+        let vec = self.vec.as_mut();
+        vec.reserve_for(self.tail_start + self.tail_len, additional);
+
+        let new_tail_start = self.tail_start + additional;
+        let src = vec.as_ptr().add(self.tail_start);
+        let dst = vec.as_mut_ptr().add(new_tail_start);
+        ptr::copy(src, dst, self.tail_len);
+
+        self.tail_start = new_tail_start;
+    }
+}
+
+impl<T, A: Allocator> Iterator for Drain<'_, T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cur == self.end {
+            None
+        } else {
+            // SAFETY:
+            // the element at `cur` is initialized, and we never read it again.
+            let next = unsafe { ptr::read(self.vec.as_ref().as_ptr().add(self.cur)) };
+            self.cur += 1;
+            Some(next)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let v = self.end - self.cur;
+        (v, Some(v))
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for Drain<'_, T, A> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.cur == self.end {
+            None
+        } else {
+            self.end -= 1;
+            // SAFETY:
+            // the element at `end` is initialized, and we never read it again.
+            unsafe { Some(ptr::read(self.vec.as_ref().as_ptr().add(self.end))) }
+        }
+    }
+}
+
+impl<T, A: Allocator> Drop for Drain<'_, T, A> {
+    fn drop(&mut self) {
+        // Moves the tail back to cover the drained range and restores `vec.len`.
+        // This is a guard so that it runs even when dropping an element panics.
+        struct DropGuard<'r, 'a, T, A: Allocator>(&'r mut Drain<'a, T, A>);
+
+        impl<T, A: Allocator> Drop for DropGuard<'_, '_, T, A> {
+            fn drop(&mut self) {
+                let drain = &mut *self.0;
+                if drain.tail_len == 0 {
+                    return;
+                }
+
+                // SAFETY:
+                // the tail is initialized, and `vec.len <= tail_start`.
+                unsafe {
+                    let vec = drain.vec.as_mut();
+                    let start = vec.len;
+                    if drain.tail_start != start {
+                        let src = vec.as_ptr().add(drain.tail_start);
+                        let dst = vec.as_mut_ptr().add(start);
+                        ptr::copy(src, dst, drain.tail_len);
+                    }
+                    vec.len = start + drain.tail_len;
+                }
+            }
+        }
+
+        let guard = DropGuard(self);
+
+        // drop the elements which have not been yielded.
+        let drain = &mut *guard.0;
+        let remaining = drain.end - drain.cur;
+        // SAFETY:
+        // `cur..end` are initialized, and we mark them as yielded before dropping them.
+        unsafe {
+            let ptr = drain.vec.as_mut().as_mut_ptr().add(drain.cur);
+            drain.cur = drain.end;
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(ptr, remaining));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Vec;
+    use crate::alloc::LeakGuard;
+    use core_orc::iter::traits::{double_ended::DoubleEndedIterator, iterator::Iterator};
+    use std::cell::Cell;
+    use std::mem;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    struct PanicOnDrop<'a> {
+        panic: bool,
+        count: &'a Cell<usize>,
+    }
+
+    impl Drop for PanicOnDrop<'_> {
+        fn drop(&mut self) {
+            self.count.set(self.count.get() + 1);
+            if self.panic {
+                panic!("panic in drop");
+            }
+        }
+    }
+
+    #[test]
+    fn test_drain() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5];
+        let drained = v.drain(1..3).collect::<Vec<_>>();
+
+        assert_eq!(drained.as_slice(), &[2, 3]);
+        assert_eq!(v.as_slice(), &[1, 4, 5]);
+    }
+
+    #[test]
+    fn test_drain_ranges() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5];
+        assert_eq!(v.drain(..).collect::<Vec<_>>().as_slice(), &[1, 2, 3, 4, 5]);
+        assert!(v.is_empty());
+
+        let mut v = vec![1, 2, 3, 4, 5];
+        assert_eq!(v.drain(3..).collect::<Vec<_>>().as_slice(), &[4, 5]);
+        assert_eq!(v.as_slice(), &[1, 2, 3]);
+
+        let mut v = vec![1, 2, 3, 4, 5];
+        assert_eq!(v.drain(..=1).collect::<Vec<_>>().as_slice(), &[1, 2]);
+        assert_eq!(v.as_slice(), &[3, 4, 5]);
+
+        let mut v = vec![1, 2, 3];
+        assert!(v.drain(1..1).next().is_none());
+        assert_eq!(v.as_slice(), &[1, 2, 3]);
+    }
+
+    #[test]
+    fn test_drain_double_ended() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5];
+        let mut drain = v.drain(1..4);
+
+        assert_eq!(drain.next_back(), Some(4));
+        assert_eq!(drain.next(), Some(2));
+        assert_eq!(drain.as_slice(), &[3]);
+        assert_eq!(drain.next_back(), Some(3));
+        assert_eq!(drain.next_back(), None);
+        assert_eq!(drain.next(), None);
+        drop(drain);
+
+        assert_eq!(v.as_slice(), &[1, 5]);
+    }
+
+    #[test]
+    fn test_drain_partially_consumed() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5];
+        let mut drain = v.drain(1..4);
+        assert_eq!(drain.next(), Some(2));
+        drop(drain);
+
+        assert_eq!(v.as_slice(), &[1, 5]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_drain_out_of_bounds() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3];
+        v.drain(2..5);
+    }
+
+    #[test]
+    fn test_drain_forget() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5];
+        let mut drain = v.drain(1..3);
+        assert_eq!(drain.next(), Some(2));
+        mem::forget(drain);
+
+        // the vector is truncated at the start of the range, but still usable.
+        assert_eq!(v.as_slice(), &[1]);
+        v.push(6);
+        assert_eq!(v.as_slice(), &[1, 6]);
+    }
+
+    #[test]
+    fn test_drain_drop_panic() {
+        // no `LeakGuard`: the test harness allocates to capture the panic message.
+        let count = Cell::new(0);
+        let mut v = Vec::new();
+        for i in 0..5 {
+            v.push(PanicOnDrop {
+                panic: i == 1,
+                count: &count,
+            });
+        }
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            v.drain(1..3);
+        }));
+        assert!(result.is_err());
+
+        // both drained elements were dropped, and the tail was moved back.
+        assert_eq!(count.get(), 2);
+        assert_eq!(v.len(), 3);
+
+        drop(v);
+        assert_eq!(count.get(), 5);
+    }
+}
@@ -0,0 +1,163 @@
+use super::{slice_range, Vec};
+use crate::alloc::{Allocator, Global};
+use core_orc::iter::traits::iterator::Iterator;
+use std::ops::{Range, RangeBounds};
+use std::ptr;
+
+/// An iterator which removes and yields the elements of a `Vec` matching a predicate.
+/// It is created by `Vec::extract_if`.
+pub struct ExtractIf<'a, T, F, A: Allocator = Global> {
+    vec: &'a mut Vec<T, A>,
+    /// Index of the next element to check.
+    idx: usize,
+    /// One past the index of the last element to check.
+    end: usize,
+    /// Number of elements removed so far.
+    del: usize,
+    /// Length of the vector before `extract_if` was called.
+    old_len: usize,
+    pred: F,
+}
+
+impl<'a, T, F, A: Allocator> ExtractIf<'a, T, F, A> {
+    pub(super) fn new<R: RangeBounds<usize>>(vec: &'a mut Vec<T, A>, pred: F, range: R) -> Self {
+        let old_len = vec.len;
+        let Range { start, end } = slice_range(range, old_len);
+
+        // Hide all the elements from the vector while iterating, since we make
+        // holes in it. `Drop` restores the length.
+        vec.len = 0;
+
+        ExtractIf {
+            vec,
+            idx: start,
+            end,
+            del: 0,
+            old_len,
+            pred,
+        }
+    }
+}
+
+impl<T, F, A: Allocator> Iterator for ExtractIf<'_, T, F, A>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // SAFETY:
+        // `idx..old_len` are initialized and haven't been moved yet, and
+        // `idx - del` always points at a hole (or `idx` itself).
+        unsafe {
+            while self.idx < self.end {
+                let i = self.idx;
+                let cur = self.vec.as_mut_ptr().add(i);
+                let drained = (self.pred)(&mut *cur);
+
+                // update the index *after* calling the predicate, so that the
+                // element is not leaked if the predicate panics.
+                self.idx += 1;
+
+                if drained {
+                    self.del += 1;
+                    return Some(ptr::read(cur));
+                } else if self.del > 0 {
+                    let dst = self.vec.as_mut_ptr().add(i - self.del);
+                    ptr::copy_nonoverlapping(cur, dst, 1);
+                }
+            }
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.end - self.idx))
+    }
+}
+
+impl<T, F, A: Allocator> Drop for ExtractIf<'_, T, F, A> {
+    fn drop(&mut self) {
+        // SAFETY:
+        // `idx..old_len` are initialized, and the `del` slots before `idx` are holes.
+        unsafe {
+            if self.idx < self.old_len && self.del > 0 {
+                let ptr = self.vec.as_mut_ptr();
+                let src = ptr.add(self.idx);
+                let dst = src.sub(self.del);
+                ptr::copy(src, dst, self.old_len - self.idx);
+            }
+            self.vec.len = self.old_len - self.del;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Vec;
+    use crate::alloc::LeakGuard;
+    use core_orc::iter::traits::iterator::Iterator;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[test]
+    fn test_extract_if() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5, 6];
+        let evens = v.extract_if(.., |x| *x % 2 == 0).collect::<Vec<_>>();
+
+        assert_eq!(evens.as_slice(), &[2, 4, 6]);
+        assert_eq!(v.as_slice(), &[1, 3, 5]);
+    }
+
+    #[test]
+    fn test_extract_if_range() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![2, 4, 6, 8];
+        let extracted = v.extract_if(1..3, |_| true).collect::<Vec<_>>();
+
+        assert_eq!(extracted.as_slice(), &[4, 6]);
+        assert_eq!(v.as_slice(), &[2, 8]);
+    }
+
+    #[test]
+    fn test_extract_if_partially_consumed() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5, 6];
+        let mut iter = v.extract_if(.., |x| *x % 2 == 0);
+        assert_eq!(iter.next(), Some(2));
+        drop(iter);
+
+        // the unchecked elements are kept.
+        assert_eq!(v.as_slice(), &[1, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_extract_if_predicate_panic() {
+        // no `LeakGuard`: the test harness allocates to capture the panic message.
+        let mut v = vec![1, 2, 3, 4, 5, 6];
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut iter = v.extract_if(.., |x| {
+                if *x == 4 {
+                    panic!("predicate panicked");
+                }
+                *x % 2 == 0
+            });
+            while iter.next().is_some() {}
+        }));
+        assert!(result.is_err());
+
+        assert_eq!(v.as_slice(), &[1, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_extract_if_forget() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3];
+        let mut iter = v.extract_if(.., |_| true);
+        assert_eq!(iter.next(), Some(1));
+        std::mem::forget(iter);
+
+        assert!(v.is_empty());
+    }
+}
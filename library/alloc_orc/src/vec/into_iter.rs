@@ -0,0 +1,151 @@
+use super::Vec;
+use crate::alloc::{Allocator, Global, Layout};
+use core_orc::iter::traits::{double_ended::DoubleEndedIterator, iterator::Iterator};
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop};
+use std::ptr::{self, NonNull};
+
+/// An iterator which moves the elements out of a `Vec`.
+/// It is created by `IntoIterator::into_iter`.
+pub struct IntoIter<T, A: Allocator = Global> {
+    buf: NonNull<T>,
+    cap: usize,
+    alloc: ManuallyDrop<A>,
+    cur_pos: usize,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+// `IntoIter` owns the remaining elements, like `Vec`.
+unsafe impl<T: Send, A: Allocator + Send> Send for IntoIter<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for IntoIter<T, A> {}
+
+impl<T, A: Allocator> IntoIter<T, A> {
+    pub(super) fn new(vec: Vec<T, A>) -> Self {
+        // the buffer is owned by `IntoIter` from now on.
+        let vec = ManuallyDrop::new(vec);
+
+        IntoIter {
+            buf: vec.ptr,
+            cap: vec.cap,
+            // SAFETY:
+            // `vec` is never used (nor dropped) again, so the allocator is moved out.
+            alloc: ManuallyDrop::new(unsafe { ptr::read(&vec.alloc) }),
+            cur_pos: 0,
+            len: vec.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the elements which have not been yielded yet.
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY:
+        // `cur_pos..len` are initialized and not yet moved out.
+        unsafe {
+            std::slice::from_raw_parts(self.buf.as_ptr().add(self.cur_pos), self.len - self.cur_pos)
+        }
+    }
+}
+
+impl<T, A: Allocator> Iterator for IntoIter<T, A> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // boundary check
+        if self.cur_pos == self.len {
+            None
+        } else {
+            let next = unsafe { ptr::read(self.buf.as_ptr().add(self.cur_pos)) };
+            self.cur_pos += 1;
+            Some(next)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let v = self.len - self.cur_pos;
+        (v, Some(v))
+    }
+}
+
+impl<T, A: Allocator> DoubleEndedIterator for IntoIter<T, A> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.cur_pos == self.len {
+            None
+        } else {
+            self.len -= 1;
+            // SAFETY:
+            // the element at `len` is initialized, and never read again.
+            unsafe { Some(ptr::read(self.buf.as_ptr().add(self.len))) }
+        }
+    }
+}
+
+impl<T, A: Allocator> Drop for IntoIter<T, A> {
+    fn drop(&mut self) {
+        // SAFETY:
+        // `cur_pos..len` are initialized, and the buffer was allocated by `Vec`
+        // with `Layout::array::<T>(cap)`.
+        unsafe {
+            let remaining = ptr::slice_from_raw_parts_mut(
+                self.buf.as_ptr().add(self.cur_pos),
+                self.len - self.cur_pos,
+            );
+            self.cur_pos = self.len;
+            ptr::drop_in_place(remaining);
+
+            if self.cap != 0 && mem::size_of::<T>() != 0 {
+                self.alloc
+                    .deallocate(self.buf.cast(), Layout::array::<T>(self.cap).unwrap());
+            }
+            ManuallyDrop::drop(&mut self.alloc);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::alloc::LeakGuard;
+    use core_orc::{
+        into_iterator::IntoIterator,
+        iter::traits::{double_ended::DoubleEndedIterator, iterator::Iterator},
+    };
+
+    #[test]
+    fn test_into_iter() {
+        let _leaks = LeakGuard::new();
+        let v = vec![1, 2, 3];
+        let mut iter = v.into_iter();
+
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.next(), Some(1));
+        assert_eq!(iter.as_slice(), &[2, 3]);
+        assert_eq!(iter.next(), Some(2));
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn test_into_iter_drops_remaining() {
+        let _leaks = LeakGuard::new();
+        let v = vec![String::from("a"), String::from("b")];
+        let mut iter = v.into_iter();
+        assert_eq!(iter.next().as_deref(), Some("a"));
+    }
+
+    #[test]
+    fn test_into_iter_double_ended() {
+        let _leaks = LeakGuard::new();
+        let v = vec![String::from("a"), String::from("b"), String::from("c")];
+        let mut iter = v.into_iter();
+
+        assert_eq!(iter.next_back().as_deref(), Some("c"));
+        assert_eq!(iter.as_slice(), &["a", "b"]);
+        assert_eq!(iter.next().as_deref(), Some("a"));
+        assert_eq!(iter.next_back().as_deref(), Some("b"));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+
+        let v = vec![1, 2, 3];
+        assert_eq!(v.into_iter().rev().collect::<super::Vec<_>>(), [3, 2, 1]);
+    }
+}
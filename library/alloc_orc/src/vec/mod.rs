@@ -1,67 +1,143 @@
 // Resources:
 // https://doc.rust-lang.org/nomicon/vec/vec-alloc.html
 
-// TODO:
-// * be able to reallocate
-// * impl Eq, Drop
+use crate::alloc::{handle_alloc_error, Allocator, Global, Layout};
+use crate::bocs::Bocs;
+use core_orc::{
+    borrow::{Borrow, BorrowMut},
+    into_iterator::IntoIterator,
+    iter::traits::{collector::FromIterator, iterator::Iterator},
+};
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::mem::{self, ManuallyDrop};
+use std::ops::{Bound, Deref, DerefMut, Range, RangeBounds};
+use std::ptr::{self, NonNull};
+use std::slice;
 
-use std::alloc::{self, dealloc, Layout};
-use std::ptr;
+mod drain;
+mod extract_if;
+mod into_iter;
+mod partial_eq;
+mod splice;
+
+pub use drain::Drain;
+pub use extract_if::ExtractIf;
+pub use into_iter::IntoIter;
+pub use splice::Splice;
 
 // TODO: rename
-#[derive(Debug)]
-pub struct Vec<T> {
-    // TODO: Use unique.
-    ptr: *const T,
+pub struct Vec<T, A: Allocator = Global> {
+    ptr: NonNull<T>,
     len: usize,
     cap: usize,
+    alloc: A,
+    // tell the drop checker that we own values of type `T`.
+    _marker: PhantomData<T>,
 }
 
+// `Vec` owns its elements (and the allocator), so it is `Send`/`Sync` if they are.
+unsafe impl<T: Send, A: Allocator + Send> Send for Vec<T, A> {}
+unsafe impl<T: Sync, A: Allocator + Sync> Sync for Vec<T, A> {}
+
 impl<T> Vec<T> {
+    pub fn new() -> Self {
+        Vec::new_in(Global)
+    }
+
+    /// Creates an empty vector which can hold at least `cap` elements
+    /// without reallocating.
+    pub fn with_capacity(cap: usize) -> Self {
+        Vec::with_capacity_in(cap, Global)
+    }
+
+    pub fn new_1(x: T) -> Self {
+        let mut v = Vec::new();
+        v.push(x);
+        v
+    }
+}
+
+impl<T, A: Allocator> Vec<T, A> {
     #[inline(always)]
     fn size_of_inner() -> usize {
         std::mem::size_of::<T>()
     }
 
-    pub fn new_with_size_and_cap(len: usize, cap: usize) -> Self {
-        // For more detail, see https://doc.rust-lang.org/nomicon/vec/vec-alloc.html
-        assert!(cap != 0, "cap == 0 is prohibited for now.");
-
-        let layout = Layout::array::<T>(cap).unwrap();
-
-        // SAFETY:
-        // Here, we ensure that allocated memory is not zero-sized.
-        let ptr = unsafe { alloc::alloc(layout) };
+    /// Creates an empty vector which allocates from `alloc`.
+    pub fn new_in(alloc: A) -> Self {
+        // zero-sized types never need an allocation, so we can treat the
+        // capacity as infinite.
+        let cap = if Self::size_of_inner() == 0 {
+            usize::MAX
+        } else {
+            0
+        };
 
-        Vec {
-            ptr: ptr as *const T,
-            len: len,
-            cap: cap,
+        Self {
+            ptr: NonNull::dangling(),
+            len: 0,
+            cap,
+            alloc,
+            _marker: PhantomData,
         }
     }
 
-    pub fn new() -> Self {
-        Self {
-            ptr: ptr::null(),
-            len: 0,
-            cap: 0,
+    /// Same as `with_capacity`, but allocates from `alloc`.
+    pub fn with_capacity_in(cap: usize, alloc: A) -> Self {
+        let mut v = Vec::new_in(alloc);
+        if cap > v.cap {
+            v.grow(cap);
         }
+        v
     }
 
-    pub fn new_1(x: T) -> Self {
-        let layout = Layout::array::<T>(1).unwrap();
+    /// Returns a reference to the underlying allocator.
+    pub fn allocator(&self) -> &A {
+        &self.alloc
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    pub fn as_ptr(&self) -> *const T {
+        self.ptr.as_ptr()
+    }
+
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        self.ptr.as_ptr()
+    }
 
+    pub fn as_slice(&self) -> &[T] {
         // SAFETY:
-        // Here, we ensure that allocated memory is not zero-sized.
-        let m = unsafe { alloc::alloc(layout) as *mut T };
+        // the first `len` elements are always initialized.
+        unsafe { slice::from_raw_parts(self.as_ptr(), self.len) }
+    }
 
-        unsafe { ptr::write(m, x) };
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY:
+        // the first `len` elements are always initialized.
+        unsafe { slice::from_raw_parts_mut(self.as_mut_ptr(), self.len) }
+    }
 
-        Vec {
-            ptr: m as *const T,
-            len: 1,
-            cap: 1,
-        }
+    /// # Safety
+    ///
+    /// `new_len` must be less than or equal to `capacity()`, and the elements
+    /// at `old_len..new_len` must be initialized.
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.cap);
+        self.len = new_len;
     }
 
     pub fn at(&self, index: usize) -> &T {
@@ -74,56 +150,665 @@ impl<T> Vec<T> {
         );
 
         // SAFETY:
-        // this memory location is allocated by `Self::new_with_size_and_cap`,
+        // this memory location is allocated by `Self::grow`,
         // and not be null, dangling, unaligned.
-        unsafe { &*(self.ptr as *mut T).wrapping_add(index) }
+        unsafe { &*self.as_ptr().add(index) }
     }
 
-    pub fn push(&mut self, x: T) {
-        if self.len + 1 <= self.cap {
-            // here, we don't need to allocate additional heap memory.
+    /// Reserves capacity for at least `additional` more elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.reserve_for(self.len, additional);
+    }
+
+    /// Same as `reserve`, but counts from `len` instead of `self.len`.
+    /// `Drain` uses this since it hides its tail from `self.len`.
+    fn reserve_for(&mut self, len: usize, additional: usize) {
+        let required = len.checked_add(additional).expect("capacity overflow");
+        if required <= self.cap {
+            return;
+        }
 
-            let offset = (self.ptr as *mut T).wrapping_add(self.len);
+        // usually, it is common way to `double` its cap when we want to grow the vector's cap.
+        let new_cap = required.max(self.cap * 2).max(8);
+        self.grow(new_cap);
+    }
+
+    /// Reallocates the buffer so that it can hold `new_cap` elements.
+    fn grow(&mut self, new_cap: usize) {
+        debug_assert!(new_cap > self.cap);
+
+        let new_layout = Layout::array::<T>(new_cap).expect("capacity overflow");
+
+        // zero-sized types never reach here, since their `cap` is `usize::MAX`.
+        let new_ptr = if self.cap == 0 {
+            self.alloc.allocate(new_layout)
+        } else {
+            let old_layout = Layout::array::<T>(self.cap).unwrap();
             // SAFETY:
-            // dest is writable and aligned.
-            unsafe { ptr::write(offset, x) };
-            self.len += 1;
+            // `ptr` was allocated by `self.alloc` with `old_layout`.
+            unsafe { self.alloc.grow(self.ptr.cast(), old_layout, new_layout) }
+        };
+
+        self.ptr = match new_ptr {
+            Ok(p) => p.cast(),
+            Err(_) => handle_alloc_error(new_layout),
+        };
+        self.cap = new_cap;
+    }
+
+    /// Shrinks the capacity of the vector as much as possible.
+    pub fn shrink_to_fit(&mut self) {
+        // zero-sized types never allocate.
+        if mem::size_of::<T>() == 0 || self.cap == self.len {
+            return;
+        }
+
+        let old_layout = Layout::array::<T>(self.cap).unwrap();
+        if self.len == 0 {
+            // SAFETY:
+            // `ptr` was allocated by `self.alloc` with `old_layout`.
+            unsafe { self.alloc.deallocate(self.ptr.cast(), old_layout) };
+            self.ptr = NonNull::dangling();
         } else {
+            let new_layout = Layout::array::<T>(self.len).unwrap();
+            // SAFETY:
+            // `ptr` was allocated by `self.alloc` with `old_layout`, and
+            // `new_layout` is smaller.
+            let new_ptr = unsafe { self.alloc.shrink(self.ptr.cast(), old_layout, new_layout) };
+            self.ptr = match new_ptr {
+                Ok(p) => p.cast(),
+                Err(_) => handle_alloc_error(new_layout),
+            };
+        }
+        self.cap = self.len;
+    }
+
+    /// Converts the vector into `Bocs<[T]>`, dropping any excess capacity.
+    pub fn into_boxed_slice(mut self) -> Bocs<[T], A> {
+        self.shrink_to_fit();
+
+        let me = ManuallyDrop::new(self);
+        let slice = ptr::slice_from_raw_parts_mut(me.ptr.as_ptr(), me.len);
+        // SAFETY:
+        // the buffer was allocated by `alloc` with `Layout::array::<T>(len)`,
+        // which is the layout of `[T]` of `len` elements. `me` is never
+        // used (nor dropped) again, so the allocator is moved out.
+        unsafe {
+            let alloc = ptr::read(&me.alloc);
+            Bocs::from_raw_in(slice, alloc)
+        }
+    }
+
+    pub fn push(&mut self, x: T) {
+        if self.len == self.cap {
             // here, we have to reallocate heap memory for this vec.
+            self.reserve(1);
+        }
+
+        let offset = self.as_mut_ptr().wrapping_add(self.len);
+        // SAFETY:
+        // dest is writable and aligned.
+        unsafe { ptr::write(offset, x) };
+        self.len += 1;
+    }
 
-            // usually, it is common way to `double` its cap when we want to grow the vector's cap.
-            let new_cap = if self.cap == 0 { 8 } else { self.cap * 2 };
-            let layout = Layout::array::<T>(new_cap).unwrap();
-            let new_ptr = unsafe { alloc::alloc(layout) };
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            None
+        } else {
+            self.len -= 1;
+            // SAFETY:
+            // the element at `len` is initialized, and no longer reachable from the Vec.
+            unsafe { Some(ptr::read(self.as_ptr().add(self.len))) }
+        }
+    }
 
-            let old_pointer = self.ptr as *mut u8;
-            let old_cap = self.cap;
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len {
+            return;
+        }
 
-            // copy data to new vec.
+        let remaining = self.len - len;
+        // SAFETY:
+        // update `len` first, so that a panic in `T::drop` doesn't cause a double drop.
+        unsafe {
+            let tail = ptr::slice_from_raw_parts_mut(self.as_mut_ptr().add(len), remaining);
+            self.len = len;
+            ptr::drop_in_place(tail);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0)
+    }
+
+    /// Removes the specified range from the vector, returning the removed
+    /// elements as an iterator.
+    ///
+    /// The tail is moved back when the `Drain` is dropped. If the `Drain` is
+    /// leaked (e.g. by `mem::forget`), the vector is left truncated at the
+    /// start of the range, and the drained elements and the tail are leaked.
+    pub fn drain<R>(&mut self, range: R) -> Drain<'_, T, A>
+    where
+        R: RangeBounds<usize>,
+    {
+        let len = self.len;
+        let Range { start, end } = slice_range(range, len);
+
+        // Shorten the vector first, so that no moved-out or uninitialized
+        // element is reachable even if the destructor of `Drain` never runs.
+        self.len = start;
+
+        Drain::new(self, start, end, len)
+    }
+
+    /// Replaces the specified range with the elements of `replace_with`,
+    /// returning the removed elements as an iterator.
+    ///
+    /// The replacement happens when the `Splice` is dropped, even if it was
+    /// not consumed.
+    pub fn splice<R, I>(&mut self, range: R, replace_with: I) -> Splice<'_, I::IntoIter, A>
+    where
+        R: RangeBounds<usize>,
+        I: IntoIterator<Item = T>,
+    {
+        Splice::new(self.drain(range), replace_with.into_iter())
+    }
+
+    /// Returns an iterator which removes and yields the elements in `range`
+    /// for which `filter` returns `true`.
+    ///
+    /// Elements for which `filter` returns `false` stay in the vector in their
+    /// original order. If the iterator is dropped before being exhausted, the
+    /// remaining elements are retained.
+    pub fn extract_if<F, R>(&mut self, range: R, filter: F) -> ExtractIf<'_, T, F, A>
+    where
+        F: FnMut(&mut T) -> bool,
+        R: RangeBounds<usize>,
+    {
+        ExtractIf::new(self, filter, range)
+    }
+
+    /// Retains only the elements for which `f` returns `true`.
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_mut(|elem| f(elem));
+    }
+
+    /// Same as `retain`, but passes a mutable reference to `f`.
+    pub fn retain_mut<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        let original_len = self.len;
+        if original_len == 0 {
+            return;
+        }
+
+        // Avoid double drop if the guard below is leaked, since we make some
+        // holes during the process.
+        self.len = 0;
+
+        // Vec: [Kept, Kept, Hole, Hole, Unchecked, Unchecked]
+        //      |<-   processed_len  ->| ^- next to check
+        //                  |<- deleted ->|
+        //
+        // This guard runs even when `f` or `T::drop` panics. It shifts the
+        // unchecked elements back to cover the holes and restores `len`.
+        struct BackshiftOnDrop<'a, T, A: Allocator> {
+            v: &'a mut Vec<T, A>,
+            processed_len: usize,
+            deleted_cnt: usize,
+            original_len: usize,
+        }
+
+        impl<T, A: Allocator> Drop for BackshiftOnDrop<'_, T, A> {
+            fn drop(&mut self) {
+                if self.deleted_cnt > 0 {
+                    // SAFETY:
+                    // unchecked elements are initialized, and the holes are
+                    // located right before them.
+                    unsafe {
+                        let ptr = self.v.as_mut_ptr();
+                        ptr::copy(
+                            ptr.add(self.processed_len),
+                            ptr.add(self.processed_len - self.deleted_cnt),
+                            self.original_len - self.processed_len,
+                        );
+                    }
+                }
+                self.v.len = self.original_len - self.deleted_cnt;
+            }
+        }
+
+        let mut g = BackshiftOnDrop {
+            v: self,
+            processed_len: 0,
+            deleted_cnt: 0,
+            original_len,
+        };
+
+        while g.processed_len != original_len {
             // SAFETY:
-            // dest is writable and aligned.
-            unsafe { ptr::copy::<T>(self.ptr, new_ptr as *mut T, self.cap) };
+            // the element at `processed_len` is unchecked, so it is initialized.
+            let cur = unsafe { &mut *g.v.as_mut_ptr().add(g.processed_len) };
+            if !f(cur) {
+                // advance early, so that a panic in `drop_in_place` doesn't cause a double drop.
+                g.processed_len += 1;
+                g.deleted_cnt += 1;
+                unsafe { ptr::drop_in_place(cur) };
+                continue;
+            }
+            if g.deleted_cnt > 0 {
+                // SAFETY:
+                // `deleted_cnt > 0`, so the hole slot doesn't overlap with `cur`.
+                unsafe {
+                    let hole = g.v.as_mut_ptr().add(g.processed_len - g.deleted_cnt);
+                    ptr::copy_nonoverlapping(cur, hole, 1);
+                }
+            }
+            g.processed_len += 1;
+        }
+
+        // `g` fixes up `len` here.
+        drop(g);
+    }
+
+    /// Removes consecutive elements that resolve to the same key.
+    pub fn dedup_by_key<F, K>(&mut self, mut key: F)
+    where
+        F: FnMut(&mut T) -> K,
+        K: PartialEq,
+    {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+
+    /// Removes consecutive elements for which `same_bucket(current, previous)`
+    /// returns `true`.
+    pub fn dedup_by<F>(&mut self, mut same_bucket: F)
+    where
+        F: FnMut(&mut T, &mut T) -> bool,
+    {
+        let len = self.len;
+        if len <= 1 {
+            return;
+        }
+
+        // INVARIANT: len > read >= write > write - 1 >= 0
+        //
+        // This guard runs only when `same_bucket` or `T::drop` panics. It moves
+        // the unchecked elements `read..len` to `write`, so that there is no hole.
+        struct FillGapOnDrop<'a, T, A: Allocator> {
+            // offset of the element we want to check next.
+            read: usize,
+            // offset of the place where the next distinct element goes.
+            write: usize,
+            vec: &'a mut Vec<T, A>,
+        }
+
+        impl<T, A: Allocator> Drop for FillGapOnDrop<'_, T, A> {
+            fn drop(&mut self) {
+                // SAFETY:
+                // `read..len` are initialized and `write <= read`.
+                unsafe {
+                    let ptr = self.vec.as_mut_ptr();
+                    let len = self.vec.len;
+                    ptr::copy(ptr.add(self.read), ptr.add(self.write), len - self.read);
+                    self.vec.len = len - (self.read - self.write);
+                }
+            }
+        }
+
+        let mut gap = FillGapOnDrop {
+            read: 1,
+            write: 1,
+            vec: self,
+        };
+        let ptr = gap.vec.as_mut_ptr();
+
+        // SAFETY:
+        // `read` and `write` are always in bounds, and `write - 1` always points
+        // at the last kept element.
+        unsafe {
+            while gap.read < len {
+                let read_ptr = ptr.add(gap.read);
+                let prev_ptr = ptr.add(gap.write - 1);
+
+                if same_bucket(&mut *read_ptr, &mut *prev_ptr) {
+                    // advance early, so that a panic in `drop_in_place` doesn't cause a double drop.
+                    gap.read += 1;
+                    ptr::drop_in_place(read_ptr);
+                } else {
+                    ptr::copy(read_ptr, ptr.add(gap.write), 1);
+                    gap.write += 1;
+                    gap.read += 1;
+                }
+            }
+
+            gap.vec.len = gap.write;
+            mem::forget(gap);
+        }
+    }
+}
+
+impl<T: PartialEq, A: Allocator> Vec<T, A> {
+    /// Removes consecutive repeated elements.
+    pub fn dedup(&mut self) {
+        self.dedup_by(|a, b| a == b)
+    }
+}
+
+// sorting. See `core_orc::slice` for details.
+impl<T, A: Allocator> Vec<T, A> {
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        core_orc::slice::sort(self)
+    }
+
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        core_orc::slice::sort_by(self, compare)
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        core_orc::slice::sort_by_key(self, f)
+    }
+
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        core_orc::slice::sort_unstable(self)
+    }
+
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        core_orc::slice::sort_unstable_by(self, compare)
+    }
+
+    pub fn sort_unstable_by_key<K, F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        core_orc::slice::sort_unstable_by_key(self, f)
+    }
+
+    pub fn select_nth_unstable(&mut self, index: usize) -> (&mut [T], &mut T, &mut [T])
+    where
+        T: Ord,
+    {
+        core_orc::slice::select_nth_unstable(self, index)
+    }
 
-            // write new pushed data.
-            unsafe { ptr::write((new_ptr as *mut T).wrapping_add(self.cap), x) }
+    pub fn select_nth_unstable_by<F>(
+        &mut self,
+        index: usize,
+        compare: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        core_orc::slice::select_nth_unstable_by(self, index, compare)
+    }
+
+    pub fn select_nth_unstable_by_key<K, F>(
+        &mut self,
+        index: usize,
+        f: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        core_orc::slice::select_nth_unstable_by_key(self, index, f)
+    }
+}
+
+/// Creates a vector with `n` clones of `elem`. This is used by `vec![elem; n]`.
+///
+/// `elem` itself is moved into the last slot, so it is cloned `n - 1` times.
+#[doc(hidden)]
+pub fn from_elem<T: Clone>(elem: T, n: usize) -> Vec<T> {
+    let mut v = Vec::with_capacity(n);
+    if n > 0 {
+        for _ in 1..n {
+            v.push(elem.clone());
+        }
+        v.push(elem);
+    }
+    v
+}
+
+/// Converts `range` into `start..end`, panicking if it is out of `0..len`.
+fn slice_range<R>(range: R, len: usize) -> Range<usize>
+where
+    R: RangeBounds<usize>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start
+            .checked_add(1)
+            .expect("attempted to index slice from after maximum usize"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end
+            .checked_add(1)
+            .expect("attempted to index slice up to maximum usize"),
+        Bound::Excluded(&end) => end,
+        Bound::Unbounded => len,
+    };
+
+    assert!(
+        start <= end,
+        "slice index starts at {} but ends at {}",
+        start,
+        end
+    );
+    assert!(
+        end <= len,
+        "range end index {} out of range for slice of length {}",
+        end,
+        len
+    );
+
+    start..end
+}
+
+//
+// trait implementation
+//
+
+impl<T> Default for Vec<T> {
+    fn default() -> Self {
+        Vec::new()
+    }
+}
+
+impl<T: Clone, A: Allocator + Clone> Clone for Vec<T, A> {
+    fn clone(&self) -> Self {
+        let mut new_vec = Vec::with_capacity_in(self.len, self.alloc.clone());
+        // `push` updates `len` one by one, so the cloned elements are dropped
+        // properly even if `T::clone` panics.
+        for v in self.as_slice() {
+            new_vec.push(v.clone());
+        }
+        new_vec
+    }
+}
+
+/// Prints the elements like a list, e.g. `[1, 2, 3]`.
+impl<T: fmt::Debug, A: Allocator> fmt::Debug for Vec<T, A> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl<T: Eq, A: Allocator> Eq for Vec<T, A> {}
+
+/// Compares the elements lexicographically.
+impl<T: PartialOrd, A: Allocator> PartialOrd for Vec<T, A> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PartialOrd::partial_cmp(self.as_slice(), other.as_slice())
+    }
+}
+
+impl<T: Ord, A: Allocator> Ord for Vec<T, A> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(self.as_slice(), other.as_slice())
+    }
+}
+
+/// Hashes the same as the slice of its elements, so that `Borrow<[T]>`-based
+/// lookups would work.
+impl<T: Hash, A: Allocator> Hash for Vec<T, A> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(self.as_slice(), state)
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vec<T> {
+    fn from(arr: [T; N]) -> Self {
+        let mut new_vec = Vec::new();
+        new_vec.reserve(N);
+        for v in arr {
+            new_vec.push(v);
+        }
+        new_vec
+    }
+}
+
+impl<T: Clone> From<&[T]> for Vec<T> {
+    fn from(s: &[T]) -> Self {
+        let mut new_vec = Vec::new();
+        new_vec.reserve(s.len());
+        for v in s {
+            new_vec.push(v.clone());
+        }
+        new_vec
+    }
+}
+
+/// Takes over the buffer of `std::vec::Vec` without copying the elements.
+impl<T> From<std::vec::Vec<T>> for Vec<T> {
+    fn from(v: std::vec::Vec<T>) -> Self {
+        let mut v = ManuallyDrop::new(v);
+
+        // SAFETY:
+        // `std::vec::Vec` allocates its buffer by the global allocator with
+        // `Layout::array::<T>(capacity)` (or doesn't allocate at all, when
+        // `capacity` is 0 or `T` is zero-sized), which is what we do as well.
+        Vec {
+            ptr: NonNull::new(v.as_mut_ptr()).unwrap(),
+            len: v.len(),
+            cap: v.capacity(),
+            alloc: Global,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, A: Allocator> Deref for Vec<T, A> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, A: Allocator> DerefMut for Vec<T, A> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, A: Allocator> Borrow<[T]> for Vec<T, A> {
+    fn borrow(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T, A: Allocator> BorrowMut<[T]> for Vec<T, A> {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T, A: Allocator> Drop for Vec<T, A> {
+    fn drop(&mut self) {
+        // SAFETY:
+        // the first `len` elements are initialized, and the buffer was allocated
+        // by `Self::grow` with `Layout::array::<T>(cap)` unless `cap` is 0 or `T` is zero-sized.
+        unsafe {
+            ptr::drop_in_place(self.as_mut_slice());
+            if self.cap != 0 && Self::size_of_inner() != 0 {
+                self.alloc
+                    .deallocate(self.ptr.cast(), Layout::array::<T>(self.cap).unwrap());
+            }
+        }
+    }
+}
 
-            // mutate Self.
-            self.ptr = new_ptr as *const T;
-            self.cap = new_cap;
-            self.len += 1;
+impl<T, A: Allocator> IntoIterator for Vec<T, A> {
+    type Item = T;
+    type IntoIter = IntoIter<T, A>;
 
-            // drop old heap memory.
-            unsafe { dealloc(old_pointer, Layout::array::<T>(old_cap).unwrap()) };
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter::new(self)
+    }
+}
+
+impl<T> FromIterator<T> for Vec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut inner_iter = iter.into_iter();
+
+        let (size, _) = inner_iter.size_hint();
+        let mut new_vec = Vec::new();
+        new_vec.reserve(size);
+        while let Some(v) = inner_iter.next() {
+            new_vec.push(v)
         }
+
+        new_vec
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::alloc::LeakGuard;
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    /// Counts how many times it was dropped.
+    #[derive(Debug)]
+    struct DropCounter<'a> {
+        value: i32,
+        count: &'a Cell<usize>,
+    }
+
+    impl Drop for DropCounter<'_> {
+        fn drop(&mut self) {
+            self.count.set(self.count.get() + 1);
+        }
+    }
 
     #[test]
     fn test_new_1() {
+        let _leaks = LeakGuard::new();
         let a = 42;
         let v: Vec<i32> = Vec::new_1(a);
         let result = v.at(0);
@@ -132,6 +817,7 @@ mod test {
 
     #[test]
     fn test_push() {
+        let _leaks = LeakGuard::new();
         let mut v = Vec::new_1(0);
         v.push(1);
         v.push(2);
@@ -145,6 +831,7 @@ mod test {
 
     #[test]
     fn test_new() {
+        let _leaks = LeakGuard::new();
         let mut v: Vec<i32> = Vec::new();
         v.push(0);
         v.push(1);
@@ -153,4 +840,199 @@ mod test {
         assert_eq!(v.at(1), &1);
         assert_eq!(v.at(2), &2);
     }
+
+    #[test]
+    fn test_zero_sized_type() {
+        let _leaks = LeakGuard::new();
+        let mut v = Vec::new();
+        for _ in 0..100 {
+            v.push(());
+        }
+        assert_eq!(v.len(), 100);
+        assert_eq!(v.pop(), Some(()));
+        assert_eq!(v.len(), 99);
+    }
+
+    #[test]
+    fn test_drop_elements() {
+        let _leaks = LeakGuard::new();
+        let count = Cell::new(0);
+        {
+            let mut v = Vec::new();
+            for i in 0..10 {
+                v.push(DropCounter {
+                    value: i,
+                    count: &count,
+                });
+            }
+            v.truncate(4);
+            assert_eq!(count.get(), 6);
+        }
+        assert_eq!(count.get(), 10);
+    }
+
+    #[test]
+    fn test_clone_and_eq() {
+        let _leaks = LeakGuard::new();
+        let v = vec![String::from("a"), String::from("b")];
+        let cloned = v.clone();
+
+        assert_eq!(v, cloned);
+        assert_eq!(v, ["a", "b"]);
+        assert_eq!(v, &["a", "b"][..]);
+        assert_eq!(&["a", "b"][..], v);
+        assert_ne!(v, vec![String::from("a")]);
+    }
+
+    #[test]
+    fn test_ord_and_hash() {
+        let _leaks = LeakGuard::new();
+        use std::collections::HashSet;
+
+        assert!(vec![1, 2] < vec![1, 3]);
+        assert!(vec![1, 2] < vec![1, 2, 0]);
+        assert_eq!(vec![3, 2].cmp(&vec![3, 2]), Ordering::Equal);
+
+        let mut set = HashSet::new();
+        set.insert(vec![1, 2, 3]);
+        assert!(set.contains(&Vec::from([1, 2, 3])));
+        assert!(!set.contains(&Vec::from([3, 2, 1])));
+    }
+
+    #[test]
+    fn test_debug() {
+        let _leaks = LeakGuard::new();
+        assert_eq!(format!("{:?}", vec!["a", "b"]), r#"["a", "b"]"#);
+        assert_eq!(format!("{:?}", Vec::<i32>::default()), "[]");
+    }
+
+    #[test]
+    fn test_from() {
+        let _leaks = LeakGuard::new();
+        let v = Vec::from(&[1, 2, 3][..]);
+        assert_eq!(v, [1, 2, 3]);
+
+        let mut v = Vec::from(std::vec![String::from("a"), String::from("b")]);
+        v.push(String::from("c"));
+        assert_eq!(v, ["a", "b", "c"]);
+
+        let v = Vec::from(std::vec::Vec::<i32>::new());
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5, 6];
+        v.retain(|x| x % 2 == 0);
+        assert_eq!(v.as_slice(), &[2, 4, 6]);
+
+        let mut v = vec![1, 2, 3];
+        v.retain_mut(|x| {
+            *x *= 10;
+            *x != 20
+        });
+        assert_eq!(v.as_slice(), &[10, 30]);
+    }
+
+    #[test]
+    fn test_retain_predicate_panic() {
+        // no `LeakGuard`: the test harness allocates to capture the panic message.
+        let count = Cell::new(0);
+        let mut v = Vec::new();
+        for i in 0..6 {
+            v.push(DropCounter {
+                value: i,
+                count: &count,
+            });
+        }
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            v.retain(|x| {
+                if x.value == 3 {
+                    panic!("predicate panicked");
+                }
+                x.value % 2 == 0
+            })
+        }));
+        assert!(result.is_err());
+
+        // `1` was removed, and everything from `3` was kept as is.
+        assert_eq!(count.get(), 1);
+        let values = v.iter().map(|x| x.value).collect::<std::vec::Vec<_>>();
+        assert_eq!(values, [0, 2, 3, 4, 5]);
+
+        drop(v);
+        assert_eq!(count.get(), 6);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 1, 2, 3, 3, 3, 1, 4, 4];
+        v.dedup();
+        assert_eq!(v.as_slice(), &[1, 2, 3, 1, 4]);
+
+        let mut v = vec![10, 11, 20, 21, 22, 30];
+        v.dedup_by_key(|x| *x / 10);
+        assert_eq!(v.as_slice(), &[10, 20, 30]);
+
+        let mut v: Vec<i32> = vec![];
+        v.dedup();
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn test_sort() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![3, 1, 4, 1, 5, 9, 2, 6];
+        v.sort();
+        assert_eq!(v.as_slice(), &[1, 1, 2, 3, 4, 5, 6, 9]);
+
+        let mut v = vec![(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd')];
+        v.sort_by_key(|x| x.0);
+        assert_eq!(v.as_slice(), &[(0, 'b'), (0, 'd'), (1, 'a'), (1, 'c')]);
+
+        let mut v = vec![3, 1, 4, 1, 5];
+        v.sort_unstable_by(|a, b| b.cmp(a));
+        assert_eq!(v.as_slice(), &[5, 4, 3, 1, 1]);
+    }
+
+    #[test]
+    fn test_select_nth_unstable() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![9, 3, 7, 1, 5];
+        let (_, nth, _) = v.select_nth_unstable_by_key(1, |x| *x);
+        assert_eq!(*nth, 3);
+    }
+
+    #[test]
+    fn test_dedup_panic() {
+        // no `LeakGuard`: the test harness allocates to capture the panic message.
+        let count = Cell::new(0);
+        let mut v = Vec::new();
+        for i in [0, 0, 1, 1, 2, 2] {
+            v.push(DropCounter {
+                value: i,
+                count: &count,
+            });
+        }
+
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            v.dedup_by(|a, b| {
+                if a.value == 2 {
+                    panic!("same_bucket panicked");
+                }
+                a.value == b.value
+            })
+        }));
+        assert!(result.is_err());
+
+        assert_eq!(count.get(), 2);
+        let values = v.iter().map(|x| x.value).collect::<std::vec::Vec<_>>();
+        assert_eq!(values, [0, 1, 2, 2]);
+
+        drop(v);
+        assert_eq!(count.get(), 6);
+    }
 }
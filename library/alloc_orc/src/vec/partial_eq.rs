@@ -0,0 +1,15 @@
+use super::Vec;
+use crate::alloc::Allocator;
+
+use core_orc::impl_slice_eq;
+
+impl_slice_eq! { [A1: Allocator, A2: Allocator] Vec<T, A1>, Vec<U, A2> }
+impl_slice_eq! { [A: Allocator] Vec<T, A>, [U] }
+impl_slice_eq! { [A: Allocator] Vec<T, A>, &[U] }
+impl_slice_eq! { [A: Allocator] Vec<T, A>, &mut [U] }
+impl_slice_eq! { [A: Allocator] [T], Vec<U, A> }
+impl_slice_eq! { [A: Allocator] &[T], Vec<U, A> }
+impl_slice_eq! { [A: Allocator] &mut [T], Vec<U, A> }
+impl_slice_eq! { [A: Allocator] Vec<T, A>, std::vec::Vec<U> }
+impl_slice_eq! { [A: Allocator, const N: usize] Vec<T, A>, [U; N] }
+impl_slice_eq! { [A: Allocator, const N: usize] Vec<T, A>, &[U; N] }
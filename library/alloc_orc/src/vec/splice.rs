@@ -0,0 +1,143 @@
+use super::{Drain, Vec};
+use crate::alloc::{Allocator, Global};
+use core_orc::{into_iterator::IntoIterator, iter::traits::iterator::Iterator};
+
+/// An iterator which replaces a range of a `Vec` with another iterator.
+/// It is created by `Vec::splice`.
+///
+/// The drained elements are yielded by this iterator, and the replacement
+/// is done on drop.
+pub struct Splice<'a, I: Iterator + 'a, A: Allocator + 'a = Global> {
+    drain: Drain<'a, I::Item, A>,
+    replace_with: I,
+}
+
+impl<'a, I: Iterator, A: Allocator> Splice<'a, I, A> {
+    pub(super) fn new(drain: Drain<'a, I::Item, A>, replace_with: I) -> Self {
+        Splice {
+            drain,
+            replace_with,
+        }
+    }
+}
+
+impl<I: Iterator, A: Allocator> Iterator for Splice<'_, I, A> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.drain.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.drain.size_hint()
+    }
+}
+
+impl<I: Iterator, A: Allocator> Drop for Splice<'_, I, A> {
+    fn drop(&mut self) {
+        // exhaust the drained range first.
+        while self.drain.next().is_some() {}
+
+        // SAFETY:
+        // all the drained elements were yielded above.
+        unsafe {
+            if self.drain.tail_len == 0 {
+                // there is no tail, so we can just push to the vector.
+                let vec = self.drain.vec.as_mut();
+                while let Some(item) = self.replace_with.next() {
+                    vec.push(item);
+                }
+                return;
+            }
+
+            // first, fill the range left by `drain`.
+            if !self.drain.fill(&mut self.replace_with) {
+                return;
+            }
+
+            // there may be more elements, so use the lower bound as an estimate.
+            let (lower_bound, _) = self.replace_with.size_hint();
+            if lower_bound > 0 {
+                self.drain.move_tail(lower_bound);
+                if !self.drain.fill(&mut self.replace_with) {
+                    return;
+                }
+            }
+
+            // collect the rest, so that we know exactly how much room we need.
+            let mut collected = Vec::new();
+            while let Some(item) = self.replace_with.next() {
+                collected.push(item);
+            }
+            if !collected.is_empty() {
+                self.drain.move_tail(collected.len());
+                let mut collected = collected.into_iter();
+                let filled = self.drain.fill(&mut collected);
+                debug_assert!(filled);
+            }
+        }
+        // `Drain::drop` moves the tail back and restores `vec.len`.
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::Vec;
+    use crate::alloc::LeakGuard;
+    use core_orc::{into_iterator::IntoIterator, iter::traits::iterator::Iterator};
+
+    /// An iterator whose `size_hint` lies about its length.
+    struct NoHint<I>(I);
+
+    impl<I: Iterator> Iterator for NoHint<I> {
+        type Item = I::Item;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.next()
+        }
+    }
+
+    #[test]
+    fn test_splice() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5];
+        let removed = v.splice(1..3, vec![10, 20]).collect::<Vec<_>>();
+
+        assert_eq!(removed.as_slice(), &[2, 3]);
+        assert_eq!(v.as_slice(), &[1, 10, 20, 4, 5]);
+    }
+
+    #[test]
+    fn test_splice_shrink_and_grow() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3, 4, 5];
+        v.splice(1..4, vec![10]);
+        assert_eq!(v.as_slice(), &[1, 10, 5]);
+
+        let mut v = vec![1, 2, 3];
+        v.splice(1..2, vec![10, 20, 30, 40]);
+        assert_eq!(v.as_slice(), &[1, 10, 20, 30, 40, 3]);
+
+        let mut v = vec![1, 2, 3];
+        v.splice(3.., vec![4, 5]);
+        assert_eq!(v.as_slice(), &[1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_splice_without_size_hint() {
+        let _leaks = LeakGuard::new();
+        let mut v = vec![1, 2, 3];
+        v.splice(..1, NoHint(vec![7, 8, 9, 10].into_iter()));
+        assert_eq!(v.as_slice(), &[7, 8, 9, 10, 2, 3]);
+    }
+
+    #[test]
+    fn test_splice_forget() {
+        // no `LeakGuard`: forgetting `Splice` leaks `replace_with`.
+        let mut v = vec![1, 2, 3, 4];
+        std::mem::forget(v.splice(1..3, vec![10, 20, 30]));
+
+        // the tail is leaked, but the vector is still valid.
+        assert_eq!(v.as_slice(), &[1]);
+    }
+}
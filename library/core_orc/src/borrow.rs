@@ -0,0 +1,60 @@
+//! Traits for borrowing data from its owner.
+//!
+//! Unlike `AsRef`, `Borrow` requires the borrowed form to behave the same as
+//! the owned one, i.e. `Eq`, `Ord` and `Hash` must agree between the two. This
+//! is what lets `alloc_orc::borrow::Cow` compare and hash either form alike.
+//!
+//! Resources:
+//! * https://doc.rust-lang.org/std/borrow/index.html
+
+/// Borrows `Borrowed` from an owned value.
+pub trait Borrow<Borrowed: ?Sized> {
+    fn borrow(&self) -> &Borrowed;
+}
+
+/// Mutably borrows `Borrowed` from an owned value.
+pub trait BorrowMut<Borrowed: ?Sized>: Borrow<Borrowed> {
+    fn borrow_mut(&mut self) -> &mut Borrowed;
+}
+
+impl<T: ?Sized> Borrow<T> for T {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> BorrowMut<T> for T {
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for &T {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for &mut T {
+    fn borrow(&self) -> &T {
+        self
+    }
+}
+
+impl<T: ?Sized> BorrowMut<T> for &mut T {
+    fn borrow_mut(&mut self) -> &mut T {
+        self
+    }
+}
+
+impl<T, const N: usize> Borrow<[T]> for [T; N] {
+    fn borrow(&self) -> &[T] {
+        self
+    }
+}
+
+impl<T, const N: usize> BorrowMut<[T]> for [T; N] {
+    fn borrow_mut(&mut self) -> &mut [T] {
+        self
+    }
+}
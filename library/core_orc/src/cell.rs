@@ -1,31 +1,65 @@
+/// The primitive for interior mutability, which hands out `*mut T` from `&self`.
+///
+/// Sharing it between threads would be a data race, so it is `!Sync`. It can
+/// still be sent to another thread if `T` can.
+///
+/// ```compile_fail,E0277
+/// use core_orc::cell::UnsafeCell;
+///
+/// let cell = UnsafeCell::new(1);
+/// std::thread::scope(|s| {
+///     s.spawn(|| unsafe { *cell.get() = 2 });
+/// });
+/// ```
 pub struct UnsafeCell<T> {
-    value: T,
+    /// Only std's `UnsafeCell` tells the compiler that the value may change
+    /// behind a shared reference, and it is `!Sync` too.
+    value: std::cell::UnsafeCell<T>,
 }
 
 impl<T> UnsafeCell<T> {
     #[inline(always)]
     pub fn new(v: T) -> Self {
-        UnsafeCell { value: v }
+        UnsafeCell {
+            value: std::cell::UnsafeCell::new(v),
+        }
     }
 
     pub fn into_inner(self) -> T {
-        self.value
+        self.value.into_inner()
     }
 
     pub fn get(&self) -> *mut T {
-        self as *const UnsafeCell<T> as *const T as *mut T
+        self.value.get()
     }
 }
 
 impl<T: Clone> Clone for UnsafeCell<T> {
     fn clone(&self) -> Self {
-        UnsafeCell {
-            value: self.value.clone(),
-        }
+        // SAFETY:
+        // as for any read through `get`, the caller must not be mutating the
+        // value meanwhile.
+        UnsafeCell::new(unsafe { (*self.get()).clone() })
     }
 }
-impl<T: Copy> Copy for UnsafeCell<T> {}
 
+/// A mutable memory location, which is `Send` if `T` is, but never `Sync`.
+///
+/// ```
+/// use core_orc::cell::Cell;
+///
+/// let cell = Cell::new(1);
+/// std::thread::spawn(move || cell.set(2)).join().unwrap();
+/// ```
+///
+/// ```compile_fail,E0277
+/// use core_orc::cell::Cell;
+///
+/// let cell = Cell::new(1);
+/// std::thread::scope(|s| {
+///     s.spawn(|| cell.set(2));
+/// });
+/// ```
 pub struct Cell<T> {
     value: UnsafeCell<T>,
 }
@@ -46,7 +80,7 @@ impl<T> Cell<T> {
     }
 
     pub fn into_inner(self) -> T {
-        self.value.value
+        self.value.into_inner()
     }
 }
 
@@ -61,10 +95,11 @@ impl<T: Clone> Clone for Cell<T> {
 impl<T: Copy> Cell<T> {
     // Replicate inner value by clone, and return that value.
     pub fn get(&self) -> T {
-        self.value.value.clone()
+        // SAFETY:
+        // `Cell` never hands out references to the value.
+        unsafe { *self.value.get() }
     }
 }
-impl<T: Copy> Copy for Cell<T> {}
 
 #[derive(Copy, Clone)]
 enum RefState {
@@ -73,6 +108,24 @@ enum RefState {
     MutableRef,
 }
 
+/// A mutable memory location with dynamically checked borrow rules, which is
+/// `Send` if `T` is, but never `Sync`.
+///
+/// ```
+/// use core_orc::cell::RefCell;
+///
+/// let cell = RefCell::new(1);
+/// std::thread::spawn(move || *cell.borrow_mut() = 2).join().unwrap();
+/// ```
+///
+/// ```compile_fail,E0277
+/// use core_orc::cell::RefCell;
+///
+/// let cell = RefCell::new(1);
+/// std::thread::scope(|s| {
+///     s.spawn(|| *cell.borrow_mut() = 2);
+/// });
+/// ```
 pub struct RefCell<T> {
     value: UnsafeCell<T>,
     ref_state: Cell<RefState>,
@@ -86,19 +139,22 @@ impl<'b, T> RefCell<T> {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn borrow(&'b self) -> &'b T {
-        let next_state = match self.ref_state.into_inner() {
+        let next_state = match self.ref_state.get() {
             RefState::Initialized => RefState::ImmutableRef(1),
             RefState::ImmutableRef(n) => RefState::ImmutableRef(n + 1),
             RefState::MutableRef => panic!("err"),
         };
         self.ref_state.set(next_state);
 
-        &self.value.value
+        unsafe { &*self.value.get() }
     }
 
+    // TODO: return a guard type (like `RefMut`) instead of a bare `&mut T`.
+    #[allow(clippy::mut_from_ref)]
     pub fn borrow_mut(&'b self) -> &'b mut T {
-        let next_state = match self.ref_state.into_inner() {
+        let next_state = match self.ref_state.get() {
             RefState::Initialized => RefState::MutableRef,
             RefState::ImmutableRef(_) => panic!("errr"),
             RefState::MutableRef => panic!("errr"),
@@ -111,6 +167,6 @@ impl<'b, T> RefCell<T> {
 
 impl<T: PartialEq> PartialEq for RefCell<T> {
     fn eq(&self, other: &Self) -> bool {
-        self.value.value == other.value.value
+        unsafe { *self.value.get() == *other.value.get() }
     }
 }
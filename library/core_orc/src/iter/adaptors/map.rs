@@ -1,4 +1,4 @@
-use super::super::traits::iterator::Iterator;
+use super::super::traits::{double_ended::DoubleEndedIterator, iterator::Iterator};
 pub struct Map<A, F> {
     inner: A,
     f: F,
@@ -12,7 +12,7 @@ pub struct Map<A, F> {
 
 impl<A: Iterator, B, F: FnMut(A::Item) -> B> Map<A, F> {
     pub fn new(inner: A, f: F) -> Self {
-        Map { inner: inner, f: f }
+        Map { inner, f }
     }
 }
 
@@ -30,6 +30,12 @@ impl<A: Iterator, B, F: FnMut(A::Item) -> B> Iterator for Map<A, F> {
     }
 }
 
+impl<A: DoubleEndedIterator, B, F: FnMut(A::Item) -> B> DoubleEndedIterator for Map<A, F> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back().map(&mut self.f)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::{into_iterator::IntoIterator, iter::traits::iterator::Iterator};
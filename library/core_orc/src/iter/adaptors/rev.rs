@@ -0,0 +1,58 @@
+use super::super::traits::{double_ended::DoubleEndedIterator, iterator::Iterator};
+
+/// An iterator which yields the elements of `inner` in reverse order.
+/// It is created by `Iterator::rev`.
+pub struct Rev<I> {
+    inner: I,
+}
+
+impl<I: DoubleEndedIterator> Rev<I> {
+    pub fn new(inner: I) -> Self {
+        Rev { inner }
+    }
+}
+
+impl<I: DoubleEndedIterator> Iterator for Rev<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<I: DoubleEndedIterator> DoubleEndedIterator for Rev<I> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        into_iterator::IntoIterator,
+        iter::traits::{double_ended::DoubleEndedIterator, iterator::Iterator},
+        vec1::Vec1,
+    };
+
+    #[test]
+    fn test_rev() {
+        let v = Vec1::from([1, 2, 3, 4]);
+        let mut iter = v.into_iter().map(|x| x * 10).rev();
+
+        assert_eq!(iter.next(), Some(40));
+        assert_eq!(iter.next_back(), Some(10));
+        assert_eq!(iter.size_hint(), (2, Some(2)));
+        assert_eq!(iter.collect::<Vec1<_>>(), [30, 20]);
+    }
+
+    #[test]
+    fn test_rfold() {
+        let v = Vec1::from([String::from("a"), String::from("b"), String::from("c")]);
+        let s = v.into_iter().rfold(String::new(), |acc, x| acc + &x);
+        assert_eq!(s, "cba");
+    }
+}
@@ -0,0 +1,22 @@
+use super::iterator::Iterator;
+
+/// An iterator which can yield elements from both ends.
+///
+/// `next` and `next_back` take elements from the same range, so the
+/// iterator is exhausted when they meet in the middle.
+pub trait DoubleEndedIterator: Iterator {
+    fn next_back(&mut self) -> Option<Self::Item>;
+
+    /// Same as `fold`, but starts from the back.
+    fn rfold<B, F>(mut self, init: B, mut f: F) -> B
+    where
+        Self: Sized,
+        F: FnMut(B, Self::Item) -> B,
+    {
+        let mut acc = init;
+        while let Some(v) = self.next_back() {
+            acc = f(acc, v);
+        }
+        acc
+    }
+}
@@ -1,5 +1,6 @@
-use super::super::adaptors::map::Map;
+use super::super::adaptors::{map::Map, rev::Rev};
 use super::collector::FromIterator;
+use super::double_ended::DoubleEndedIterator;
 
 pub trait Iterator {
     type Item;
@@ -27,6 +28,13 @@ pub trait Iterator {
         Map::new(self, f)
     }
 
+    fn rev(self) -> Rev<Self>
+    where
+        Self: Sized + DoubleEndedIterator,
+    {
+        Rev::new(self)
+    }
+
     fn collect<B: FromIterator<Self::Item>>(self) -> B
     where
         Self: Sized,
@@ -1,2 +1,3 @@
 pub mod collector;
+pub mod double_ended;
 pub mod iterator;
@@ -1,5 +1,9 @@
+pub mod borrow;
 pub mod cell;
 pub mod into_iterator;
 pub mod iter;
 pub mod mem;
+pub mod pin;
+pub mod slice;
+pub mod str;
 pub mod vec1;
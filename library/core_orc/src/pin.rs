@@ -0,0 +1,344 @@
+//! Types which pin data to its location in memory.
+//!
+//! A `Pin<P>` wraps a pointer `P`, and guarantees that the pointee never
+//! moves (nor is invalidated) until it's dropped, unless it implements
+//! `Unpin`. This is what self-referential types (e.g. async state machines)
+//! rely on.
+//!
+//! `Unpin` and `PhantomPinned` are std's, since auto traits can't be defined
+//! on stable.
+//!
+//! Resources:
+//! * https://doc.rust-lang.org/std/pin/index.html
+//! * https://without.boats/blog/pin/
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+
+/// A pinned pointer.
+///
+/// ```compile_fail,E0277
+/// use core_orc::pin::Pin;
+/// use std::marker::PhantomPinned;
+///
+/// // `PhantomPinned` is not `Unpin`, so it can't be pinned safely.
+/// let mut p = PhantomPinned;
+/// let _ = Pin::new(&mut p);
+/// ```
+#[repr(transparent)]
+#[derive(Copy, Clone)]
+pub struct Pin<P> {
+    pointer: P,
+}
+
+impl<P: Deref<Target: Unpin>> Pin<P> {
+    /// Pins `pointer`. This is safe, since the pointee is `Unpin`.
+    pub fn new(pointer: P) -> Pin<P> {
+        // SAFETY:
+        // the pointee is `Unpin`, so it doesn't care whether it moves.
+        unsafe { Pin::new_unchecked(pointer) }
+    }
+
+    /// Unwraps the pointer.
+    pub fn into_inner(pin: Pin<P>) -> P {
+        pin.pointer
+    }
+}
+
+impl<P: Deref> Pin<P> {
+    /// Pins `pointer`, whose pointee may not be `Unpin`.
+    ///
+    /// # Safety
+    ///
+    /// The pointee must never be moved (nor invalidated without being
+    /// dropped) after this, even after the `Pin` is dropped. For example,
+    /// `Pin<&mut T>` of a local variable is unsound if the variable is moved
+    /// after the `Pin` is dropped.
+    pub unsafe fn new_unchecked(pointer: P) -> Pin<P> {
+        Pin { pointer }
+    }
+
+    /// Returns a pinned shared reference to the pointee.
+    pub fn as_ref(&self) -> Pin<&P::Target> {
+        // SAFETY:
+        // the pointee is pinned by `self`.
+        unsafe { Pin::new_unchecked(&*self.pointer) }
+    }
+
+    /// Unwraps the pointer.
+    ///
+    /// # Safety
+    ///
+    /// The pointee must still be treated as pinned.
+    pub unsafe fn into_inner_unchecked(pin: Pin<P>) -> P {
+        pin.pointer
+    }
+}
+
+impl<P: DerefMut> Pin<P> {
+    /// Returns a pinned mutable reference to the pointee.
+    pub fn as_mut(&mut self) -> Pin<&mut P::Target> {
+        // SAFETY:
+        // the pointee is pinned by `self`.
+        unsafe { Pin::new_unchecked(&mut *self.pointer) }
+    }
+
+    /// Replaces the pointee with `value`. The old value is dropped in place,
+    /// so it's never moved.
+    pub fn set(&mut self, value: P::Target)
+    where
+        P::Target: Sized,
+    {
+        *self.pointer = value;
+    }
+}
+
+impl<'a, T: ?Sized> Pin<&'a T> {
+    /// Returns the shared reference. This is safe, since the pointee can't
+    /// be moved out of a shared reference.
+    pub fn get_ref(self) -> &'a T {
+        self.pointer
+    }
+
+    /// Constructs a new pin by mapping the pointee, e.g. to a field.
+    ///
+    /// # Safety
+    ///
+    /// The returned data must not move as long as the argument doesn't move.
+    pub unsafe fn map_unchecked<U: ?Sized, F>(self, f: F) -> Pin<&'a U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        Pin::new_unchecked(f(self.pointer))
+    }
+}
+
+impl<'a, T: ?Sized> Pin<&'a mut T> {
+    /// Converts into a pinned shared reference.
+    pub fn into_ref(self) -> Pin<&'a T> {
+        Pin {
+            pointer: self.pointer,
+        }
+    }
+
+    /// Returns the mutable reference, which is safe only if `T` is `Unpin`.
+    ///
+    /// ```compile_fail,E0277
+    /// use core_orc::pin::Pin;
+    /// use std::marker::PhantomPinned;
+    ///
+    /// fn unpin(p: Pin<&mut PhantomPinned>) -> &mut PhantomPinned {
+    ///     p.get_mut()
+    /// }
+    /// ```
+    pub fn get_mut(self) -> &'a mut T
+    where
+        T: Unpin,
+    {
+        self.pointer
+    }
+
+    /// Returns the mutable reference.
+    ///
+    /// # Safety
+    ///
+    /// The pointee must not be moved out of the returned reference.
+    pub unsafe fn get_unchecked_mut(self) -> &'a mut T {
+        self.pointer
+    }
+
+    /// Constructs a new pin by mapping the pointee, e.g. to a field
+    /// (structural pinning).
+    ///
+    /// # Safety
+    ///
+    /// The returned data must not move as long as the argument doesn't move.
+    pub unsafe fn map_unchecked_mut<U: ?Sized, F>(self, f: F) -> Pin<&'a mut U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        Pin::new_unchecked(f(self.pointer))
+    }
+}
+
+impl<P: Deref> Deref for Pin<P> {
+    type Target = P::Target;
+
+    fn deref(&self) -> &P::Target {
+        &self.pointer
+    }
+}
+
+impl<P: DerefMut<Target: Unpin>> DerefMut for Pin<P> {
+    fn deref_mut(&mut self) -> &mut P::Target {
+        &mut self.pointer
+    }
+}
+
+impl<P: fmt::Debug> fmt::Debug for Pin<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.pointer, f)
+    }
+}
+
+impl<P: fmt::Display> fmt::Display for Pin<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.pointer, f)
+    }
+}
+
+/// Pins a value on the stack, returning `Pin<&mut T>`.
+///
+/// The value is moved into a temporary, which can't be named, so it can't be
+/// moved again. The temporary lives until the end of the enclosing
+/// statement, so the pin is meant to be passed on directly, e.g. to a
+/// function.
+///
+/// ```
+/// use core_orc::pin;
+/// use core_orc::pin::Pin;
+///
+/// fn exclaim(mut p: Pin<&mut String>) -> usize {
+///     p.as_mut().push('!');
+///     p.len()
+/// }
+///
+/// assert_eq!(exclaim(pin!(String::from("pinned"))), 7);
+/// ```
+///
+/// Unlike std's `pin!`, which extends the lifetime of the temporary through
+/// an unstable private field, it can't be bound by `let`.
+///
+/// ```compile_fail,E0716
+/// use core_orc::pin;
+///
+/// let p = pin!(String::from("pinned"));
+/// assert_eq!(p.len(), 6);
+/// ```
+#[macro_export]
+macro_rules! pin {
+    ($value:expr $(,)?) => {
+        // `$value` is evaluated outside of the `unsafe` block, so it can't
+        // use unsafe operations unchecked.
+        match &mut { $value } {
+            // SAFETY:
+            // the temporary can't be named, so it is never moved, and it's
+            // dropped in place.
+            pointer => unsafe { $crate::pin::Pin::<&mut _>::new_unchecked(pointer) },
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::marker::PhantomPinned;
+    use std::ptr;
+
+    /// A struct which holds a pointer to its own field.
+    ///
+    /// `ptr` is derived from a raw pointer to the whole struct, and `data` is
+    /// accessed only through it afterwards, so that no reference to `data`
+    /// invalidates it (see Stacked Borrows).
+    struct SelfRef {
+        data: String,
+        ptr: *mut String,
+        _pinned: PhantomPinned,
+    }
+
+    impl SelfRef {
+        fn new(data: &str) -> Self {
+            SelfRef {
+                data: String::from(data),
+                ptr: ptr::null_mut(),
+                _pinned: PhantomPinned,
+            }
+        }
+
+        fn init(this: Pin<&mut Self>) {
+            // SAFETY:
+            // nothing is moved out.
+            let this: *mut Self = unsafe { this.get_unchecked_mut() };
+            // SAFETY:
+            // `this` is valid, and no reference to `data` is created.
+            unsafe { (*this).ptr = ptr::addr_of_mut!((*this).data) };
+        }
+
+        fn data(this: Pin<&Self>) -> &str {
+            // SAFETY:
+            // `ptr` points to `data`, which never moves since `this` is pinned.
+            unsafe { &*this.get_ref().ptr }
+        }
+
+        fn data_mut(this: Pin<&mut Self>) -> Pin<&mut String> {
+            // SAFETY:
+            // `ptr` points to `data`, which is structurally pinned.
+            unsafe { Pin::new_unchecked(&mut *this.ptr) }
+        }
+    }
+
+    #[test]
+    fn test_new_and_unpin() {
+        let mut x = 1;
+        let mut p = Pin::new(&mut x);
+        *p = 2;
+        *p.as_mut() += 1;
+        assert_eq!(*p.as_ref(), 3);
+
+        let r = Pin::into_inner(p);
+        *r += 1;
+        assert_eq!(x, 4);
+    }
+
+    #[test]
+    fn test_pin_macro_self_referential() {
+        let check = |mut p: Pin<&mut SelfRef>| {
+            SelfRef::init(p.as_mut());
+            assert_eq!(SelfRef::data(p.as_ref()), "hello");
+
+            SelfRef::data_mut(p.as_mut()).push_str(" world");
+            assert_eq!(SelfRef::data(p.as_ref()), "hello world");
+            assert!(ptr::eq(p.ptr, ptr::addr_of!(p.data)));
+        };
+        check(crate::pin!(SelfRef::new("hello")));
+    }
+
+    #[test]
+    fn test_set() {
+        let check = |mut p: Pin<&mut SelfRef>| {
+            SelfRef::init(p.as_mut());
+            p.set(SelfRef::new("new"));
+            SelfRef::init(p.as_mut());
+            assert_eq!(SelfRef::data(p.into_ref()), "new");
+        };
+        check(crate::pin!(SelfRef::new("old")));
+    }
+
+    #[test]
+    fn test_map_unchecked_mut() {
+        struct Pair {
+            a: String,
+            _pinned: PhantomPinned,
+        }
+
+        let check = |p: Pin<&mut Pair>| {
+            // SAFETY:
+            // `a` is structurally pinned.
+            let mut a = unsafe { p.map_unchecked_mut(|p| &mut p.a) };
+            a.push('!');
+            assert_eq!(&*a, "a!");
+        };
+        check(crate::pin!(Pair {
+            a: String::from("a"),
+            _pinned: PhantomPinned,
+        }));
+    }
+
+    #[test]
+    fn test_get_mut() {
+        let mut s = String::from("a");
+        let p = Pin::new(&mut s);
+        p.get_mut().push('b');
+        assert_eq!(s, "ab");
+    }
+}
@@ -0,0 +1,86 @@
+//! Sorting and selection on slices.
+//!
+//! `[T]` is a foreign type, so these are free functions instead of methods.
+//! Collections like `Vec1` expose them as inherent methods.
+
+use std::cmp::Ordering;
+
+pub mod sort;
+
+/// Sorts the slice stably.
+pub fn sort<T: Ord>(v: &mut [T]) {
+    sort::stable::sort(v, &mut T::lt);
+}
+
+/// Sorts the slice stably with a comparator function.
+pub fn sort_by<T, F>(v: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sort::stable::sort(v, &mut |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice stably with a key extraction function.
+pub fn sort_by_key<T, K, F>(v: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort::stable::sort(v, &mut |a, b| f(a).lt(&f(b)));
+}
+
+/// Sorts the slice, but might not preserve the order of equal elements.
+pub fn sort_unstable<T: Ord>(v: &mut [T]) {
+    sort::unstable::sort(v, &mut T::lt);
+}
+
+/// Sorts the slice with a comparator function, but might not preserve the
+/// order of equal elements.
+pub fn sort_unstable_by<T, F>(v: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sort::unstable::sort(v, &mut |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts the slice with a key extraction function, but might not preserve
+/// the order of equal elements.
+pub fn sort_unstable_by_key<T, K, F>(v: &mut [T], mut f: F)
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort::unstable::sort(v, &mut |a, b| f(a).lt(&f(b)));
+}
+
+/// Reorders the slice so that the element at `index` is at its final sorted position.
+///
+/// Returns the elements before `index`, the element at `index`, and the elements after it.
+pub fn select_nth_unstable<T: Ord>(v: &mut [T], index: usize) -> (&mut [T], &mut T, &mut [T]) {
+    sort::select::partition_at_index(v, index, &mut T::lt)
+}
+
+/// Same as `select_nth_unstable`, with a comparator function.
+pub fn select_nth_unstable_by<T, F>(
+    v: &mut [T],
+    index: usize,
+    mut compare: F,
+) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    sort::select::partition_at_index(v, index, &mut |a, b| compare(a, b) == Ordering::Less)
+}
+
+/// Same as `select_nth_unstable`, with a key extraction function.
+pub fn select_nth_unstable_by_key<T, K, F>(
+    v: &mut [T],
+    index: usize,
+    mut f: F,
+) -> (&mut [T], &mut T, &mut [T])
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    sort::select::partition_at_index(v, index, &mut |a, b| f(a).lt(&f(b)))
+}
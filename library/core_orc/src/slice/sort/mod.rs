@@ -0,0 +1,7 @@
+// Resources:
+// https://github.com/rust-lang/rust/tree/master/library/core/src/slice/sort
+
+pub mod select;
+mod shared;
+pub mod stable;
+pub mod unstable;
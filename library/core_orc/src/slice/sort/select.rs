@@ -0,0 +1,178 @@
+//! Selection of the `index`-th smallest element (quickselect).
+
+use super::shared::{heapsort, insertion_sort_shift_left};
+use super::unstable::{choose_pivot, partition, partition_equal};
+use std::mem;
+
+/// Reorders `v` so that the element at `index` is at its final sorted
+/// position, every element before it is less than or equal to it, and every
+/// element after it is greater than or equal to it.
+///
+/// Returns the elements before `index`, the element at `index`, and the
+/// elements after it.
+///
+/// # Panics
+///
+/// Panics when `index >= v.len()`.
+pub fn partition_at_index<'a, T, F>(
+    v: &'a mut [T],
+    index: usize,
+    is_less: &mut F,
+) -> (&'a mut [T], &'a mut T, &'a mut [T])
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    assert!(
+        index < len,
+        "partition_at_index index {} greater than length of slice {}",
+        index,
+        len
+    );
+
+    if mem::size_of::<T>() == 0 {
+        // sorting has no meaningful behavior on zero-sized types.
+    } else if index == len - 1 {
+        // just find the maximum element, and place it at the last position.
+        let max = max_index(v, is_less);
+        v.swap(max, index);
+    } else if index == 0 {
+        // just find the minimum element, and place it at the first position.
+        let min = min_index(v, is_less);
+        v.swap(min, index);
+    } else {
+        partition_at_index_loop(v, index, is_less, None);
+    }
+
+    let (left, right) = v.split_at_mut(index);
+    let (pivot, right) = right.split_at_mut(1);
+    (left, &mut pivot[0], right)
+}
+
+fn partition_at_index_loop<'a, T, F>(
+    mut v: &'a mut [T],
+    mut index: usize,
+    is_less: &mut F,
+    mut pred: Option<&'a T>,
+) where
+    F: FnMut(&T, &T) -> bool,
+{
+    // slices of up to this length get sorted using insertion sort.
+    const MAX_INSERTION: usize = 10;
+
+    // limit the number of iterations, and fall back to heapsort to
+    // guarantee `O(n * log(n))`.
+    let mut limit = 16;
+
+    loop {
+        if v.len() <= MAX_INSERTION {
+            if v.len() >= 2 {
+                insertion_sort_shift_left(v, 1, is_less);
+            }
+            return;
+        }
+
+        if limit == 0 {
+            heapsort(v, is_less);
+            return;
+        }
+        limit -= 1;
+
+        let (pivot, _) = choose_pivot(v, is_less);
+
+        // if the chosen pivot is equal to the predecessor, it's the smallest
+        // element in the slice. This happens when there are many equal elements.
+        if let Some(p) = pred {
+            if !is_less(p, &v[pivot]) {
+                let mid = partition_equal(v, pivot, is_less);
+
+                // `index` is in the equal elements, so we're done.
+                if mid > index {
+                    return;
+                }
+
+                // otherwise, continue with the elements greater than the pivot.
+                v = &mut v[mid..];
+                index -= mid;
+                pred = None;
+                continue;
+            }
+        }
+
+        let (mid, _) = partition(v, pivot, is_less);
+
+        let (left, right) = v.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        if mid < index {
+            v = right;
+            index = index - mid - 1;
+            pred = Some(pivot);
+        } else if mid > index {
+            v = left;
+        } else {
+            // `partition` guarantees the pivot is at its final position.
+            return;
+        }
+    }
+}
+
+fn min_index<T, F>(v: &[T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut min = 0;
+    for i in 1..v.len() {
+        if is_less(&v[i], &v[min]) {
+            min = i;
+        }
+    }
+    min
+}
+
+fn max_index<T, F>(v: &[T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut max = 0;
+    for i in 1..v.len() {
+        if !is_less(&v[i], &v[max]) {
+            max = i;
+        }
+    }
+    max
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::shared::random_vec;
+    use super::*;
+
+    #[test]
+    fn test_partition_at_index() {
+        for len in [1, 2, 10, 11, 100, 1000] {
+            for modulus in [2, 10, u64::MAX] {
+                let original = random_vec(len, modulus, (len as u64 ^ modulus) | 1);
+                let mut sorted = original.clone();
+                sorted.sort();
+
+                for index in [0, len / 3, len / 2, len - 1] {
+                    let mut v = original.clone();
+                    let (left, nth, right) = partition_at_index(&mut v, index, &mut |a, b| a < b);
+
+                    assert_eq!(*nth, sorted[index]);
+                    assert!(left.iter().all(|x| x <= nth));
+                    assert!(right.iter().all(|x| x >= nth));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_partition_at_index_out_of_bounds() {
+        let mut v = [1, 2, 3];
+        partition_at_index(&mut v, 3, &mut |a, b| a < b);
+    }
+}
@@ -0,0 +1,221 @@
+//! Building blocks shared by the stable and the unstable sort.
+
+use std::mem::ManuallyDrop;
+use std::ptr;
+
+/// When dropped, copies from `src` into `dest`.
+///
+/// This is how a "hole" in a slice is filled even if a comparison panics,
+/// so that every element stays in the slice exactly once.
+pub(super) struct CopyOnDrop<T> {
+    pub(super) src: *const T,
+    pub(super) dest: *mut T,
+}
+
+impl<T> Drop for CopyOnDrop<T> {
+    fn drop(&mut self) {
+        // SAFETY:
+        // the owner guarantees that `src` is valid for reads and `dest` for writes.
+        unsafe { ptr::copy_nonoverlapping(self.src, self.dest, 1) };
+    }
+}
+
+/// Inserts `v[v.len() - 1]` into the sorted `v[..v.len() - 1]`, so that whole `v` becomes sorted.
+pub(super) fn insert_tail<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    debug_assert!(v.len() >= 2);
+
+    let arr = v.as_mut_ptr();
+    let i = v.len() - 1;
+
+    // SAFETY:
+    // all the pointers are in bounds, and `hole` always points at the slot
+    // which is logically empty.
+    unsafe {
+        let i_ptr = arr.add(i);
+        if !is_less(&*i_ptr, &*i_ptr.sub(1)) {
+            return;
+        }
+
+        // from now on, compare with `tmp`, since it is the value which is copied back.
+        let tmp = ManuallyDrop::new(ptr::read(i_ptr));
+        let mut hole = CopyOnDrop {
+            src: &*tmp,
+            dest: i_ptr.sub(1),
+        };
+        ptr::copy_nonoverlapping(hole.dest, i_ptr, 1);
+
+        for j in (0..i - 1).rev() {
+            let j_ptr = arr.add(j);
+            if !is_less(&*tmp, &*j_ptr) {
+                break;
+            }
+            ptr::copy_nonoverlapping(j_ptr, hole.dest, 1);
+            hole.dest = j_ptr;
+        }
+        // `hole` is dropped here, and copies `tmp` into the remaining hole.
+    }
+}
+
+/// Inserts `v[0]` into the sorted `v[1..]`, so that whole `v` becomes sorted.
+pub(super) fn insert_head<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    debug_assert!(v.len() >= 2);
+
+    let arr = v.as_mut_ptr();
+    let len = v.len();
+
+    // SAFETY:
+    // same as `insert_tail`.
+    unsafe {
+        if !is_less(&*arr.add(1), &*arr) {
+            return;
+        }
+
+        let tmp = ManuallyDrop::new(ptr::read(arr));
+        let mut hole = CopyOnDrop {
+            src: &*tmp,
+            dest: arr.add(1),
+        };
+        ptr::copy_nonoverlapping(hole.dest, arr, 1);
+
+        for j in 2..len {
+            let j_ptr = arr.add(j);
+            if !is_less(&*j_ptr, &*tmp) {
+                break;
+            }
+            ptr::copy_nonoverlapping(j_ptr, hole.dest, 1);
+            hole.dest = j_ptr;
+        }
+        // `hole` is dropped here, and copies `tmp` into the remaining hole.
+    }
+}
+
+/// Sorts `v` using insertion sort, assuming `v[..offset]` is already sorted.
+pub(super) fn insertion_sort_shift_left<T, F>(v: &mut [T], offset: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    debug_assert!(offset != 0 && offset <= v.len());
+
+    for i in offset..v.len() {
+        insert_tail(&mut v[..=i], is_less);
+    }
+}
+
+/// Sorts `v` using heapsort, which guarantees *O*(*n* \* log(*n*)) worst-case.
+///
+/// It only swaps elements, so a panic in `is_less` can't break the slice.
+pub(super) fn heapsort<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // This binary heap respects the invariant `parent >= child`.
+    let mut sift_down = |v: &mut [T], mut node: usize| loop {
+        let mut child = 2 * node + 1;
+        if child >= v.len() {
+            break;
+        }
+        // choose the greater child.
+        if child + 1 < v.len() && is_less(&v[child], &v[child + 1]) {
+            child += 1;
+        }
+        if !is_less(&v[node], &v[child]) {
+            break;
+        }
+        v.swap(node, child);
+        node = child;
+    };
+
+    // build the heap in linear time.
+    for i in (0..v.len() / 2).rev() {
+        sift_down(v, i);
+    }
+
+    // pop maximal elements from the heap.
+    for i in (1..v.len()).rev() {
+        v.swap(0, i);
+        sift_down(&mut v[..i], 0);
+    }
+}
+
+/// Returns `len` pseudo-random numbers below `modulus`, for the tests of the
+/// sorts.
+#[cfg(test)]
+pub(super) fn random_vec(len: usize, modulus: u64, mut seed: u64) -> std::vec::Vec<u64> {
+    (0..len)
+        .map(|_| {
+            // xorshift
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % modulus
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    type SortFn = fn(&mut [String], &mut dyn FnMut(&String, &String) -> bool);
+
+    #[test]
+    fn test_insert_tail_and_head() {
+        let mut v = [1, 3, 5, 7, 4];
+        insert_tail(&mut v, &mut |a, b| a < b);
+        assert_eq!(v, [1, 3, 4, 5, 7]);
+
+        let mut v = [6, 1, 3, 5, 7];
+        insert_head(&mut v, &mut |a, b| a < b);
+        assert_eq!(v, [1, 3, 5, 6, 7]);
+    }
+
+    #[test]
+    fn test_heapsort() {
+        let mut v = [5, 1, 4, 1, 5, 9, 2, 6, 5, 3];
+        heapsort(&mut v, &mut |a, b| a < b);
+        assert_eq!(v, [1, 1, 2, 3, 4, 5, 5, 5, 6, 9]);
+    }
+
+    #[test]
+    fn test_sort_comparator_panic() {
+        // `String` would be double-freed if an element got duplicated.
+        let original = random_vec(500, 1000, 7)
+            .into_iter()
+            .map(|x| x.to_string())
+            .collect::<std::vec::Vec<_>>();
+
+        let sorts: [SortFn; 2] = [
+            |v, is_less| super::super::stable::sort(v, &mut |a, b| is_less(a, b)),
+            |v, is_less| super::super::unstable::sort(v, &mut |a, b| is_less(a, b)),
+        ];
+        for sort in sorts {
+            for panic_at in [1, 10, 100, 1000, 2000] {
+                let mut v = original.clone();
+                let mut count = 0;
+                let result = catch_unwind(AssertUnwindSafe(|| {
+                    sort(&mut v, &mut |a, b| {
+                        count += 1;
+                        if count == panic_at {
+                            panic!("comparator panicked");
+                        }
+                        a < b
+                    })
+                }));
+                assert!(result.is_err());
+
+                // `v` is still a permutation of the original.
+                let mut expected = original.clone();
+                v.sort();
+                expected.sort();
+                assert_eq!(v, expected);
+            }
+        }
+    }
+}
@@ -0,0 +1,313 @@
+//! Stable sort.
+//!
+//! This is a natural merge sort: it finds already sorted runs in the input,
+//! extends the short ones with insertion sort, and merges them in a way
+//! that keeps the run stack balanced (like TimSort).
+//!
+//! Resources:
+//! * https://github.com/python/cpython/blob/main/Objects/listsort.txt
+//! * https://github.com/Voultapher/driftsort
+
+use super::shared::{insert_head, insertion_sort_shift_left};
+use std::alloc::{self, Layout};
+use std::mem;
+use std::ptr;
+
+/// Slices of up to this length get sorted using insertion sort.
+const MAX_INSERTION: usize = 20;
+
+/// Very short runs are extended using insertion sort to span at least this many elements.
+const MIN_RUN: usize = 10;
+
+/// Sorts `v` stably, i.e. equal elements keep their relative order.
+///
+/// If `is_less` panics, `v` still contains all of its elements exactly once,
+/// in an unspecified order.
+pub fn sort<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // sorting has no meaningful behavior on zero-sized types.
+    if mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    let len = v.len();
+    if len <= MAX_INSERTION {
+        if len >= 2 {
+            insertion_sort_shift_left(v, 1, is_less);
+        }
+        return;
+    }
+
+    // the shorter side of a merge is copied into this buffer, so half of `len` is enough.
+    let buf = BufGuard::new(len / 2);
+
+    let mut runs: std::vec::Vec<Run> = std::vec::Vec::new();
+    let mut end = len;
+    while end > 0 {
+        // find the next natural run, and reverse it if it's strictly descending.
+        // (strictly, so that reversing doesn't break stability)
+        let mut start = end - 1;
+        if start > 0 {
+            start -= 1;
+            if is_less(&v[start + 1], &v[start]) {
+                while start > 0 && is_less(&v[start], &v[start - 1]) {
+                    start -= 1;
+                }
+                v[start..end].reverse();
+            } else {
+                while start > 0 && !is_less(&v[start], &v[start - 1]) {
+                    start -= 1;
+                }
+            }
+        }
+
+        // insert some more elements into the run if it's too short.
+        while start > 0 && end - start < MIN_RUN {
+            start -= 1;
+            insert_head(&mut v[start..end], is_less);
+        }
+
+        runs.push(Run {
+            start,
+            len: end - start,
+        });
+        end = start;
+
+        // merge some pairs of adjacent runs to keep the stack balanced.
+        while let Some(r) = collapse(&runs) {
+            let left = runs[r + 1];
+            let right = runs[r];
+            // SAFETY:
+            // `buf` can hold `len / 2` elements, which is at least the shorter run.
+            unsafe {
+                merge(
+                    &mut v[left.start..right.start + right.len],
+                    left.len,
+                    buf.ptr,
+                    is_less,
+                );
+            }
+            runs[r] = Run {
+                start: left.start,
+                len: left.len + right.len,
+            };
+            runs.remove(r + 1);
+        }
+    }
+
+    debug_assert!(runs.len() == 1 && runs[0].start == 0 && runs[0].len == len);
+}
+
+#[derive(Clone, Copy)]
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+/// Examines the stack of runs and returns the index of the next pair of runs to merge.
+///
+/// The runs on the stack satisfy the following invariants when this returns `None`:
+/// 1. `runs[i].len > runs[i + 1].len`
+/// 2. `runs[i].len > runs[i + 1].len + runs[i + 2].len`
+///
+/// Checking the top four runs (not three) is needed for the invariants to hold
+/// for the whole stack; see https://www.envisage-project.eu/proving-android-java-and-python-sorting-algorithm-is-broken-and-how-to-fix-it/
+fn collapse(runs: &[Run]) -> Option<usize> {
+    let n = runs.len();
+    if n >= 2
+        && (runs[n - 1].start == 0
+            || runs[n - 2].len <= runs[n - 1].len
+            || (n >= 3 && runs[n - 3].len <= runs[n - 2].len + runs[n - 1].len)
+            || (n >= 4 && runs[n - 4].len <= runs[n - 3].len + runs[n - 2].len))
+    {
+        if n >= 3 && runs[n - 3].len < runs[n - 1].len {
+            Some(n - 3)
+        } else {
+            Some(n - 2)
+        }
+    } else {
+        None
+    }
+}
+
+/// Merges the sorted runs `v[..mid]` and `v[mid..]`, using `buf` as temporary storage.
+///
+/// # Safety
+///
+/// `buf` must be valid for writes of `min(mid, v.len() - mid)` elements, and
+/// must not overlap with `v`.
+unsafe fn merge<T, F>(v: &mut [T], mid: usize, buf: *mut T, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = v.len();
+    let v = v.as_mut_ptr();
+    let v_mid = v.add(mid);
+    let v_end = v.add(len);
+
+    // The shorter run is copied into `buf`, and then merged back into `v`.
+    // While merging, `hole` tracks the part of `buf` which is not merged yet,
+    // and where in `v` it has to go. If `is_less` panics, dropping `hole`
+    // copies the rest back, so no element is lost or duplicated.
+    let mut hole;
+
+    if mid <= len - mid {
+        // the left run is shorter, so merge forwards.
+        ptr::copy_nonoverlapping(v, buf, mid);
+        hole = MergeHole {
+            start: buf,
+            end: buf.add(mid),
+            dest: v,
+        };
+
+        let left = &mut hole.start;
+        let mut right = v_mid;
+        let out = &mut hole.dest;
+
+        while *left < hole.end && right < v_end {
+            // consume the lesser side. if equal, prefer the left run to keep stability.
+            let to_copy = if is_less(&*right, &**left) {
+                get_and_increment(&mut right)
+            } else {
+                get_and_increment(left)
+            };
+            ptr::copy_nonoverlapping(to_copy, get_and_increment(out), 1);
+        }
+    } else {
+        // the right run is shorter, so merge backwards.
+        ptr::copy_nonoverlapping(v_mid, buf, len - mid);
+        hole = MergeHole {
+            start: buf,
+            end: buf.add(len - mid),
+            dest: v_mid,
+        };
+
+        let left = &mut hole.dest;
+        let right = &mut hole.end;
+        let mut out = v_end;
+
+        while v < *left && buf < *right {
+            // consume the greater side. if equal, prefer the right run to keep stability.
+            let to_copy = if is_less(&*right.sub(1), &*left.sub(1)) {
+                decrement_and_get(left)
+            } else {
+                decrement_and_get(right)
+            };
+            ptr::copy_nonoverlapping(to_copy, decrement_and_get(&mut out), 1);
+        }
+    }
+    // `hole` is dropped here, and copies the unconsumed part of the shorter run into `v`.
+
+    unsafe fn get_and_increment<T>(ptr: &mut *mut T) -> *mut T {
+        let old = *ptr;
+        *ptr = ptr.add(1);
+        old
+    }
+
+    unsafe fn decrement_and_get<T>(ptr: &mut *mut T) -> *mut T {
+        *ptr = ptr.sub(1);
+        *ptr
+    }
+}
+
+/// When dropped, copies `start..end` to `dest`.
+struct MergeHole<T> {
+    start: *mut T,
+    end: *mut T,
+    dest: *mut T,
+}
+
+impl<T> Drop for MergeHole<T> {
+    fn drop(&mut self) {
+        // SAFETY:
+        // `start..end` is the unconsumed part of `buf`, and there is exactly
+        // that much room at `dest`.
+        unsafe {
+            let len = self.end.offset_from(self.start) as usize;
+            ptr::copy_nonoverlapping(self.start, self.dest, len);
+        }
+    }
+}
+
+/// Temporary buffer for merging, which is freed even if `is_less` panics.
+struct BufGuard<T> {
+    ptr: *mut T,
+    cap: usize,
+}
+
+impl<T> BufGuard<T> {
+    fn new(cap: usize) -> Self {
+        debug_assert!(cap != 0 && mem::size_of::<T>() != 0);
+
+        let layout = Layout::array::<T>(cap).unwrap();
+        // SAFETY:
+        // `layout` is not zero-sized.
+        let ptr = unsafe { alloc::alloc(layout) } as *mut T;
+        if ptr.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+
+        BufGuard { ptr, cap }
+    }
+}
+
+impl<T> Drop for BufGuard<T> {
+    fn drop(&mut self) {
+        // SAFETY:
+        // `ptr` was allocated in `BufGuard::new` with the same layout. The
+        // buffer never owns any element, so there's nothing to drop.
+        unsafe {
+            alloc::dealloc(self.ptr as *mut u8, Layout::array::<T>(self.cap).unwrap());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::shared::random_vec;
+    use super::*;
+
+    #[test]
+    fn test_sort() {
+        for len in [0, 1, 2, 10, 20, 21, 100, 1000] {
+            for modulus in [2, 10, 1000, u64::MAX] {
+                let mut v = random_vec(len, modulus, (len as u64 ^ modulus) | 1);
+                let mut expected = v.clone();
+                expected.sort();
+
+                sort(&mut v, &mut |a, b| a < b);
+                assert_eq!(v, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_patterns() {
+        let mut ascending = (0..500).collect::<std::vec::Vec<_>>();
+        sort(&mut ascending, &mut |a, b| a < b);
+        assert!(ascending.windows(2).all(|w| w[0] <= w[1]));
+
+        let mut descending = (0..500).rev().collect::<std::vec::Vec<_>>();
+        sort(&mut descending, &mut |a, b| a < b);
+        assert_eq!(descending, (0..500).collect::<std::vec::Vec<_>>());
+
+        let mut saw = (0..500).map(|x| x % 37).collect::<std::vec::Vec<_>>();
+        sort(&mut saw, &mut |a, b| a < b);
+        assert!(saw.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn test_sort_is_stable() {
+        // sort by the key only, and check the original indices are kept in order.
+        let keys = random_vec(1000, 10, 42);
+        let mut v = keys.iter().copied().zip(0..).collect::<std::vec::Vec<_>>();
+
+        sort(&mut v, &mut |a, b| a.0 < b.0);
+
+        assert!(v
+            .windows(2)
+            .all(|w| w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1)));
+    }
+}
@@ -0,0 +1,387 @@
+//! Unstable sort.
+//!
+//! This is pattern-defeating quicksort (pdqsort): a quicksort which falls
+//! back to heapsort on too many imbalanced partitions, and detects already
+//! sorted inputs and inputs with many equal elements.
+//!
+//! Everything except insertion sort moves elements only by swapping, so a
+//! panic in `is_less` leaves every element in the slice exactly once.
+//!
+//! Resources:
+//! * https://github.com/orlp/pdqsort
+//! * https://arxiv.org/abs/2106.05123
+
+use super::shared::{heapsort, insert_head, insert_tail, insertion_sort_shift_left};
+use std::mem;
+
+/// Slices of up to this length get sorted using insertion sort.
+const MAX_INSERTION: usize = 20;
+
+/// Sorts `v` without preserving the order of equal elements.
+pub fn sort<T, F>(v: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // sorting has no meaningful behavior on zero-sized types.
+    if mem::size_of::<T>() == 0 {
+        return;
+    }
+
+    // limit the number of imbalanced partitions to `floor(log2(len)) + 1`.
+    let limit = usize::BITS - v.len().leading_zeros();
+
+    recurse(v, is_less, None, limit);
+}
+
+/// Sorts `v` recursively.
+///
+/// `pred` is the pivot of the parent partition, which is less than or equal
+/// to every element of `v`. `limit` is the number of imbalanced partitions
+/// allowed before switching to heapsort.
+fn recurse<'a, T, F>(mut v: &'a mut [T], is_less: &mut F, mut pred: Option<&'a T>, mut limit: u32)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // true if the last partitioning was reasonably balanced.
+    let mut was_balanced = true;
+    // true if the last partitioning didn't move any element (the slice was already partitioned).
+    let mut was_partitioned = true;
+
+    loop {
+        let len = v.len();
+
+        if len <= MAX_INSERTION {
+            if len >= 2 {
+                insertion_sort_shift_left(v, 1, is_less);
+            }
+            return;
+        }
+
+        // too many bad pivot choices, so fall back to heapsort to guarantee `O(n * log(n))`.
+        if limit == 0 {
+            heapsort(v, is_less);
+            return;
+        }
+
+        // if the last partitioning was imbalanced, try breaking patterns in
+        // the slice by shuffling some elements around.
+        if !was_balanced {
+            break_patterns(v);
+            limit -= 1;
+        }
+
+        let (pivot, likely_sorted) = choose_pivot(v, is_less);
+
+        // if the last partitioning was balanced and didn't move elements, and
+        // pivot selection predicts the slice is likely already sorted, try
+        // fixing a few out-of-order elements with insertion sort.
+        if was_balanced && was_partitioned && likely_sorted && partial_insertion_sort(v, is_less) {
+            return;
+        }
+
+        // if the chosen pivot is equal to the predecessor, it's the smallest
+        // element in the slice. This happens when there are many equal elements.
+        if let Some(p) = pred {
+            if !is_less(p, &v[pivot]) {
+                let mid = partition_equal(v, pivot, is_less);
+
+                // continue sorting the elements greater than the pivot.
+                v = &mut v[mid..];
+                continue;
+            }
+        }
+
+        let (mid, was_p) = partition(v, pivot, is_less);
+        was_balanced = mid.min(len - mid) >= len / 8;
+        was_partitioned = was_p;
+
+        let (left, right) = v.split_at_mut(mid);
+        let (pivot, right) = right.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        // recurse into the shorter side only, and loop on the longer side,
+        // so that the recursion depth stays `O(log(n))`.
+        if left.len() < right.len() {
+            recurse(left, is_less, pred, limit);
+            v = right;
+            pred = Some(pivot);
+        } else {
+            recurse(right, is_less, Some(pivot), limit);
+            v = left;
+        }
+    }
+}
+
+/// Partitions `v` into elements smaller than `v[pivot]`, followed by the
+/// pivot, followed by elements greater than or equal to `v[pivot]`.
+///
+/// Returns the new index of the pivot, and `true` if `v` was already partitioned.
+pub(super) fn partition<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // place the pivot at the beginning of the slice.
+    v.swap(0, pivot);
+
+    let (mid, was_partitioned) = {
+        let (pivot, v) = v.split_at_mut(1);
+        let pivot = &pivot[0];
+
+        // INVARIANT: v[..l] < pivot <= v[r..]
+        let mut l = 0;
+        let mut r = v.len();
+
+        // find the first pair of out-of-order elements.
+        while l < r && is_less(&v[l], pivot) {
+            l += 1;
+        }
+        while l < r && !is_less(&v[r - 1], pivot) {
+            r -= 1;
+        }
+        let was_partitioned = l >= r;
+
+        loop {
+            while l < r && is_less(&v[l], pivot) {
+                l += 1;
+            }
+            while l < r && !is_less(&v[r - 1], pivot) {
+                r -= 1;
+            }
+            if l >= r {
+                break;
+            }
+            // `v[l] >= pivot` and `v[r - 1] < pivot`, so swap them.
+            r -= 1;
+            v.swap(l, r);
+            l += 1;
+        }
+
+        (l, was_partitioned)
+    };
+
+    // place the pivot between the two partitions.
+    v.swap(0, mid);
+
+    (mid, was_partitioned)
+}
+
+/// Partitions `v` into elements equal to `v[pivot]` followed by elements
+/// greater than `v[pivot]`, assuming `v` has no element smaller than the pivot.
+///
+/// Returns the number of elements equal to the pivot.
+pub(super) fn partition_equal<T, F>(v: &mut [T], pivot: usize, is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // place the pivot at the beginning of the slice.
+    v.swap(0, pivot);
+    let (pivot, v) = v.split_at_mut(1);
+    let pivot = &pivot[0];
+
+    // INVARIANT: v[..l] == pivot < v[r..]
+    let mut l = 0;
+    let mut r = v.len();
+    loop {
+        while l < r && !is_less(pivot, &v[l]) {
+            l += 1;
+        }
+        while l < r && is_less(pivot, &v[r - 1]) {
+            r -= 1;
+        }
+        if l >= r {
+            break;
+        }
+        r -= 1;
+        v.swap(l, r);
+        l += 1;
+    }
+
+    // add 1 to also account for the pivot.
+    l + 1
+}
+
+/// Chooses a pivot in `v`, and returns its index and `true` if the slice is
+/// likely already sorted.
+///
+/// Elements in `v` might be reordered in the process.
+pub(super) fn choose_pivot<T, F>(v: &mut [T], is_less: &mut F) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // slices of at least this length use the median of medians (ninther),
+    // and the shorter ones use the median of three.
+    const SHORTEST_MEDIAN_OF_MEDIANS: usize = 50;
+    // maximum number of swaps that can be performed in this function.
+    const MAX_SWAPS: usize = 4 * 3;
+
+    let len = v.len();
+
+    // three indices near which we are going to choose a pivot.
+    let mut a = len / 4;
+    let mut b = len / 4 * 2;
+    let mut c = len / 4 * 3;
+
+    // counts the total number of swaps of indices.
+    let mut swaps = 0;
+
+    if len >= 8 {
+        // swaps indices so that `v[a] <= v[b]`.
+        let mut sort2 = |a: &mut usize, b: &mut usize| {
+            if is_less(&v[*b], &v[*a]) {
+                mem::swap(a, b);
+                swaps += 1;
+            }
+        };
+
+        // swaps indices so that `v[a] <= v[b] <= v[c]`.
+        let mut sort3 = |a: &mut usize, b: &mut usize, c: &mut usize| {
+            sort2(a, b);
+            sort2(b, c);
+            sort2(a, b);
+        };
+
+        if len >= SHORTEST_MEDIAN_OF_MEDIANS {
+            // finds the median of `v[a - 1], v[a], v[a + 1]` and stores the index into `a`.
+            let mut sort_adjacent = |a: &mut usize| {
+                let tmp = *a;
+                sort3(&mut (tmp - 1), a, &mut (tmp + 1));
+            };
+
+            sort_adjacent(&mut a);
+            sort_adjacent(&mut b);
+            sort_adjacent(&mut c);
+        }
+
+        // find the median among `a`, `b`, and `c`.
+        sort3(&mut a, &mut b, &mut c);
+    }
+
+    if swaps < MAX_SWAPS {
+        (b, swaps == 0)
+    } else {
+        // the maximum number of swaps was performed, so the slice is likely
+        // descending. reversing it will probably help sort it faster.
+        v.reverse();
+        (len - 1 - b, true)
+    }
+}
+
+/// Scatters some elements around, so that patterns which cause imbalanced
+/// partitions are broken.
+fn break_patterns<T>(v: &mut [T]) {
+    let len = v.len();
+    if len < 8 {
+        return;
+    }
+
+    // pseudorandom number generator from the "Xorshift RNGs" paper by George Marsaglia.
+    let mut seed = len as u64;
+    let mut gen_usize = || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize
+    };
+
+    // take random numbers modulo this number, which is cheaper than `% len`.
+    let modulus = len.next_power_of_two();
+
+    // some pivot candidates will be in the nearby of this index, so randomize them.
+    let pos = len / 4 * 2;
+
+    for i in 0..3 {
+        // `other` is less than `2 * len`, so subtracting `len` once is enough.
+        let mut other = gen_usize() & (modulus - 1);
+        if other >= len {
+            other -= len;
+        }
+        v.swap(pos - 1 + i, other);
+    }
+}
+
+/// Partially sorts `v` by shifting several out-of-order elements around.
+///
+/// Returns `true` if the slice is sorted at the end.
+fn partial_insertion_sort<T, F>(v: &mut [T], is_less: &mut F) -> bool
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // maximum number of adjacent out-of-order pairs that will get shifted.
+    const MAX_STEPS: usize = 5;
+    // if the slice is shorter than this, don't shift any elements.
+    const SHORTEST_SHIFTING: usize = 50;
+
+    let len = v.len();
+    let mut i = 1;
+
+    for _ in 0..MAX_STEPS {
+        // find the next pair of adjacent out-of-order elements.
+        while i < len && !is_less(&v[i], &v[i - 1]) {
+            i += 1;
+        }
+
+        if i == len {
+            return true;
+        }
+
+        // shifting elements on short slices is not worth it.
+        if len < SHORTEST_SHIFTING {
+            return false;
+        }
+
+        // swap the found pair, which puts them in the correct order.
+        v.swap(i - 1, i);
+
+        if i >= 2 {
+            // shift the smaller element to the left.
+            insert_tail(&mut v[..i], is_less);
+        }
+        if len - i >= 2 {
+            // shift the greater element to the right.
+            insert_head(&mut v[i..], is_less);
+        }
+    }
+
+    // didn't manage to sort the slice in the limited number of steps.
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::shared::random_vec;
+    use super::*;
+
+    #[test]
+    fn test_sort() {
+        for len in [0, 1, 2, 10, 20, 21, 100, 1000] {
+            for modulus in [2, 10, 1000, u64::MAX] {
+                let mut v = random_vec(len, modulus, (len as u64 ^ modulus) | 1);
+                let mut expected = v.clone();
+                expected.sort();
+
+                sort(&mut v, &mut |a, b| a < b);
+                assert_eq!(v, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_patterns() {
+        let mut ascending = (0..500).collect::<std::vec::Vec<_>>();
+        sort(&mut ascending, &mut |a, b| a < b);
+        assert_eq!(ascending, (0..500).collect::<std::vec::Vec<_>>());
+
+        let mut descending = (0..500).rev().collect::<std::vec::Vec<_>>();
+        sort(&mut descending, &mut |a, b| a < b);
+        assert_eq!(descending, (0..500).collect::<std::vec::Vec<_>>());
+
+        let mut all_equal = [7; 500];
+        sort(&mut all_equal, &mut |a, b| a < b);
+        assert_eq!(all_equal, [7; 500]);
+
+        // organ pipe
+        let mut pipe = (0..250).chain((0..250).rev()).collect::<std::vec::Vec<_>>();
+        sort(&mut pipe, &mut |a, b| a < b);
+        assert!(pipe.windows(2).all(|w| w[0] <= w[1]));
+    }
+}
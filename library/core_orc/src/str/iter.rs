@@ -0,0 +1,122 @@
+use super::validations::{next_code_point, next_code_point_reverse};
+use crate::iter::traits::{double_ended::DoubleEndedIterator, iterator::Iterator};
+
+/// An iterator over the chars of a string slice, created by `str::chars`.
+#[derive(Clone, Debug)]
+pub struct Chars<'a> {
+    // always valid UTF-8, as only whole chars are removed from either end.
+    bytes: &'a [u8],
+}
+
+impl<'a> Chars<'a> {
+    pub(super) fn new(s: &'a str) -> Self {
+        Chars {
+            bytes: s.as_bytes(),
+        }
+    }
+
+    /// Returns the rest of the string, which is not yielded yet.
+    pub fn as_str(&self) -> &'a str {
+        // SAFETY:
+        // see `bytes`.
+        unsafe { std::str::from_utf8_unchecked(self.bytes) }
+    }
+}
+
+impl Iterator for Chars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let (ch, len) = next_code_point(self.bytes)?;
+        self.bytes = &self.bytes[len..];
+        Some(ch)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // a char takes 1 to 4 bytes.
+        (self.bytes.len().div_ceil(4), Some(self.bytes.len()))
+    }
+}
+
+impl DoubleEndedIterator for Chars<'_> {
+    fn next_back(&mut self) -> Option<char> {
+        let (ch, len) = next_code_point_reverse(self.bytes)?;
+        self.bytes = &self.bytes[..self.bytes.len() - len];
+        Some(ch)
+    }
+}
+
+/// An iterator over the chars of a string slice and their byte positions,
+/// created by `str::char_indices`.
+#[derive(Clone, Debug)]
+pub struct CharIndices<'a> {
+    front_offset: usize,
+    chars: Chars<'a>,
+}
+
+impl<'a> CharIndices<'a> {
+    pub(super) fn new(s: &'a str) -> Self {
+        CharIndices {
+            front_offset: 0,
+            chars: Chars::new(s),
+        }
+    }
+
+    /// Returns the rest of the string, which is not yielded yet.
+    pub fn as_str(&self) -> &'a str {
+        self.chars.as_str()
+    }
+}
+
+impl Iterator for CharIndices<'_> {
+    type Item = (usize, char);
+
+    fn next(&mut self) -> Option<(usize, char)> {
+        let ch = self.chars.next()?;
+        let index = self.front_offset;
+        self.front_offset += ch.len_utf8();
+        Some((index, ch))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chars.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for CharIndices<'_> {
+    fn next_back(&mut self) -> Option<(usize, char)> {
+        let ch = self.chars.next_back()?;
+        Some((self.front_offset + self.chars.bytes.len(), ch))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        iter::traits::{double_ended::DoubleEndedIterator, iterator::Iterator},
+        str::{char_indices, chars},
+        vec1::Vec1,
+    };
+
+    #[test]
+    fn chars_from_both_ends() {
+        let mut iter = chars("a\u{E9}\u{20AC}\u{1F600}b");
+        assert_eq!(iter.next(), Some('a'));
+        assert_eq!(iter.next_back(), Some('b'));
+        assert_eq!(iter.next_back(), Some('\u{1F600}'));
+        assert_eq!(iter.as_str(), "\u{E9}\u{20AC}");
+        assert_eq!(iter.size_hint(), (2, Some(5)));
+        assert_eq!(iter.collect::<Vec1<_>>(), ['\u{E9}', '\u{20AC}']);
+    }
+
+    #[test]
+    fn char_indices_from_both_ends() {
+        let mut iter = char_indices("a\u{E9}\u{20AC}b");
+        assert_eq!(iter.next(), Some((0, 'a')));
+        assert_eq!(iter.next_back(), Some((6, 'b')));
+        assert_eq!(iter.next_back(), Some((3, '\u{20AC}')));
+        assert_eq!(iter.next(), Some((1, '\u{E9}')));
+        assert_eq!(iter.next(), None);
+        assert_eq!(iter.next_back(), None);
+    }
+}
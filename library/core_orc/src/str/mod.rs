@@ -0,0 +1,44 @@
+//! UTF-8 validation and decoding of string slices.
+//!
+//! Like `[T]`, `str` is a foreign type, so these are free functions instead
+//! of methods. `alloc_orc::string::String` exposes them as inherent methods.
+//!
+//! Resources:
+//! * https://doc.rust-lang.org/std/str/index.html
+//! * https://datatracker.ietf.org/doc/html/rfc3629
+
+mod iter;
+mod validations;
+
+pub use iter::{CharIndices, Chars};
+pub use validations::Utf8Error;
+
+/// Converts a slice of bytes to a string slice, if it is valid UTF-8.
+///
+/// ```
+/// use core_orc::str::from_utf8;
+///
+/// assert_eq!(from_utf8(b"caf\xC3\xA9"), Ok("café"));
+///
+/// // `\xE9` is the start of a 3 bytes sequence, but `!` is no continuation
+/// // byte.
+/// let err = from_utf8(b"ok\xE9!").unwrap_err();
+/// assert_eq!(err.valid_up_to(), 2);
+/// assert_eq!(err.error_len(), Some(1));
+/// ```
+pub fn from_utf8(v: &[u8]) -> Result<&str, Utf8Error> {
+    validations::run_utf8_validation(v)?;
+    // SAFETY:
+    // validated right above.
+    Ok(unsafe { std::str::from_utf8_unchecked(v) })
+}
+
+/// Returns an iterator over the chars of `s`.
+pub fn chars(s: &str) -> Chars<'_> {
+    Chars::new(s)
+}
+
+/// Returns an iterator over the chars of `s` and their byte positions.
+pub fn char_indices(s: &str) -> CharIndices<'_> {
+    CharIndices::new(s)
+}
@@ -0,0 +1,217 @@
+use std::error::Error;
+use std::fmt;
+
+/// The error of `from_utf8`.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Utf8Error {
+    valid_up_to: usize,
+    error_len: Option<u8>,
+}
+
+impl Utf8Error {
+    /// Returns the length of the valid prefix of the input.
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+
+    /// Returns the length of the invalid sequence at `valid_up_to`, or `None`
+    /// if the input ends in the middle of a sequence which could still be
+    /// completed by more bytes.
+    pub fn error_len(&self) -> Option<usize> {
+        self.error_len.map(|len| len as usize)
+    }
+}
+
+impl fmt::Display for Utf8Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.error_len {
+            Some(len) => write!(
+                f,
+                "invalid utf-8 sequence of {} bytes from index {}",
+                len, self.valid_up_to
+            ),
+            None => write!(
+                f,
+                "incomplete utf-8 byte sequence from index {}",
+                self.valid_up_to
+            ),
+        }
+    }
+}
+
+impl Error for Utf8Error {}
+
+/// Returns the length of the sequence starting with `first`, or 0 if `first`
+/// can't start a sequence.
+pub(super) fn utf8_char_width(first: u8) -> usize {
+    match first {
+        0x00..=0x7F => 1,
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        // continuation bytes, overlong 2 bytes sequences and code points
+        // beyond U+10FFFF.
+        _ => 0,
+    }
+}
+
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+pub(super) fn run_utf8_validation(v: &[u8]) -> Result<(), Utf8Error> {
+    let mut index = 0;
+    while index < v.len() {
+        let start = index;
+        let err = |error_len| Utf8Error {
+            valid_up_to: start,
+            error_len,
+        };
+        // the byte at `start + n`, or the error of an incomplete sequence.
+        let next = |n: usize| v.get(start + n).copied().ok_or(err(None));
+
+        let first = v[start];
+        match utf8_char_width(first) {
+            1 => {}
+            2 => {
+                if !is_continuation(next(1)?) {
+                    return Err(err(Some(1)));
+                }
+            }
+            3 => {
+                // the second byte excludes overlong encodings (after `E0`)
+                // and surrogates (after `ED`).
+                match (first, next(1)?) {
+                    (0xE0, 0xA0..=0xBF)
+                    | (0xE1..=0xEC, 0x80..=0xBF)
+                    | (0xED, 0x80..=0x9F)
+                    | (0xEE..=0xEF, 0x80..=0xBF) => {}
+                    _ => return Err(err(Some(1))),
+                }
+                if !is_continuation(next(2)?) {
+                    return Err(err(Some(2)));
+                }
+            }
+            4 => {
+                // the second byte excludes overlong encodings (after `F0`)
+                // and code points beyond U+10FFFF (after `F4`).
+                match (first, next(1)?) {
+                    (0xF0, 0x90..=0xBF) | (0xF1..=0xF3, 0x80..=0xBF) | (0xF4, 0x80..=0x8F) => {}
+                    _ => return Err(err(Some(1))),
+                }
+                if !is_continuation(next(2)?) {
+                    return Err(err(Some(2)));
+                }
+                if !is_continuation(next(3)?) {
+                    return Err(err(Some(3)));
+                }
+            }
+            _ => return Err(err(Some(1))),
+        }
+        index = start + utf8_char_width(first);
+    }
+    Ok(())
+}
+
+/// Decodes the first char of `bytes`, which must be valid UTF-8, and returns
+/// it together with its length.
+pub(super) fn next_code_point(bytes: &[u8]) -> Option<(char, usize)> {
+    let first = *bytes.first()?;
+    let width = utf8_char_width(first);
+    if width == 1 {
+        return Some((first as char, 1));
+    }
+    // the leading byte keeps `7 - width` bits of the code point, and each
+    // continuation byte 6 more.
+    let mut code = (first & (0x7F >> width)) as u32;
+    for &byte in &bytes[1..width] {
+        code = (code << 6) | (byte & 0x3F) as u32;
+    }
+    // SAFETY:
+    // valid UTF-8 only encodes valid chars.
+    Some((unsafe { char::from_u32_unchecked(code) }, width))
+}
+
+/// Decodes the last char of `bytes`, which must be valid UTF-8, and returns
+/// it together with its length.
+pub(super) fn next_code_point_reverse(bytes: &[u8]) -> Option<(char, usize)> {
+    let mut start = bytes.len().checked_sub(1)?;
+    while is_continuation(bytes[start]) {
+        start -= 1;
+    }
+    next_code_point(&bytes[start..])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check(v: &[u8]) -> Result<(), (usize, Option<usize>)> {
+        run_utf8_validation(v).map_err(|e| (e.valid_up_to(), e.error_len()))
+    }
+
+    #[test]
+    fn valid_input_passes() {
+        let s = "a\u{7F}\u{80}\u{7FF}\u{800}\u{D7FF}\u{E000}\u{FFFF}\u{10000}\u{10FFFF}";
+        assert_eq!(check(s.as_bytes()), Ok(()));
+        assert_eq!(check(b""), Ok(()));
+    }
+
+    #[test]
+    fn errors_report_the_position_and_length() {
+        // lone continuation byte.
+        assert_eq!(check(b"ab\x80"), Err((2, Some(1))));
+        // overlong encodings.
+        assert_eq!(check(b"\xC0\x80"), Err((0, Some(1))));
+        assert_eq!(check(b"\xE0\x80\x80"), Err((0, Some(1))));
+        assert_eq!(check(b"\xF0\x80\x80\x80"), Err((0, Some(1))));
+        // surrogate U+D800.
+        assert_eq!(check(b"x\xED\xA0\x80"), Err((1, Some(1))));
+        // beyond U+10FFFF.
+        assert_eq!(check(b"\xF4\x90\x80\x80"), Err((0, Some(1))));
+        assert_eq!(check(b"\xF5\x80\x80\x80"), Err((0, Some(1))));
+        // the third and the fourth byte are not continuation bytes.
+        assert_eq!(check(b"\xE2\x82a"), Err((0, Some(2))));
+        assert_eq!(check(b"\xF0\x9F\x98a"), Err((0, Some(3))));
+    }
+
+    #[test]
+    fn truncated_sequence_has_no_error_len() {
+        assert_eq!(check(b"a\xE2\x82"), Err((1, None)));
+        assert_eq!(check(b"\xF0\x9F\x98"), Err((0, None)));
+        // invalid before the end, so it can't be completed.
+        assert_eq!(check(b"\xE2a"), Err((0, Some(1))));
+    }
+
+    #[test]
+    fn errors_agree_with_std() {
+        let inputs: [&[u8]; 6] = [
+            b"\xFFabc",
+            b"abc\xE2\x82",
+            b"\xED\xBF\xBF",
+            b"\xF0\x9F\x98\xC0",
+            b"\xC3",
+            b"ok \xF0\x9F\x98\x80 \xC3\x28",
+        ];
+        for input in inputs {
+            let std_err = std::str::from_utf8(input).unwrap_err();
+            assert_eq!(
+                check(input),
+                Err((std_err.valid_up_to(), std_err.error_len()))
+            );
+        }
+    }
+
+    #[test]
+    fn decodes_both_ends() {
+        let s = "a\u{E9}\u{20AC}\u{1F600}";
+        assert_eq!(next_code_point(s.as_bytes()), Some(('a', 1)));
+        assert_eq!(next_code_point(&s.as_bytes()[1..]), Some(('\u{E9}', 2)));
+        assert_eq!(next_code_point(&s.as_bytes()[3..]), Some(('\u{20AC}', 3)));
+        assert_eq!(
+            next_code_point_reverse(s.as_bytes()),
+            Some(('\u{1F600}', 4))
+        );
+        assert_eq!(next_code_point_reverse(b""), None);
+    }
+}
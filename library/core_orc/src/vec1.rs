@@ -3,25 +3,47 @@
 
 use crate::{
     into_iterator::IntoIterator,
-    iter::traits::{collector::FromIterator, iterator::Iterator},
+    iter::traits::{
+        collector::FromIterator, double_ended::DoubleEndedIterator, iterator::Iterator,
+    },
 };
 use std::alloc::{self, dealloc, Layout};
-use std::ptr;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::mem::{self, ManuallyDrop};
+use std::ops::{Deref, DerefMut};
+use std::ptr::{self, NonNull};
+use std::slice;
 
-#[derive(Debug)]
 pub struct Vec1<T> {
     ptr: *const T,
     len: usize,
     cap: usize,
 }
 
+// `Vec1` owns its elements, like `Vec`.
+unsafe impl<T: Send> Send for Vec1<T> {}
+unsafe impl<T: Sync> Sync for Vec1<T> {}
+
 impl<T> Vec1<T> {
-    #[inline(always)]
-    fn size_of_inner() -> usize {
-        std::mem::size_of::<T>()
+    /// Creates an empty `Vec1`, which doesn't allocate until the first `push`.
+    pub fn new() -> Self {
+        Vec1 {
+            ptr: NonNull::dangling().as_ptr(),
+            len: 0,
+            cap: 0,
+        }
     }
 
-    pub fn new_with_size_and_cap(len: usize, cap: usize) -> Self {
+    /// Allocates a buffer for `cap` elements, of which the first `len` are
+    /// regarded as initialized.
+    ///
+    /// # Safety
+    ///
+    /// The first `len` elements must be written before the `Vec1` is read or
+    /// dropped, as dropping it drops them.
+    pub unsafe fn new_with_size_and_cap(len: usize, cap: usize) -> Self {
         // For more detail, see https://doc.rust-lang.org/nomicon/vec/vec-alloc.html
         assert!(cap != 0, "cap == 0 is prohibited for now.");
 
@@ -33,8 +55,8 @@ impl<T> Vec1<T> {
 
         Vec1 {
             ptr: s as *const T,
-            len: len,
-            cap: cap,
+            len,
+            cap,
         }
     }
 
@@ -70,7 +92,7 @@ impl<T> Vec1<T> {
     }
 
     pub fn push(&mut self, x: T) {
-        if self.len + 1 <= self.cap {
+        if self.len < self.cap {
             // here, we don't need to allocate additional heap memory.
 
             let offset = (self.ptr as *mut T).wrapping_add(self.len);
@@ -82,7 +104,7 @@ impl<T> Vec1<T> {
             // here, we have to reallocate heap memory for this vec.
 
             // usually, it is common way to `double` its cap when we want to grow the vector's cap.
-            let new_cap = self.cap * 2;
+            let new_cap = if self.cap == 0 { 8 } else { self.cap * 2 };
             let layout = Layout::array::<T>(new_cap).unwrap();
             let new_ptr = unsafe { alloc::alloc(layout) };
 
@@ -103,31 +125,139 @@ impl<T> Vec1<T> {
             self.len += 1;
 
             // drop old heap memory.
-            unsafe { dealloc(old_pointer, Layout::array::<T>(old_cap).unwrap()) };
+            if old_cap != 0 {
+                unsafe { dealloc(old_pointer, Layout::array::<T>(old_cap).unwrap()) };
+            }
         }
     }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY:
+        // the first `len` elements are initialized.
+        unsafe { slice::from_raw_parts(self.ptr, self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY:
+        // the first `len` elements are initialized.
+        unsafe { slice::from_raw_parts_mut(self.ptr as *mut T, self.len) }
+    }
+}
+
+// sorting. See `crate::slice` for details.
+impl<T> Vec1<T> {
+    pub fn sort(&mut self)
+    where
+        T: Ord,
+    {
+        crate::slice::sort(self.as_mut_slice())
+    }
+
+    pub fn sort_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        crate::slice::sort_by(self.as_mut_slice(), compare)
+    }
+
+    pub fn sort_by_key<K, F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        crate::slice::sort_by_key(self.as_mut_slice(), f)
+    }
+
+    pub fn sort_unstable(&mut self)
+    where
+        T: Ord,
+    {
+        crate::slice::sort_unstable(self.as_mut_slice())
+    }
+
+    pub fn sort_unstable_by<F>(&mut self, compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        crate::slice::sort_unstable_by(self.as_mut_slice(), compare)
+    }
+
+    pub fn sort_unstable_by_key<K, F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        crate::slice::sort_unstable_by_key(self.as_mut_slice(), f)
+    }
+
+    pub fn select_nth_unstable(&mut self, index: usize) -> (&mut [T], &mut T, &mut [T])
+    where
+        T: Ord,
+    {
+        crate::slice::select_nth_unstable(self.as_mut_slice(), index)
+    }
+
+    pub fn select_nth_unstable_by<F>(
+        &mut self,
+        index: usize,
+        compare: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        crate::slice::select_nth_unstable_by(self.as_mut_slice(), index, compare)
+    }
+
+    pub fn select_nth_unstable_by_key<K, F>(
+        &mut self,
+        index: usize,
+        f: F,
+    ) -> (&mut [T], &mut T, &mut [T])
+    where
+        F: FnMut(&T) -> K,
+        K: Ord,
+    {
+        crate::slice::select_nth_unstable_by_key(self.as_mut_slice(), index, f)
+    }
 }
 
 /// it is Vec specific type, which impl `Iterator` trait.
 pub struct VecIntoIter<T> {
     ptr: *const T,
+    cap: usize,
     len: usize,
     cur_pos: usize,
 }
 
+// `VecIntoIter` owns the remaining elements, like `Vec1`.
+unsafe impl<T: Send> Send for VecIntoIter<T> {}
+unsafe impl<T: Sync> Sync for VecIntoIter<T> {}
+
 impl<T> VecIntoIter<T> {
     fn new(vec: Vec1<T>) -> Self {
+        // the buffer is owned by `VecIntoIter` from now on.
+        let vec = ManuallyDrop::new(vec);
+
         VecIntoIter {
             ptr: vec.ptr,
+            cap: vec.cap,
             len: vec.len,
             cur_pos: 0,
         }
     }
 }
 
-///
-/// trait implementation
-///
+//
+// trait implementation
+//
 
 impl<T> IntoIterator for Vec1<T> {
     type Item = T;
@@ -142,7 +272,7 @@ impl<T> Iterator for VecIntoIter<T> {
     type Item = T;
     fn next(&mut self) -> Option<Self::Item> {
         // boundary check
-        if self.cur_pos > self.len - 1 {
+        if self.cur_pos >= self.len {
             None
         } else {
             let next = unsafe { Some(ptr::read(self.ptr.add(self.cur_pos))) };
@@ -157,12 +287,180 @@ impl<T> Iterator for VecIntoIter<T> {
     }
 }
 
+impl<T> DoubleEndedIterator for VecIntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.cur_pos >= self.len {
+            None
+        } else {
+            self.len -= 1;
+            // SAFETY:
+            // the element at `len` is initialized, and never read again.
+            unsafe { Some(ptr::read(self.ptr.add(self.len))) }
+        }
+    }
+}
+
+impl<T> Drop for VecIntoIter<T> {
+    fn drop(&mut self) {
+        // SAFETY:
+        // `cur_pos..len` are initialized and not yet yielded, and the buffer
+        // was allocated with `cap`.
+        unsafe {
+            let remaining = self.ptr.add(self.cur_pos) as *mut T;
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(
+                remaining,
+                self.len - self.cur_pos,
+            ));
+            dealloc_buffer::<T>(self.ptr, self.cap);
+        }
+    }
+}
+
+impl<T> Drop for Vec1<T> {
+    fn drop(&mut self) {
+        // SAFETY:
+        // `0..len` are initialized, and the buffer was allocated with `cap`.
+        unsafe {
+            ptr::drop_in_place(self.as_mut_slice());
+            dealloc_buffer::<T>(self.ptr, self.cap);
+        }
+    }
+}
+
+/// # Safety
+///
+/// `ptr` must be allocated with `Layout::array::<T>(cap)`, unless `cap` is 0.
+unsafe fn dealloc_buffer<T>(ptr: *const T, cap: usize) {
+    if cap != 0 && mem::size_of::<T>() != 0 {
+        dealloc(ptr as *mut u8, Layout::array::<T>(cap).unwrap());
+    }
+}
+
+impl<T> Default for Vec1<T> {
+    fn default() -> Self {
+        Vec1::new()
+    }
+}
+
+impl<T> Deref for Vec1<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.as_slice()
+    }
+}
+
+impl<T> DerefMut for Vec1<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.as_mut_slice()
+    }
+}
+
+impl<T: Clone> Clone for Vec1<T> {
+    fn clone(&self) -> Self {
+        let mut new_vec = Vec1::new();
+        for v in self.as_slice() {
+            new_vec.push(v.clone());
+        }
+        new_vec
+    }
+}
+
+/// Prints the elements like a list, e.g. `[1, 2, 3]`.
+impl<T: fmt::Debug> fmt::Debug for Vec1<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+// Compares element-wise, so a vector can be compared with slices and arrays
+// of another element type as long as the elements are comparable. Exported
+// so that `alloc_orc` implements the same comparisons for its `Vec`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! impl_slice_eq {
+    ([$($vars:tt)*] $lhs:ty, $rhs:ty) => {
+        impl<T, U, $($vars)*> PartialEq<$rhs> for $lhs
+        where
+            T: PartialEq<U>,
+        {
+            fn eq(&self, other: &$rhs) -> bool {
+                self[..] == other[..]
+            }
+        }
+    };
+}
+
+impl_slice_eq! { [] Vec1<T>, Vec1<U> }
+impl_slice_eq! { [] Vec1<T>, [U] }
+impl_slice_eq! { [] Vec1<T>, &[U] }
+impl_slice_eq! { [] Vec1<T>, &mut [U] }
+impl_slice_eq! { [] [T], Vec1<U> }
+impl_slice_eq! { [] &[T], Vec1<U> }
+impl_slice_eq! { [] &mut [T], Vec1<U> }
+impl_slice_eq! { [const N: usize] Vec1<T>, [U; N] }
+impl_slice_eq! { [const N: usize] Vec1<T>, &[U; N] }
+
+impl<T: Eq> Eq for Vec1<T> {}
+
+/// Compares the elements lexicographically.
+impl<T: PartialOrd> PartialOrd for Vec1<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        PartialOrd::partial_cmp(self.as_slice(), other.as_slice())
+    }
+}
+
+impl<T: Ord> Ord for Vec1<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        Ord::cmp(self.as_slice(), other.as_slice())
+    }
+}
+
+/// Hashes the same as the slice of its elements.
+impl<T: Hash> Hash for Vec1<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Hash::hash(self.as_slice(), state)
+    }
+}
+
+impl<T, const N: usize> From<[T; N]> for Vec1<T> {
+    fn from(arr: [T; N]) -> Self {
+        let mut new_vec = Vec1::new();
+        for v in arr {
+            new_vec.push(v);
+        }
+        new_vec
+    }
+}
+
+impl<T: Clone> From<&[T]> for Vec1<T> {
+    fn from(s: &[T]) -> Self {
+        let mut new_vec = Vec1::new();
+        for v in s {
+            new_vec.push(v.clone());
+        }
+        new_vec
+    }
+}
+
+impl<T> From<std::vec::Vec<T>> for Vec1<T> {
+    fn from(v: std::vec::Vec<T>) -> Self {
+        let mut new_vec = Vec1::new();
+        for v in v {
+            new_vec.push(v);
+        }
+        new_vec
+    }
+}
+
 impl<A> FromIterator<A> for Vec1<A> {
     fn from_iter<T: IntoIterator<Item = A>>(iter: T) -> Self {
         let mut inner_iter = iter.into_iter();
 
         let (size, _) = inner_iter.size_hint();
-        let mut new_vec: Vec1<A> = Vec1::new_with_size_and_cap(size, size);
+        // SAFETY:
+        // the length is 0, so nothing has to be initialized.
+        let mut new_vec: Vec1<A> = unsafe { Vec1::new_with_size_and_cap(0, size.max(1)) };
         while let Some(v) = inner_iter.next() {
             new_vec.push(v)
         }
@@ -224,4 +522,88 @@ mod test {
 
         assert_eq!(result, 6);
     }
+
+    #[test]
+    fn test_new() {
+        let mut v: Vec1<i32> = Vec1::new();
+        assert!(v.is_empty());
+        assert!(v.clone().into_iter().next().is_none());
+
+        v.push(1);
+        v.push(2);
+        assert_eq!(v, [1, 2]);
+    }
+
+    #[test]
+    fn test_clone_and_eq() {
+        let v = Vec1::from([1, 2, 3]);
+        let cloned = v.clone();
+
+        assert_eq!(v, cloned);
+        assert_eq!(v, [1, 2, 3]);
+        assert_eq!(v, &[1, 2, 3][..]);
+        assert_eq!(&[1, 2, 3][..], v);
+        assert_ne!(v, Vec1::from([1, 2]));
+    }
+
+    #[test]
+    fn test_ord_and_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let (a, b, c) = (
+            Vec1::from([1, 2]),
+            Vec1::from([1, 3]),
+            Vec1::from([1, 2, 0]),
+        );
+        assert!(a < b);
+        assert!(a < c);
+        assert_eq!(b.cmp(&c), Ordering::Greater);
+
+        let hash = |x: &dyn Fn(&mut DefaultHasher)| {
+            let mut hasher = DefaultHasher::new();
+            x(&mut hasher);
+            hasher.finish()
+        };
+        let v = Vec1::from(vec![1, 2, 3]);
+        assert_eq!(hash(&|h| v.hash(h)), hash(&|h| [1, 2, 3][..].hash(h)));
+    }
+
+    #[test]
+    fn test_debug() {
+        let v = Vec1::from(&["a", "b"][..]);
+        assert_eq!(format!("{:?}", v), r#"["a", "b"]"#);
+        assert_eq!(format!("{:?}", Vec1::<i32>::default()), "[]");
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut v = Vec1::new_1(3);
+        v.push(1);
+        v.push(4);
+        v.push(1);
+        v.push(5);
+
+        v.sort();
+        assert_eq!(v.as_slice(), &[1, 1, 3, 4, 5]);
+
+        v.sort_by(|a, b| b.cmp(a));
+        assert_eq!(v.as_slice(), &[5, 4, 3, 1, 1]);
+
+        v.sort_unstable_by_key(|x| *x);
+        assert_eq!(v.as_slice(), &[1, 1, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_select_nth_unstable() {
+        let mut v = Vec1::new_1(5);
+        v.push(2);
+        v.push(4);
+        v.push(1);
+        v.push(3);
+
+        let (left, median, right) = v.select_nth_unstable(2);
+        assert_eq!(*median, 3);
+        assert!(left.iter().all(|x| *x < 3));
+        assert!(right.iter().all(|x| *x > 3));
+    }
 }
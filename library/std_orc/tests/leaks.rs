@@ -0,0 +1,101 @@
+// Checks that the collections of the workspace free everything they allocate,
+// by installing `TrackingAllocator` as the global allocator.
+use alloc_orc::alloc::{LeakGuard, TrackingAllocator};
+use alloc_orc::bocs::Bocs;
+use alloc_orc::rc::Rc;
+use core_orc::into_iterator::IntoIterator;
+use core_orc::iter::traits::iterator::Iterator;
+use core_orc::vec1::Vec1;
+use std_orc::vec;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator::new();
+
+#[test]
+fn vec1_does_not_leak() {
+    let _leaks = LeakGuard::new();
+
+    let mut v = Vec1::new();
+    for i in 0..100 {
+        v.push(String::from("x").repeat(i));
+    }
+    let cloned = v.clone();
+    assert_eq!(cloned.len(), 100);
+}
+
+#[test]
+fn vec1_into_iter_does_not_leak() {
+    let _leaks = LeakGuard::new();
+
+    let v = Vec1::from([String::from("a"), String::from("b"), String::from("c")]);
+    let mut iter = v.into_iter();
+    assert_eq!(iter.next().as_deref(), Some("a"));
+}
+
+#[test]
+fn collections_do_not_leak() {
+    let _leaks = LeakGuard::new();
+
+    let mut v = vec![String::from("a"); 10];
+    v.drain(2..5);
+    v.push(String::from("b"));
+    let mut iter = v.into_iter();
+    iter.next();
+
+    let b = Bocs::new(1);
+    assert_eq!(*b, 1);
+
+    let rc = Rc::new(vec![1, 2, 3]);
+    let _rc2 = rc.clone();
+}
+
+#[test]
+fn vec1_sort_and_select_do_not_leak() {
+    let _leaks = LeakGuard::new();
+
+    // the stable sort allocates a scratch buffer for long slices.
+    let mut v = Vec1::from((0..100).rev().collect::<Vec<_>>())
+        .into_iter()
+        .map(|i| i.to_string())
+        .collect::<Vec1<_>>();
+    v.sort();
+    v.sort_by_key(|s| s.len());
+    v.sort_unstable();
+    v.sort_unstable_by(|a, b| b.cmp(a));
+    let (_, nth, _) = v.select_nth_unstable(50);
+    assert_eq!(nth, "54");
+}
+
+#[test]
+fn vec1_from_iter_does_not_leak() {
+    let _leaks = LeakGuard::new();
+
+    let v = Vec1::from([1, 2, 3])
+        .into_iter()
+        .map(|i| i.to_string())
+        .collect::<Vec1<_>>();
+    assert_eq!(v.len(), 3);
+    let v = v.into_iter().collect::<Vec1<_>>();
+    assert_eq!(v.len(), 3);
+}
+
+#[test]
+fn vec_macro_does_not_leak() {
+    let _leaks = LeakGuard::new();
+
+    let v: alloc_orc::vec::Vec<String> = vec![];
+    assert!(v.is_empty());
+    let v = vec![String::from("a"), String::from("b")];
+    assert_eq!(v.len(), 2);
+    let v = vec![String::from("a"); 5];
+    assert_eq!(v.len(), 5);
+    let v = vec![String::from("a"); 0];
+    assert!(v.is_empty());
+}
+
+#[test]
+#[should_panic = "1 allocations"]
+fn leak_is_detected() {
+    let _leaks = LeakGuard::new();
+    std::mem::forget(Vec1::new_1(1));
+}
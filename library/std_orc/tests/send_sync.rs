@@ -0,0 +1,61 @@
+// Checks the `Send`/`Sync` contract of the types across the crates. The
+// negative cases (`Rc`, `Cell` and `RefCell` crossing threads) are
+// `compile_fail,E0277` doc tests on the types themselves.
+use alloc_orc::bocs::Bocs;
+use alloc_orc::rc::Rc;
+use alloc_orc::sync::Arc;
+use alloc_orc::vec::{Drain, IntoIter, Vec};
+use core_orc::cell::{Cell, RefCell, UnsafeCell};
+use core_orc::vec1::{Vec1, VecIntoIter};
+use std::thread;
+
+fn assert_send<T: Send>() {}
+fn assert_sync<T: Sync>() {}
+
+#[test]
+fn owning_types_are_send_and_sync() {
+    assert_send::<Vec<String>>();
+    assert_sync::<Vec<String>>();
+    assert_send::<IntoIter<String>>();
+    assert_sync::<IntoIter<String>>();
+    assert_send::<Drain<'static, String>>();
+    assert_sync::<Drain<'static, String>>();
+    assert_send::<Bocs<String>>();
+    assert_sync::<Bocs<String>>();
+    assert_send::<Bocs<dyn Fn() + Send>>();
+    assert_send::<Vec1<String>>();
+    assert_sync::<Vec1<String>>();
+    assert_send::<VecIntoIter<String>>();
+    assert_sync::<VecIntoIter<String>>();
+    assert_send::<Arc<String>>();
+    assert_sync::<Arc<String>>();
+    assert_send::<alloc_orc::sync::Weak<String>>();
+    assert_sync::<alloc_orc::sync::Weak<String>>();
+}
+
+#[test]
+fn cells_are_send_but_not_sync() {
+    assert_send::<UnsafeCell<String>>();
+    assert_send::<Cell<i32>>();
+    assert_send::<RefCell<String>>();
+    // unlike `Arc<RefCell<T>>`, the `Bocs` owns the cell.
+    assert_send::<Bocs<RefCell<String>>>();
+}
+
+#[test]
+fn values_cross_threads() {
+    let v: Vec<i32> = std_orc::vec![1, 2, 3];
+    let b = Bocs::new(String::from("bocs"));
+    let cell = Cell::new(0);
+    let len = thread::spawn(move || {
+        cell.set(v.len());
+        cell.get() + b.len()
+    })
+    .join()
+    .unwrap();
+    assert_eq!(len, 7);
+
+    // an `Rc` is used where it was created.
+    let rc = Rc::new(1);
+    assert_eq!(*rc, 1);
+}
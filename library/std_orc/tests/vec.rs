@@ -0,0 +1,18 @@
+// `vec!` is re-exported along with the `vec` module, and expands to
+// `alloc_orc::vec::Vec` even though std's `Vec` is in scope here.
+use std_orc::vec;
+
+#[test]
+fn vec_macro_from_downstream_crate() {
+    let v: std_orc::vec::Vec<i32> = vec![1, 2, 3];
+    assert_eq!(v, [1, 2, 3]);
+
+    let v: std_orc::vec::Vec<i32> = vec![];
+    assert!(v.is_empty());
+}
+
+#[test]
+fn vec_macro_repeat_form() {
+    let v: std_orc::vec::Vec<String> = vec![String::from("x"); 2];
+    assert_eq!(v, ["x", "x"]);
+}